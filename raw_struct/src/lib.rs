@@ -0,0 +1,830 @@
+// RawStruct derive が生成するコードから参照される実行時サポート
+pub use raw_struct_macro::{EnumNames, RawStruct};
+
+// 生成コードが利用側のクレートの依存関係に頼らず csv を参照できるようにする
+#[doc(hidden)]
+pub use csv;
+#[doc(hidden)]
+pub use regex;
+
+// feature で有効にした指定の生成コードが使うクレートも同じく再エクスポートする
+#[cfg(feature = "chrono")]
+#[doc(hidden)]
+pub use chrono;
+#[cfg(feature = "timezone")]
+#[doc(hidden)]
+pub use chrono_tz;
+#[cfg(feature = "checksum")]
+#[doc(hidden)]
+pub use crc32fast;
+#[cfg(feature = "glob")]
+#[doc(hidden)]
+pub use glob;
+#[cfg(feature = "phone")]
+#[doc(hidden)]
+pub use phonenumber;
+#[cfg(feature = "semver")]
+#[doc(hidden)]
+pub use semver;
+#[cfg(feature = "uuid")]
+#[doc(hidden)]
+pub use uuid;
+#[cfg(feature = "graphemes")]
+#[doc(hidden)]
+pub use unicode_segmentation;
+#[cfg(feature = "width")]
+#[doc(hidden)]
+pub use unicode_width;
+#[cfg(feature = "unicode")]
+#[doc(hidden)]
+pub use unicode_normalization;
+
+/// RawStruct を derive した構造体と、生成された生データ型を結びつけるトレイト。
+///
+/// ジェネリックなコードから `<Record as HasRaw>::Raw` として生データ型を参照できる。
+pub trait HasRaw {
+    type Raw;
+}
+
+/// 生データ型のフィールドを型を知らずに列挙するためのトレイト。
+///
+/// RawStruct が生成する生データ型に実装され、汎用的なログ出力や差分表示などに使える。
+pub trait HasFields {
+    /// (フィールド名, 解析前の値) の組を宣言順に返す。
+    fn fields(&self) -> impl Iterator<Item = (&'static str, Option<&str>)>;
+}
+
+/// 列挙型のバリアントを名前から得るためのトレイト。
+///
+/// `#[validate(enum_name = "Status")]` を指定したフィールドの検証と変換に使う。
+/// ユニットバリアントだけの列挙型には `#[derive(EnumNames)]` で実装できる。
+pub trait EnumNames: Sized {
+    /// バリアント名の一覧 (宣言順)。
+    const NAMES: &'static [&'static str];
+
+    /// 名前が完全に一致するバリアントを返す。
+    fn from_name(name: &str) -> Option<Self>;
+
+    /// 英字の大文字・小文字を区別せずに名前が一致するバリアントを返す。
+    fn from_name_ignore_case(name: &str) -> Option<Self> {
+        Self::NAMES
+            .iter()
+            .find(|candidate| candidate.eq_ignore_ascii_case(name))
+            .and_then(|candidate| Self::from_name(candidate))
+    }
+}
+
+/// 数字の並びが Luhn アルゴリズムのチェックディジットを満たすかを判定する。
+///
+/// 空白やハイフンなど数字以外の文字は無視する。数字が1つもない場合は `false` を返す。
+pub fn luhn_valid(value: &str) -> bool {
+    let digits = value.chars().filter_map(|c| c.to_digit(10)).collect::<Vec<_>>();
+    if digits.is_empty() {
+        return false;
+    }
+    let sum: u32 = digits
+        .iter()
+        .rev()
+        .enumerate()
+        .map(|(i, &digit)| {
+            if !i.is_multiple_of(2) {
+                let doubled = digit * 2;
+                if doubled > 9 { doubled - 9 } else { doubled }
+            } else {
+                digit
+            }
+        })
+        .sum();
+    sum.is_multiple_of(10)
+}
+
+/// IBAN の検証に失敗した理由。
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum IbanError {
+    /// 書式が正しくないか、国コードに対して桁数が合わない。
+    Format,
+    /// 書式は正しいが mod-97 のチェックサムが一致しない。
+    Checksum,
+}
+
+// 国コードごとの IBAN の桁数 (SWIFT の IBAN Registry による)
+const IBAN_LENGTHS: &[(&str, usize)] = &[
+    ("AD", 24), ("AE", 23), ("AL", 28), ("AT", 20), ("AZ", 28), ("BA", 20), ("BE", 16), ("BG", 22),
+    ("BH", 22), ("BI", 27), ("BR", 29), ("BY", 28), ("CH", 21), ("CR", 22), ("CY", 28), ("CZ", 24),
+    ("DE", 22), ("DJ", 27), ("DK", 18), ("DO", 28), ("EE", 20), ("EG", 29), ("ES", 24), ("FI", 18),
+    ("FK", 18), ("FO", 18), ("FR", 27), ("GB", 22), ("GE", 22), ("GI", 23), ("GL", 18), ("GR", 27),
+    ("GT", 28), ("HR", 21), ("HU", 28), ("IE", 22), ("IL", 23), ("IQ", 23), ("IS", 26), ("IT", 27),
+    ("JO", 30), ("KW", 30), ("KZ", 20), ("LB", 28), ("LC", 32), ("LI", 21), ("LT", 20), ("LU", 20),
+    ("LV", 21), ("LY", 25), ("MC", 27), ("MD", 24), ("ME", 22), ("MK", 19), ("MN", 20), ("MR", 27),
+    ("MT", 31), ("MU", 30), ("NI", 28), ("NL", 18), ("NO", 15), ("OM", 23), ("PK", 24), ("PL", 28),
+    ("PS", 29), ("PT", 25), ("QA", 29), ("RO", 24), ("RS", 22), ("RU", 33), ("SA", 24), ("SC", 31),
+    ("SD", 18), ("SE", 24), ("SI", 19), ("SK", 24), ("SM", 27), ("SO", 23), ("ST", 25), ("SV", 28),
+    ("TL", 23), ("TN", 24), ("TR", 26), ("UA", 29), ("VA", 22), ("VG", 24), ("XK", 20), ("YE", 30),
+];
+
+/// 文字列が IBAN (国際銀行口座番号) として正しいかを検証する。
+///
+/// 印字用の4文字ごとの空白は無視し、英字の大文字・小文字は区別しない。
+/// 国コードごとの桁数と ISO 7064 の mod-97 チェックサムを確認する。
+pub fn iban_check(value: &str) -> Result<(), IbanError> {
+    let iban = value
+        .chars()
+        .filter(|c| *c != ' ')
+        .map(|c| c.to_ascii_uppercase())
+        .collect::<String>();
+    if !iban.bytes().all(|b| b.is_ascii_alphanumeric()) {
+        return Err(IbanError::Format);
+    }
+    let expected_len = iban
+        .get(..2)
+        .and_then(|country| IBAN_LENGTHS.iter().find(|(code, _)| *code == country))
+        .map(|(_, len)| *len)
+        .ok_or(IbanError::Format)?;
+    if iban.len() != expected_len || !iban.as_bytes()[2..4].iter().all(u8::is_ascii_digit) {
+        return Err(IbanError::Format);
+    }
+    // 先頭4文字を末尾に回し、英字を 10 ～ 35 の数に置き換えた数を 97 で割った余りが 1 になる
+    let remainder = iban[4..]
+        .chars()
+        .chain(iban[..4].chars())
+        .fold(0u32, |acc, c| {
+            let digit = c.to_digit(36).unwrap();
+            if digit < 10 { (acc * 10 + digit) % 97 } else { (acc * 100 + digit) % 97 }
+        });
+    if remainder == 1 { Ok(()) } else { Err(IbanError::Checksum) }
+}
+
+/// 文字列が RFC 1123 のホスト名として正しいかを判定する。
+///
+/// 全体で 253 文字以内、各ラベルが 1 ～ 63 文字の英数字とハイフンで、
+/// ラベルの先頭と末尾がハイフンでないことを確認する。末尾のドット1つは許可する。
+pub fn hostname_valid(value: &str) -> bool {
+    let value = value.strip_suffix('.').unwrap_or(value);
+    if value.is_empty() || value.len() > 253 {
+        return false;
+    }
+    value.split('.').all(|label| {
+        !label.is_empty()
+            && label.len() <= 63
+            && !label.starts_with('-')
+            && !label.ends_with('-')
+            && label.bytes().all(|b| b.is_ascii_alphanumeric() || b == b'-')
+    })
+}
+
+/// フィールドのエラーを先頭の `max` 件までに減らし、省いた件数を知らせるエラーを末尾に足す。
+///
+/// 省いた件数は "too_many_errors" のエラーの "omitted" パラメータに入れる。
+/// すでにこのエラーがある場合は件数を足し合わせるため、何度呼んでも結果は変わらない。
+/// derive(RawStruct) の validate(max_errors = N) から呼ばれる。
+pub fn cap_field_errors(errors: &mut validator::ValidationErrors, key: &str, max: usize) {
+    let Some(validator::ValidationErrorsKind::Field(errs)) = errors.errors_mut().get_mut(key) else {
+        return;
+    };
+    let mut omitted = match errs.iter().position(|err| err.code == "too_many_errors") {
+        Some(pos) => errs.remove(pos).params.get("omitted").and_then(|v| v.as_u64()).unwrap_or(0) as usize,
+        None => 0,
+    };
+    if errs.len() > max {
+        omitted += errs.len() - max;
+        errs.truncate(max);
+    }
+    if omitted > 0 {
+        let mut err = validator::ValidationError::new("too_many_errors");
+        err.add_param("omitted".into(), &omitted);
+        err.message = Some(format!("フィールド '{}' には他に {} 件のエラーがあります", key, omitted).into());
+        errs.push(err);
+    }
+}
+
+/// 検証エラーを (キー, エラー一覧) の組に平らにして返す。
+///
+/// validator の `field_errors()` は入れ子のエラーを含まないため、flatten したフィールドの子のエラーは
+/// "home.city" のように親のキーと "." でつないだキーで返す。リストのエラーは "items[0].name" のようにする。
+pub fn flat_field_errors(errors: &validator::ValidationErrors) -> Vec<(String, &[validator::ValidationError])> {
+    fn walk<'a>(errors: &'a validator::ValidationErrors, prefix: &str, flat: &mut Vec<(String, &'a [validator::ValidationError])>) {
+        for (key, kind) in errors.errors() {
+            let key = format!("{}{}", prefix, key);
+            match kind {
+                validator::ValidationErrorsKind::Field(errs) => flat.push((key, errs.as_slice())),
+                validator::ValidationErrorsKind::Struct(child) => walk(child, &format!("{}.", key), flat),
+                validator::ValidationErrorsKind::List(children) => {
+                    for (index, child) in children {
+                        walk(child, &format!("{}[{}].", key, index), flat);
+                    }
+                },
+            }
+        }
+    }
+
+    let mut flat = Vec::new();
+    walk(errors, "", &mut flat);
+    flat
+}
+
+/// 入れ子のエラーも含めたすべてのフィールドのエラーに f を適用する。
+pub fn for_each_error_mut(errors: &mut validator::ValidationErrors, f: &mut dyn FnMut(&mut validator::ValidationError)) {
+    for kind in errors.errors_mut().values_mut() {
+        match kind {
+            validator::ValidationErrorsKind::Field(errs) => errs.iter_mut().for_each(&mut *f),
+            validator::ValidationErrorsKind::Struct(child) => for_each_error_mut(child, f),
+            validator::ValidationErrorsKind::List(children) => {
+                for child in children.values_mut() {
+                    for_each_error_mut(child, f);
+                }
+            },
+        }
+    }
+}
+
+/// 検証エラーを HTTP 422 (Unprocessable Entity) のレスポンスボディ向けの JSON に変換する。
+///
+/// `{ "errors": { "フィールド名": ["メッセージ", ...] } }` の形式で返す。
+/// メッセージが設定されていないエラーはエラーコードをそのまま使う。
+/// flatten したフィールドの子のエラーは [`flat_field_errors`] と同じ "親.子" のキーにする。
+/// 特定の Web フレームワークには依存しないため、レスポンスの組み立ては呼び出し側で行う。
+#[cfg(feature = "json")]
+pub fn into_unprocessable_entity(errors: &validator::ValidationErrors) -> serde_json::Value {
+    let fields = flat_field_errors(errors)
+        .into_iter()
+        .map(|(field, errors)| {
+            let messages = errors
+                .iter()
+                .map(|error| match &error.message {
+                    Some(message) => serde_json::Value::from(message.as_ref()),
+                    None => serde_json::Value::from(error.code.as_ref()),
+                })
+                .collect::<Vec<_>>();
+            (field, serde_json::Value::Array(messages))
+        })
+        .collect::<serde_json::Map<_, _>>();
+    serde_json::json!({ "errors": fields })
+}
+
+/// 生成される `validate_with()` に渡す、実行時に切り替える検証の設定。
+///
+/// 本番環境だけ必須にしたいフィールドなど、再コンパイルせずに厳しさを変えたい場合に使う。
+#[derive(Default)]
+pub struct ValidationConfig {
+    required: std::collections::HashSet<String>,
+    validators: std::collections::HashMap<&'static str, FieldValidator>,
+    lookups: std::collections::HashMap<&'static str, std::collections::HashMap<String, String>>,
+    holidays: std::collections::HashSet<String>,
+}
+
+/// 実行時に登録するフィールドの検証関数。エラーの場合はメッセージを返す。
+pub type FieldValidator = Box<dyn Fn(&str) -> Result<(), String> + Send + Sync>;
+
+impl ValidationConfig {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// `Option` として宣言されていても必須として扱うフィールドを追加する。
+    ///
+    /// フィールド名はエラーのキーと同じ名前 (serde_names の場合は serde の名前) で指定する。
+    pub fn require(mut self, field: impl Into<String>) -> Self {
+        self.required.insert(field.into());
+        self
+    }
+
+    /// フィールドが実行時に必須に指定されているか
+    pub fn is_required(&self, field: &str) -> bool {
+        self.required.contains(field)
+    }
+
+    /// フィールドの値を検証するクロージャを登録する。
+    ///
+    /// 実行時の設定を取り込んだ検証など、関数のパスで指定できないチェックに使う。
+    /// 同じフィールドに複数回登録した場合は最後のものだけが使われる。
+    pub fn validator<F>(mut self, field: &'static str, validator: F) -> Self
+    where
+        F: Fn(&str) -> Result<(), String> + Send + Sync + 'static,
+    {
+        self.validators.insert(field, Box::new(validator));
+        self
+    }
+
+    /// 登録されたクロージャでフィールドの値を検証する。登録がなければ何もしない。
+    pub fn check(&self, field: &str, value: &str) -> Result<(), String> {
+        match self.validators.get(field) {
+            Some(validator) => validator(value),
+            None => Ok(()),
+        }
+    }
+
+    /// フィールドの値が存在すべき参照データ (値 → 対応する値) を登録する。
+    ///
+    /// 通貨コードと小数点以下の桁数のように、値の存在を確認しながら対応する値を引きたい場合に使う。
+    /// 登録したフィールドは validate_with() で表にない値をエラーにし、
+    /// lookup_values() で対応する値を取り出せる。同じフィールドに複数回登録した場合は最後のものだけが使われる。
+    pub fn lookup(mut self, field: &'static str, table: std::collections::HashMap<String, String>) -> Self {
+        self.lookups.insert(field, table);
+        self
+    }
+
+    /// フィールドに登録された参照データを返す。登録がなければ None を返す。
+    pub fn lookup_table(&self, field: &str) -> Option<&std::collections::HashMap<String, String>> {
+        self.lookups.get(field)
+    }
+
+    /// validate(business_day) のフィールドで営業日として扱わない休日を追加する。
+    ///
+    /// 日付は "2024-01-01" の形式 (YYYY-MM-DD) で指定する。土日は登録しなくても営業日として扱わない。
+    pub fn holidays<I, S>(mut self, dates: I) -> Self
+    where
+        I: IntoIterator<Item = S>,
+        S: Into<String>,
+    {
+        self.holidays.extend(dates.into_iter().map(Into::into));
+        self
+    }
+
+    /// 日付 (YYYY-MM-DD) が休日として登録されているか
+    pub fn is_holiday(&self, date: &str) -> bool {
+        self.holidays.contains(date)
+    }
+}
+
+// クロージャは Debug を実装しないため、登録されたフィールド名だけを出力する
+impl std::fmt::Debug for ValidationConfig {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        f.debug_struct("ValidationConfig")
+            .field("required", &self.required)
+            .field("validators", &self.validators.keys().collect::<Vec<_>>())
+            .field("lookups", &self.lookups)
+            .field("holidays", &self.holidays)
+            .finish()
+    }
+}
+
+/// core_validate feature で生成される `validate_core()` のエラー。
+///
+/// 最初に見つかったエラーのフィールド名とエラーコードだけを持ち、メモリを確保しない。
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct CoreError {
+    /// フィールド名 (エラーのキーと同じ名前)
+    pub field: &'static str,
+    /// エラーコード (validate() と同じコード)
+    pub code: &'static str,
+}
+
+impl core::fmt::Display for CoreError {
+    fn fmt(&self, f: &mut core::fmt::Formatter<'_>) -> core::fmt::Result {
+        write!(f, "{}: {}", self.field, self.code)
+    }
+}
+
+/// 変換時にエラーとせず値を捨てたフィールドの記録。
+///
+/// 生成される `into_lenient_optional()` が、解析できなかった `Option` のフィールドについて返す。
+#[derive(Debug, Clone, PartialEq)]
+pub struct Warning {
+    /// フィールド名 (エラーのキーと同じ名前)
+    pub field: &'static str,
+    /// 捨てた元の値
+    pub value: String,
+    /// 値を捨てた理由
+    pub error: validator::ValidationError,
+}
+
+impl std::fmt::Display for Warning {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match &self.error.message {
+            Some(message) => write!(f, "{}", message),
+            None => write!(f, "フィールド '{}' の値 ({}) を無視しました: {}", self.field, self.value, self.error.code),
+        }
+    }
+}
+
+/// 生成される `validate_categorized()` が返す、種類ごとに分けた検証エラー。
+///
+/// `structural` には未入力の必須項目と宣言された型として解析できない値のエラーを、
+/// `semantic` には範囲・パターン・フィールド間の整合性などの制約のエラーを入れる。
+#[derive(Debug, Clone, Default, PartialEq)]
+pub struct CategorizedErrors {
+    /// 必須項目の未入力や型の誤り
+    pub structural: validator::ValidationErrors,
+    /// 属性で指定した制約やフィールド間のチェックの違反
+    pub semantic: validator::ValidationErrors,
+}
+
+impl CategorizedErrors {
+    /// エラーを (キー, エラーコード) ごとに is_structural で振り分ける。
+    ///
+    /// 入れ子のエラーは入れ子のまま振り分け、is_structural には [`flat_field_errors`] と同じ
+    /// "親.子" のキーを渡す。
+    pub fn split(errors: validator::ValidationErrors, is_structural: impl Fn(&str, &str) -> bool) -> Self {
+        Self::split_nested(&errors, "", &is_structural)
+    }
+
+    fn split_nested(errors: &validator::ValidationErrors, prefix: &str, is_structural: &dyn Fn(&str, &str) -> bool) -> Self {
+        let mut categorized = Self::default();
+        for (key, kind) in errors.errors() {
+            match kind {
+                validator::ValidationErrorsKind::Field(errs) => {
+                    let path = format!("{}{}", prefix, key);
+                    for err in errs {
+                        let target = if is_structural(&path, &err.code) {
+                            &mut categorized.structural
+                        } else {
+                            &mut categorized.semantic
+                        };
+                        target.add(key, err.clone());
+                    }
+                },
+                validator::ValidationErrorsKind::Struct(child) => {
+                    let child = Self::split_nested(child, &format!("{}{}.", prefix, key), is_structural);
+                    categorized.nest(key, child);
+                },
+                validator::ValidationErrorsKind::List(children) => {
+                    let (mut structural, mut semantic) = (std::collections::BTreeMap::new(), std::collections::BTreeMap::new());
+                    for (index, child) in children {
+                        let child = Self::split_nested(child, &format!("{}{}[{}].", prefix, key, index), is_structural);
+                        if !child.structural.is_empty() {
+                            structural.insert(*index, Box::new(child.structural));
+                        }
+                        if !child.semantic.is_empty() {
+                            semantic.insert(*index, Box::new(child.semantic));
+                        }
+                    }
+                    for (target, lists) in [(&mut categorized.structural, structural), (&mut categorized.semantic, semantic)] {
+                        if !lists.is_empty() {
+                            target.errors_mut().insert(key, validator::ValidationErrorsKind::List(lists));
+                        }
+                    }
+                },
+            }
+        }
+        categorized
+    }
+
+    /// flatten したフィールドの子のエラーを、key をキーとする入れ子のエラーとして加える。
+    ///
+    /// key にすでにエラーがある場合は child のもので置き換える。
+    pub fn nest(&mut self, key: &'static str, child: CategorizedErrors) {
+        for (target, errors) in [(&mut self.structural, child.structural), (&mut self.semantic, child.semantic)] {
+            target.errors_mut().remove(key);
+            if !errors.is_empty() {
+                target.errors_mut().insert(key, validator::ValidationErrorsKind::Struct(Box::new(errors)));
+            }
+        }
+    }
+}
+
+impl std::fmt::Display for CategorizedErrors {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match (self.structural.is_empty(), self.semantic.is_empty()) {
+            (false, false) => write!(f, "{}\n{}", self.structural, self.semantic),
+            (false, true) => write!(f, "{}", self.structural),
+            _ => write!(f, "{}", self.semantic),
+        }
+    }
+}
+
+impl std::error::Error for CategorizedErrors {}
+
+/// デシリアライズと同時に検証と型変換を行うラッパー。
+///
+/// 生データ型としてデシリアライズしたあと `validate()` を実行し、
+/// 元の構造体へ変換する。検証に失敗した場合はデシリアライザのエラーとして返すため、
+/// `serde_json::from_str::<Validated<Record>>(...)` のように CSV 以外の形式でも使える。
+#[derive(Debug, Clone, PartialEq)]
+pub struct Validated<T>(pub T);
+
+impl<T> Validated<T> {
+    /// 中の値を取り出す。
+    pub fn into_inner(self) -> T {
+        self.0
+    }
+}
+
+impl<T> std::ops::Deref for Validated<T> {
+    type Target = T;
+
+    fn deref(&self) -> &T {
+        &self.0
+    }
+}
+
+impl<'de, T> serde::Deserialize<'de> for Validated<T>
+where
+    T: HasRaw + TryFrom<<T as HasRaw>::Raw, Error = validator::ValidationErrors>,
+    <T as HasRaw>::Raw: serde::Deserialize<'de>,
+{
+    fn deserialize<D>(deserializer: D) -> Result<Self, D::Error>
+    where
+        D: serde::Deserializer<'de>,
+    {
+        let raw = <T as HasRaw>::Raw::deserialize(deserializer)?;
+        T::try_from(raw).map(Validated).map_err(serde::de::Error::custom)
+    }
+}
+
+/// ファイル全体の検証エラーを (行番号, フィールド名) ごとにまとめたもの。
+///
+/// 行番号はヘッダを除いたデータ行の 0 始まりの番号。
+/// CSV として読み込めなかった行は [`BatchErrors::RECORD_FIELD`] をフィールド名として記録する。
+#[derive(Debug, Clone, Default, PartialEq)]
+pub struct BatchErrors {
+    errors: std::collections::BTreeMap<(usize, String), Vec<String>>,
+}
+
+impl BatchErrors {
+    /// 行全体に関するエラー (CSV の読み込み失敗など) を記録するフィールド名
+    pub const RECORD_FIELD: &'static str = "__record__";
+
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// 1件のエラーメッセージを追加する。
+    pub fn add(&mut self, row: usize, field: &str, message: impl Into<String>) {
+        self.errors.entry((row, field.to_string())).or_default().push(message.into());
+    }
+
+    /// 1行分の検証エラーを追加する。メッセージがないエラーはエラーコードを使う。
+    ///
+    /// flatten したフィールドの子のエラーは [`flat_field_errors`] と同じ "親.子" のフィールド名で記録する。
+    pub fn add_row(&mut self, row: usize, errors: &validator::ValidationErrors) {
+        for (field, errors) in flat_field_errors(errors) {
+            for error in errors {
+                let message = match &error.message {
+                    Some(message) => message.to_string(),
+                    None => error.code.to_string(),
+                };
+                self.add(row, &field, message);
+            }
+        }
+    }
+
+    /// エラーが1件もないか
+    pub fn is_empty(&self) -> bool {
+        self.errors.is_empty()
+    }
+
+    /// エラーメッセージの総数
+    pub fn len(&self) -> usize {
+        self.errors.values().map(Vec::len).sum()
+    }
+
+    /// エラーのあった行の数
+    pub fn row_count(&self) -> usize {
+        let mut rows = self.errors.keys().map(|(row, _)| *row).collect::<Vec<_>>();
+        rows.dedup();
+        rows.len()
+    }
+
+    /// (行番号, フィールド名, メッセージ一覧) を行番号・フィールド名の順に返す。
+    pub fn iter(&self) -> impl Iterator<Item = (usize, &str, &[String])> {
+        self.errors.iter().map(|((row, field), messages)| (*row, field.as_str(), messages.as_slice()))
+    }
+
+    /// フィールド名ごとのエラー件数
+    pub fn summary(&self) -> std::collections::BTreeMap<&str, usize> {
+        let mut summary = std::collections::BTreeMap::new();
+        for ((_, field), messages) in &self.errors {
+            *summary.entry(field.as_str()).or_insert(0) += messages.len();
+        }
+        summary
+    }
+}
+
+impl std::fmt::Display for BatchErrors {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        write!(f, "{} 行で {} 件のエラーがあります", self.row_count(), self.len())?;
+        for (field, count) in self.summary() {
+            write!(f, "\n  {}: {} 件", field, count)?;
+        }
+        Ok(())
+    }
+}
+
+impl std::error::Error for BatchErrors {}
+
+// [{ "row": 0, "field": "...", "messages": ["..."] }, ...] の形式で出力する
+impl serde::Serialize for BatchErrors {
+    fn serialize<S>(&self, serializer: S) -> Result<S::Ok, S::Error>
+    where
+        S: serde::Serializer,
+    {
+        use serde::ser::{SerializeSeq, SerializeStruct};
+
+        struct Entry<'a>(usize, &'a str, &'a [String]);
+
+        impl serde::Serialize for Entry<'_> {
+            fn serialize<S>(&self, serializer: S) -> Result<S::Ok, S::Error>
+            where
+                S: serde::Serializer,
+            {
+                let mut entry = serializer.serialize_struct("Entry", 3)?;
+                entry.serialize_field("row", &self.0)?;
+                entry.serialize_field("field", self.1)?;
+                entry.serialize_field("messages", self.2)?;
+                entry.end()
+            }
+        }
+
+        let mut seq = serializer.serialize_seq(Some(self.errors.len()))?;
+        for (row, field, messages) in self.iter() {
+            seq.serialize_element(&Entry(row, field, messages))?;
+        }
+        seq.end()
+    }
+}
+
+/// CSV のすべての行を生データ型として読み込んで検証し、エラーをまとめて返す。
+///
+/// `T` には生成された生データ型 (例: `RawRecord`) を指定する。
+/// 途中の行でエラーがあっても最後の行まで読み続ける。
+pub fn collect_errors<T, R>(rdr: &mut csv::Reader<R>) -> BatchErrors
+where
+    T: serde::de::DeserializeOwned + validator::Validate,
+    R: std::io::Read,
+{
+    let mut batch = BatchErrors::new();
+    for (row, result) in rdr.deserialize::<T>().enumerate() {
+        match result {
+            Ok(raw) => {
+                if let Err(errors) = raw.validate() {
+                    batch.add_row(row, &errors);
+                }
+            },
+            Err(e) => batch.add(row, BatchErrors::RECORD_FIELD, e.to_string()),
+        }
+    }
+    batch
+}
+
+/// CSV のすべての行を検証して型付きの構造体に変換する。
+///
+/// 1行でも失敗した場合は途中までの結果を返さず、すべての行のエラーをまとめた
+/// [`BatchErrors`] を返す。行番号は [`collect_errors`] と同じくデータ行の 0 始まり。
+pub fn load_all<T, R>(mut rdr: csv::Reader<R>) -> Result<Vec<T>, BatchErrors>
+where
+    T: HasRaw + TryFrom<<T as HasRaw>::Raw, Error = validator::ValidationErrors>,
+    <T as HasRaw>::Raw: serde::de::DeserializeOwned,
+    R: std::io::Read,
+{
+    let mut records = Vec::new();
+    let mut batch = BatchErrors::new();
+    for (row, result) in rdr.deserialize::<<T as HasRaw>::Raw>().enumerate() {
+        match result.map(T::try_from) {
+            Ok(Ok(record)) => records.push(record),
+            Ok(Err(errors)) => batch.add_row(row, &errors),
+            Err(e) => batch.add(row, BatchErrors::RECORD_FIELD, e.to_string()),
+        }
+    }
+    if batch.is_empty() {
+        Ok(records)
+    } else {
+        Err(batch)
+    }
+}
+
+/// 読み込み済みの生データを rayon で並列に検証・変換する。
+///
+/// 正規表現を多用するなど検証の負荷が高い大きなファイル向け。
+/// 成功した行は元の順序のまま返し、失敗した行は (行番号, エラー) の組として行番号順に返す。
+/// 行番号は `records` の 0 始まりの添字。
+#[cfg(feature = "rayon")]
+pub fn load_all_par<T>(records: Vec<<T as HasRaw>::Raw>) -> (Vec<T>, Vec<(usize, validator::ValidationErrors)>)
+where
+    T: HasRaw + TryFrom<<T as HasRaw>::Raw, Error = validator::ValidationErrors> + Send,
+    <T as HasRaw>::Raw: Send,
+{
+    use rayon::prelude::*;
+
+    let results = records.into_par_iter().map(T::try_from).collect::<Vec<_>>();
+    let mut loaded = Vec::new();
+    let mut errors = Vec::new();
+    for (row, result) in results.into_iter().enumerate() {
+        match result {
+            Ok(record) => loaded.push(record),
+            Err(e) => errors.push((row, e)),
+        }
+    }
+    (loaded, errors)
+}
+
+/// CSV の1行から型付きの構造体を作る際のエラー。
+#[derive(Debug)]
+pub enum Error {
+    /// 生データ型へのデシリアライズに失敗した
+    Csv(csv::Error),
+    /// 値の行の列数がヘッダ行と一致しない
+    UnequalLengths { headers: usize, record: usize },
+    /// 検証または型変換に失敗した
+    Validation(validator::ValidationErrors),
+}
+
+impl std::fmt::Display for Error {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self {
+            Error::Csv(e) => write!(f, "CSV の読み込みに失敗しました: {}", e),
+            Error::UnequalLengths { headers, record } => {
+                write!(f, "列数がヘッダと一致しません (ヘッダ: {} 列, 値: {} 列)", headers, record)
+            },
+            Error::Validation(e) => write!(f, "検証に失敗しました: {}", e),
+        }
+    }
+}
+
+impl std::error::Error for Error {
+    fn source(&self) -> Option<&(dyn std::error::Error + 'static)> {
+        match self {
+            Error::Csv(e) => Some(e),
+            Error::UnequalLengths { .. } => None,
+            Error::Validation(e) => Some(e),
+        }
+    }
+}
+
+impl From<csv::Error> for Error {
+    fn from(e: csv::Error) -> Self {
+        Error::Csv(e)
+    }
+}
+
+impl From<validator::ValidationErrors> for Error {
+    fn from(e: validator::ValidationErrors) -> Self {
+        Error::Validation(e)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn luhn_accepts_valid_numbers() {
+        assert!(luhn_valid("4111111111111111"));
+        // 空白やハイフンの区切りは無視する
+        assert!(luhn_valid("4111 1111 1111 1111"));
+        assert!(luhn_valid("4111-1111-1111-1111"));
+    }
+
+    #[test]
+    fn luhn_rejects_changed_digit_and_empty_input() {
+        assert!(!luhn_valid("4111111111111112"));
+        assert!(!luhn_valid("4111111111211111"));
+        assert!(!luhn_valid(""));
+        assert!(!luhn_valid("- -"));
+    }
+
+    #[test]
+    fn iban_accepts_valid_numbers() {
+        assert_eq!(iban_check("GB82WEST12345698765432"), Ok(()));
+        // 印字用の空白と小文字も受け付ける
+        assert_eq!(iban_check("GB82 WEST 1234 5698 7654 32"), Ok(()));
+        assert_eq!(iban_check("gb82west12345698765432"), Ok(()));
+    }
+
+    #[test]
+    fn iban_wrong_check_digits_is_checksum_error() {
+        assert_eq!(iban_check("GB83WEST12345698765432"), Err(IbanError::Checksum));
+        assert_eq!(iban_check("GB82WEST12345698765433"), Err(IbanError::Checksum));
+    }
+
+    #[test]
+    fn iban_malformed_input_is_format_error() {
+        // GB は 22 桁
+        assert_eq!(iban_check("GB82WEST1234569876543"), Err(IbanError::Format));
+        assert_eq!(iban_check("GB82WEST123456987654321"), Err(IbanError::Format));
+        assert_eq!(iban_check("ZZ82WEST12345698765432"), Err(IbanError::Format));
+        assert_eq!(iban_check("GBXXWEST12345698765432"), Err(IbanError::Format));
+        assert_eq!(iban_check("GB82-WEST-12345698765432"), Err(IbanError::Format));
+        assert_eq!(iban_check(""), Err(IbanError::Format));
+    }
+
+    #[test]
+    fn hostname_accepts_valid_names() {
+        assert!(hostname_valid("example.com"));
+        assert!(hostname_valid("a-b.example.com"));
+        assert!(hostname_valid("localhost"));
+        // 末尾のドット1つは許可する
+        assert!(hostname_valid("example.com."));
+        assert!(hostname_valid(&"a".repeat(63)));
+    }
+
+    #[test]
+    fn hostname_rejects_invalid_labels() {
+        assert!(!hostname_valid("-example.com"));
+        assert!(!hostname_valid("example-.com"));
+        assert!(!hostname_valid(&"a".repeat(64)));
+        assert!(!hostname_valid("example..com"));
+        assert!(!hostname_valid("example.com.."));
+        assert!(!hostname_valid("exa_mple.com"));
+        assert!(!hostname_valid(""));
+        assert!(!hostname_valid("."));
+    }
+
+    #[test]
+    fn hostname_rejects_names_over_253_chars() {
+        let label = "a".repeat(63);
+        let name = [label.as_str(); 4].join(".");
+        assert_eq!(name.len(), 255);
+        assert!(!hostname_valid(&name));
+        assert!(hostname_valid(&name[2..]));
+    }
+}
@@ -0,0 +1,454 @@
+// arbitrary feature で生成する arbitrary_valid() の実装
+use quote::quote;
+use syn::Lit;
+use super::{
+    date_bound_expr, find_field, flatten_raw_type, get_inner_type, int_bounds, is_naive_date, is_naive_time, is_option_type, lit_as_f64,
+    FlattenField, Kana, LengthUnit, RangeBound, SocketValidation, StringValidation, DEFAULT_TIME_FORMAT,
+};
+
+// 制約を満たすランダムな生データを生成する arbitrary_valid() を生成する関数
+pub(crate) fn arbitrary_impl(
+    raw_struct_ident: &syn::Ident,
+    fields: &syn::punctuated::Punctuated<syn::Field, syn::token::Comma>,
+    flatten_fields: &[FlattenField],
+    validations: &[StringValidation],
+    strict_required: bool,
+) -> syn::Result<proc_macro2::TokenStream> {
+    let flatten_idents = flatten_fields.iter().map(|flatten| &flatten.field.ident);
+    let flatten_raw_types = flatten_fields.iter().map(flatten_raw_type);
+    let field_values = fields.iter().zip(validations).map(|(f, validation)| {
+        let field_name = &f.ident;
+        let field_type = &f.ty;
+        let is_optional = is_option_type(field_type);
+        let base_type = if is_optional {
+            get_inner_type(field_type).unwrap()
+        } else {
+            field_type
+        };
+
+        let type_str = quote!(#base_type).to_string();
+        let value = match type_str.as_str() {
+            // 許可値が決まっている場合はその中から選ぶ
+            _ if !validation.one_of.is_empty() => {
+                let allowed = validation.one_of.iter().map(|lit| match lit {
+                    Lit::Str(lit) => lit.value(),
+                    _ => quote!(#lit).to_string(),
+                });
+                quote! {
+                    u.choose(&[#(#allowed),*])?.to_string()
+                }
+            },
+            "u8" | "u16" | "u32" | "u64" | "u128" | "i8" | "i16" | "i32" | "i64" | "i128" => {
+                let (lo, hi) = int_bounds(validation, base_type, &type_str);
+                match validation.radix {
+                    // 桁数の指定がある場合は、その桁数に収まる範囲から選んで先頭を 0 で埋める
+                    _ if validation.digits.is_some() => {
+                        let digits = validation.digits.unwrap();
+                        let max = 10i128.checked_pow(digits as u32).map_or(i128::MAX, |pow| pow - 1);
+                        let min = if validation.no_leading_zeros && digits > 1 { (max + 1) / 10 } else { 0 };
+                        let max = proc_macro2::Literal::i128_suffixed(max);
+                        let min = proc_macro2::Literal::i128_suffixed(min);
+                        quote! {
+                            {
+                                let lo = #base_type::max(#lo, #base_type::try_from(#min).unwrap_or(#base_type::MAX));
+                                let hi = #base_type::min(#hi, #base_type::try_from(#max).unwrap_or(#base_type::MAX));
+                                if lo > hi {
+                                    return Err(arbitrary::Error::IncorrectFormat);
+                                }
+                                format!("{:0width$}", u.int_in_range::<#base_type>(lo..=hi)?, width = #digits)
+                            }
+                        }
+                    },
+                    // 負の値は扱わず、基数に合わせた表記で出力する
+                    Some(radix) => {
+                        let format = match radix {
+                            16 => "{:x}",
+                            8 => "{:o}",
+                            2 => "{:b}",
+                            _ => "{}",
+                        };
+                        quote! {
+                            format!(#format, u.int_in_range::<#base_type>((#lo).max(0)..=#hi)?)
+                        }
+                    },
+                    None => quote! {
+                        u.int_in_range::<#base_type>(#lo..=#hi)?.to_string()
+                    },
+                }
+            },
+            "f32" | "f64" => {
+                // gt / lt の境界は値そのものを含まないため、境界が排他かどうかも覚えておく
+                let mut lo = None;
+                let mut hi = None;
+                let mut lo_exclusive = false;
+                let mut hi_exclusive = false;
+                for (bound, lit) in &validation.range {
+                    let limit = lit_as_f64(lit).ok();
+                    match bound {
+                        RangeBound::Ge | RangeBound::Gt => {
+                            lo = limit;
+                            lo_exclusive = matches!(bound, RangeBound::Gt);
+                        },
+                        RangeBound::Le | RangeBound::Lt => {
+                            hi = limit;
+                            hi_exclusive = matches!(bound, RangeBound::Lt);
+                        },
+                    }
+                }
+                let step = validation.step.as_ref().and_then(|step| lit_as_f64(step).ok());
+                match (lo, hi) {
+                    // 刻み幅の指定がある場合は刻みの倍数から選ぶ (排他の境界にちょうど乗る倍数は除く)
+                    (lo, hi) if step.is_some() => {
+                        let step = step.unwrap();
+                        let lo_steps = lo.map(|lo| lo / step);
+                        let hi_steps = hi.map(|hi| hi / step);
+                        let min_steps = match lo_steps {
+                            Some(lo) if lo_exclusive => lo.floor() as i64 + 1,
+                            Some(lo) => lo.ceil() as i64,
+                            None => hi_steps.map_or(-1000, |hi| hi.floor() as i64 - 1000),
+                        };
+                        let max_steps = match hi_steps {
+                            Some(hi) if hi_exclusive => hi.ceil() as i64 - 1,
+                            Some(hi) => hi.floor() as i64,
+                            None => min_steps + 1000,
+                        };
+                        quote! {
+                            {
+                                if #min_steps > #max_steps {
+                                    return Err(arbitrary::Error::IncorrectFormat);
+                                }
+                                ((u.int_in_range(#min_steps..=#max_steps)? as f64 * #step) as #base_type).to_string()
+                            }
+                        }
+                    },
+                    (None, None) => quote! {
+                        (u.arbitrary::<i32>()? as #base_type / 100.0).to_string()
+                    },
+                    (lo, hi) => {
+                        // 範囲の指定がある場合はその中から一様に選ぶ。
+                        // 排他の境界がある場合は両端を含まない (0, 1) の割合を使う
+                        let lo = lo.unwrap_or_else(|| hi.unwrap() - 1000.0);
+                        let hi = hi.unwrap_or(lo + 1000.0);
+                        let ratio = if lo_exclusive || hi_exclusive {
+                            quote! { (u.arbitrary::<u16>()? as f64 + 0.5) / (u16::MAX as f64 + 1.0) }
+                        } else {
+                            quote! { u.arbitrary::<u16>()? as f64 / u16::MAX as f64 }
+                        };
+                        quote! {
+                            ((#lo + (#hi - #lo) * (#ratio)) as #base_type).to_string()
+                        }
+                    },
+                }
+            },
+            "bool" => quote! {
+                u.arbitrary::<bool>()?.to_string()
+            },
+            "String" => string_value(f, validation)?,
+            _ if is_naive_time(base_type) => {
+                let time_format = validation.time_format.as_deref().unwrap_or(DEFAULT_TIME_FORMAT);
+                quote! {
+                    raw_struct::chrono::NaiveTime::from_num_seconds_from_midnight_opt(u.int_in_range(0..=86_399)?, 0)
+                        .ok_or(arbitrary::Error::IncorrectFormat)?
+                        .format(#time_format)
+                        .to_string()
+                }
+            },
+            _ if validation.enum_name.is_some() => {
+                let enum_path = validation.enum_name.as_ref().unwrap();
+                quote! {
+                    u.choose(<#enum_path as raw_struct::EnumNames>::NAMES)?.to_string()
+                }
+            },
+            _ if validation.socket.is_some() => socket_value(validation.socket.as_ref().unwrap()),
+            _ if validation.timezone => quote! {
+                u.choose(&raw_struct::chrono_tz::TZ_VARIANTS)?.name().to_string()
+            },
+            _ if validation.glob => quote! {
+                u.choose(&["*", "*.csv", "data/**/*.csv", "report-????.txt", "[a-z]*.log"])?.to_string()
+            },
+            // 範囲の指定がない側は 1970-01-01 ～ 2099-12-31 の間で生成する
+            _ if is_naive_date(base_type) => {
+                let (min, max) = match &validation.date_range {
+                    Some((min, max)) => (min.as_ref(), max.as_ref()),
+                    None => (None, None),
+                };
+                let min = min.map(date_bound_expr).unwrap_or(quote! { raw_struct::chrono::NaiveDate::from_ymd_opt(1970, 1, 1).unwrap() });
+                let max = max.map(date_bound_expr).unwrap_or(quote! { raw_struct::chrono::NaiveDate::from_ymd_opt(2099, 12, 31).unwrap() });
+                let days_mut = if validation.business_day { quote! { mut } } else { quote! {} };
+                // business_day の場合、土日は直前の金曜日 (範囲外なら直後の月曜日) にずらす
+                let business_day_adjust = validation.business_day.then(|| quote! {
+                    // 0001-01-01 (num_days_from_ce が 1) は月曜日
+                    let weekday = (days - 1).rem_euclid(7);
+                    if weekday >= 5 {
+                        days -= weekday - 4;
+                        if days < min {
+                            days += 3;
+                        }
+                        if days > max {
+                            return Err(arbitrary::Error::IncorrectFormat);
+                        }
+                    }
+                });
+                quote! {
+                    {
+                        let min = raw_struct::chrono::Datelike::num_days_from_ce(&#min);
+                        let max = raw_struct::chrono::Datelike::num_days_from_ce(&#max);
+                        if min > max {
+                            return Err(arbitrary::Error::IncorrectFormat);
+                        }
+                        let #days_mut days = u.int_in_range(min..=max)?;
+                        #business_day_adjust
+                        raw_struct::chrono::NaiveDate::from_num_days_from_ce_opt(days)
+                            .ok_or(arbitrary::Error::IncorrectFormat)?
+                            .to_string()
+                    }
+                }
+            },
+            _ => quote! {
+                <#base_type as arbitrary::Arbitrary>::arbitrary(u)?.to_string()
+            },
+        };
+
+        Ok(if is_optional {
+            quote! {
+                #field_name: if u.arbitrary::<bool>()? { Some(#value) } else { None }
+            }
+        } else if strict_required {
+            quote! {
+                #field_name: #value
+            }
+        } else {
+            quote! {
+                #field_name: Some(#value)
+            }
+        })
+    }).collect::<syn::Result<Vec<_>>>()?;
+
+    // same_as のフィールドは、生成後に比較相手のフィールドの値で上書きする
+    let same_as_copies = fields.iter().zip(validations).filter_map(|(f, validation)| {
+        let same_as = validation.same_as.as_ref()?;
+        let other_field = find_field(fields, &same_as.value())?;
+        let field_name = &f.ident;
+        let other_name = &other_field.ident;
+        let raw_optional = |field: &syn::Field| is_option_type(&field.ty) || !strict_required;
+        Some(match (raw_optional(f), raw_optional(other_field)) {
+            (true, false) => quote! { raw.#field_name = Some(raw.#other_name.clone()); },
+            (false, true) => quote! { raw.#field_name = raw.#other_name.clone().unwrap_or_default(); },
+            _ => quote! { raw.#field_name = raw.#other_name.clone(); },
+        })
+    });
+
+    Ok(quote! {
+        impl #raw_struct_ident {
+            /// 宣言された型と制約を満たすランダムな生データを生成する。
+            ///
+            /// uuid / phone / iban などの書式の制約には、その書式に合う値を組み立てる。
+            /// requires のようにフィールドをまたぐ制約などで生成した値が validate() を
+            /// 通らない場合は `arbitrary::Error::IncorrectFormat` を返す。
+            pub fn arbitrary_valid(u: &mut arbitrary::Unstructured) -> arbitrary::Result<#raw_struct_ident> {
+                #[allow(unused_mut)]
+                let mut raw = #raw_struct_ident {
+                    #(#field_values,)*
+                    #(#flatten_idents: <#flatten_raw_types>::arbitrary_valid(u)?,)*
+                };
+                #(#same_as_copies)*
+                if validator::Validate::validate(&raw).is_err() {
+                    return Err(arbitrary::Error::IncorrectFormat);
+                }
+                Ok(raw)
+            }
+        }
+    })
+}
+
+// 文字列のフィールドの値を生成する式を作る関数
+//
+// 書式の決まった制約 (uuid / phone / iban など) は、その書式に合う値を組み立てる。
+// 正規表現のように値を組み立てられない制約はコンパイルエラーにする
+fn string_value(field: &syn::Field, validation: &StringValidation) -> syn::Result<proc_macro2::TokenStream> {
+    let unsupported = [
+        (!validation.regex_any.is_empty(), "regex_any"),
+        (validation.format_by.is_some(), "format_by"),
+    ];
+    if let Some((_, key)) = unsupported.iter().find(|(used, _)| *used) {
+        return Err(syn::Error::new_spanned(
+            field,
+            format!(
+                "arbitrary_valid() は {} の正規表現に一致する値を生成できません（arbitrary feature を使う場合は one_of で値の候補を指定してください）",
+                key
+            ),
+        ));
+    }
+
+    let value = if let Some(uuid) = &validation.uuid {
+        // バージョンの指定がない場合は v4 の形式にする (バリアントは RFC 4122)
+        let version = uuid.version.unwrap_or(4) as u16;
+        quote! {
+            format!(
+                "{:08x}-{:04x}-{:04x}-{:04x}-{:012x}",
+                u.arbitrary::<u32>()?,
+                u.arbitrary::<u16>()?,
+                (u.arbitrary::<u16>()? & 0x0fff) | (#version << 12),
+                (u.arbitrary::<u16>()? & 0x3fff) | 0x8000,
+                u.arbitrary::<u64>()? & 0xffff_ffff_ffff
+            )
+        }
+    } else if validation.semver || validation.semver_req {
+        let prefix = if validation.semver_req { "^" } else { "" };
+        quote! {
+            format!("{}{}.{}.{}", #prefix, u.arbitrary::<u8>()?, u.arbitrary::<u8>()?, u.arbitrary::<u8>()?)
+        }
+    } else if validation.phone.is_some() {
+        // 国番号付きの番号は地域の指定に関係なく解釈できる
+        quote! {
+            u.choose(&["+819012345678", "+81312345678", "+14155552671", "+442079460958"])?.to_string()
+        }
+    } else if validation.iban {
+        quote! {
+            u.choose(&["DE89370400440532013000", "GB82WEST12345698765432", "FR1420041010050500013M02606", "NL91ABNA0417164300"])?.to_string()
+        }
+    } else if validation.luhn {
+        // 末尾のチェックディジット以外をランダムに決め、Luhn のアルゴリズムで最後の桁を計算する
+        let len = validation.digits.unwrap_or(16);
+        quote! {
+            {
+                let mut digits = (1..#len)
+                    .map(|_| u.int_in_range(0..=9u32))
+                    .collect::<arbitrary::Result<Vec<u32>>>()?;
+                let sum: u32 = digits.iter().rev().enumerate().map(|(i, &d)| {
+                    if i % 2 == 0 {
+                        let d = d * 2;
+                        if d > 9 { d - 9 } else { d }
+                    } else {
+                        d
+                    }
+                }).sum();
+                digits.push((10 - sum % 10) % 10);
+                digits.iter().map(u32::to_string).collect::<String>()
+            }
+        }
+    } else if let Some(digits) = validation.digits {
+        let first = if validation.no_leading_zeros && digits > 1 { 1u8 } else { 0u8 };
+        quote! {
+            (0..#digits)
+                .map(|i| Ok(char::from(b'0' + u.int_in_range(if i == 0 { #first } else { 0 }..=9u8)?)))
+                .collect::<arbitrary::Result<String>>()?
+        }
+    } else if let Some(socket) = &validation.socket {
+        socket_value(socket)
+    } else if validation.mac.is_some() {
+        quote! {
+            (0..6)
+                .map(|_| Ok(format!("{:02x}", u.arbitrary::<u8>()?)))
+                .collect::<arbitrary::Result<Vec<String>>>()?
+                .join(":")
+        }
+    } else if validation.hostname {
+        quote! {
+            format!("host{}.example.com", u.arbitrary::<u16>()?)
+        }
+    } else if validation.jp_postal_code {
+        quote! {
+            format!("{:03}-{:04}", u.int_in_range(0..=999u16)?, u.int_in_range(0..=9999u16)?)
+        }
+    } else if let Some(hex_color) = &validation.hex_color {
+        let len = hex_color.lengths.first().copied().unwrap_or(6);
+        quote! {
+            {
+                const HEX: &[char] = &['0', '1', '2', '3', '4', '5', '6', '7', '8', '9', 'a', 'b', 'c', 'd', 'e', 'f', 'A', 'F'];
+                std::iter::once(Ok('#'))
+                    .chain((0..#len).map(|_| u.choose(HEX).copied()))
+                    .collect::<arbitrary::Result<String>>()?
+            }
+        }
+    } else if let Some(card_expiry) = &validation.card_expiry {
+        // allow_past でない場合は、まだ切れていない 2090 年代の有効期限にする
+        let years = if card_expiry.allow_past { quote! { 0..=99u8 } } else { quote! { 90..=99u8 } };
+        quote! {
+            format!("{:02}/{:02}", u.int_in_range(1..=12u8)?, u.int_in_range(#years)?)
+        }
+    } else if let Some(password) = &validation.password {
+        // 必須の文字種を1文字ずつ入れてから、文字数の下限まで英数字で埋める
+        let required = [
+            (password.require_upper, 'A'),
+            (password.require_lower, 'a'),
+            (password.require_digit, '1'),
+            (password.require_symbol, '!'),
+        ];
+        let required = required.iter().filter(|(used, _)| *used).map(|(_, c)| *c).collect::<String>();
+        let min = password.min_len.max(validation.min_length.unwrap_or(0));
+        let max = validation.max_length.unwrap_or(min + 16).max(min);
+        quote! {
+            {
+                const CHARS: &[char] = &['a', 'b', 'c', 'x', 'y', 'z', 'A', 'B', 'C', '0', '1', '9'];
+                let len = u.int_in_range(#min..=#max)?;
+                let mut value = String::from(#required);
+                while value.chars().count() < len {
+                    value.push(*u.choose(CHARS)?);
+                }
+                value
+            }
+        }
+    } else if let Some(set) = &validation.in_set {
+        quote! {
+            u.choose(&(#set).iter().collect::<Vec<_>>())?.to_string()
+        }
+    } else {
+        text_value(validation)
+    };
+    Ok(value)
+}
+
+// ソケットアドレス (127.0.0.1:ポート) を生成する式を作る関数
+fn socket_value(socket: &SocketValidation) -> proc_macro2::TokenStream {
+    let (lo, hi) = socket.port_range.unwrap_or((0, u16::MAX));
+    quote! {
+        format!("127.0.0.1:{}", u.int_in_range(#lo..=#hi)?)
+    }
+}
+
+// 書式の決まっていない文字列を、文字数・文字種・前後の文字列の指定に合わせて生成する式を作る関数
+fn text_value(validation: &StringValidation) -> proc_macro2::TokenStream {
+    let chars = match validation.kana {
+        Some(Kana::Katakana) => quote! { &['ア', 'イ', 'ウ', 'カ', 'キ', 'ン', 'ー'] },
+        Some(Kana::HalfWidthKatakana) => quote! { &['ｱ', 'ｲ', 'ｳ', 'ｶ', 'ｷ', 'ﾝ', 'ｰ'] },
+        Some(Kana::Hiragana) => quote! { &['あ', 'い', 'う', 'か', 'き', 'ん', 'ー'] },
+        // 文字数以外の単位で数える場合は、1 文字が 1 バイト・表示幅 1 になる ASCII だけを使う
+        None if !matches!(validation.length_unit, LengthUnit::Chars) => quote! {
+            &['a', 'b', 'c', 'x', 'y', 'z', 'A', 'B', 'C', '0', '1', '9']
+        },
+        None => quote! {
+            &[
+                'a', 'b', 'c', 'x', 'y', 'z', 'A', 'B', 'C', '0', '1', '9',
+                'あ', 'い', 'う', 'ア', 'イ', 'ウ', '漢', '字',
+            ]
+        },
+    };
+    let prefix = validation.starts_with.clone().unwrap_or_default();
+    let mut suffix = validation.ends_with.clone().unwrap_or_default();
+    if let Some(ext) = validation.file_ext.first() {
+        suffix = format!("{}.{}", suffix, ext);
+    }
+    // 前後の文字列の分を除いた文字数をランダムな文字で埋める
+    let fixed = prefix.chars().count() + suffix.chars().count();
+    let mut min = validation.min_length.unwrap_or(0).saturating_sub(fixed);
+    if validation.present_non_blank || !validation.file_ext.is_empty() {
+        min = min.max(1);
+    }
+    let mut max = validation.max_length.map_or(min + 32, |max| max.saturating_sub(fixed));
+    // 候補の文字は最大 3 バイトなので、バイト数の上限を超えない文字数に抑える
+    if let Some(limit) = validation.db_varchar {
+        max = max.min(limit.saturating_sub(prefix.len() + suffix.len()) / 3);
+    }
+    let max = max.max(min);
+    quote! {
+        {
+            const CHARS: &[char] = #chars;
+            let len = u.int_in_range(#min..=#max)?;
+            let body = (0..len)
+                .map(|_| u.choose(CHARS).copied())
+                .collect::<arbitrary::Result<String>>()?;
+            format!("{}{}{}", #prefix, body, #suffix)
+        }
+    }
+}
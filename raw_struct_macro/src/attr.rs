@@ -0,0 +1,174 @@
+// validate(...) 属性の中身を解析するための型
+//
+// syn の Meta では `cases = { "US" = "..." }` のような
+// 値を表現できないため、必要な構文だけを持つ独自の木構造として解析する。
+use syn::ext::IdentExt;
+use syn::parse::{Parse, ParseStream};
+use syn::punctuated::Punctuated;
+use std::fmt::Display;
+use std::str::FromStr;
+use syn::{braced, bracketed, parenthesized, Ident, Lit, LitStr, Token};
+
+// validate(...) の中の1要素
+pub enum AttrArg {
+    // finite のようなフラグ
+    Flag(Ident),
+    // min_length = 5 のようなキーと値の組
+    Value(Ident, AttrValue),
+    // phone(region = "JP") のような入れ子
+    List(Ident, Vec<AttrArg>),
+}
+
+// キーに対応する値
+pub enum AttrValue {
+    Lit(Lit),
+    // { "US" = "...", "JP" = "..." } のような対応表
+    Map(Vec<(Lit, Lit)>),
+    // ["...", "..."] のような配列
+    Array(Vec<Lit>),
+}
+
+impl AttrArg {
+    // 要素のキー
+    pub fn key(&self) -> &Ident {
+        match self {
+            AttrArg::Flag(key) | AttrArg::Value(key, _) | AttrArg::List(key, _) => key,
+        }
+    }
+}
+
+impl AttrValue {
+    // エラーを指し示す位置 (リテラル以外はキーの位置)
+    fn span(&self, key: &Ident) -> proc_macro2::Span {
+        match self {
+            AttrValue::Lit(lit) => lit.span(),
+            _ => key.span(),
+        }
+    }
+
+    // 整数リテラルとして値を取り出す
+    pub fn parse_int<N>(&self, key: &Ident) -> syn::Result<N>
+    where
+        N: FromStr,
+        N::Err: Display,
+    {
+        match self {
+            AttrValue::Lit(Lit::Int(lit)) => lit.base10_parse(),
+            _ => Err(syn::Error::new(self.span(key), format!("{} には整数を指定してください", key))),
+        }
+    }
+
+    // 文字列リテラルとして値を取り出す
+    pub fn parse_str(&self, key: &Ident) -> syn::Result<LitStr> {
+        match self {
+            AttrValue::Lit(Lit::Str(lit)) => Ok(lit.clone()),
+            _ => Err(syn::Error::new(self.span(key), format!("{} には文字列を指定してください", key))),
+        }
+    }
+
+    // 文字列の配列として値を取り出す
+    pub fn parse_str_list(&self, key: &Ident) -> syn::Result<Vec<LitStr>> {
+        let items = match self {
+            AttrValue::Array(items) => items,
+            _ => return Err(syn::Error::new(self.span(key), format!("{} には [\"...\", ...] の形式で指定してください", key))),
+        };
+        items
+            .iter()
+            .map(|item| match item {
+                Lit::Str(lit) => Ok(lit.clone()),
+                other => Err(syn::Error::new(other.span(), format!("{} の要素には文字列を指定してください", key))),
+            })
+            .collect()
+    }
+
+    // 整数の配列として値を取り出す
+    pub fn parse_int_list<N>(&self, key: &Ident) -> syn::Result<Vec<N>>
+    where
+        N: FromStr,
+        N::Err: Display,
+    {
+        let items = match self {
+            AttrValue::Array(items) => items,
+            _ => return Err(syn::Error::new(self.span(key), format!("{} には [1, 2, ...] の形式で指定してください", key))),
+        };
+        items
+            .iter()
+            .map(|item| match item {
+                Lit::Int(lit) => lit.base10_parse(),
+                other => Err(syn::Error::new(other.span(), format!("{} の要素には整数を指定してください", key))),
+            })
+            .collect()
+    }
+
+    // 文字列同士の対応表として値を取り出す
+    pub fn parse_str_map(&self, key: &Ident) -> syn::Result<Vec<(LitStr, LitStr)>> {
+        let entries = match self {
+            AttrValue::Map(entries) => entries,
+            _ => return Err(syn::Error::new(self.span(key), format!("{} には {{ \"キー\" = \"値\", ... }} の形式で指定してください", key))),
+        };
+        entries
+            .iter()
+            .map(|(k, v)| match (k, v) {
+                (Lit::Str(k), Lit::Str(v)) => Ok((k.clone(), v.clone())),
+                (Lit::Str(_), other) | (other, _) => Err(syn::Error::new(other.span(), format!("{} のキーと値には文字列を指定してください", key))),
+            })
+            .collect()
+    }
+}
+
+impl Parse for AttrArg {
+    fn parse(input: ParseStream) -> syn::Result<Self> {
+        // type や ref のような予約語もキーとして受け付ける
+        let key = input.call(Ident::parse_any)?;
+        if input.peek(Token![=]) {
+            input.parse::<Token![=]>()?;
+            Ok(AttrArg::Value(key, input.parse()?))
+        } else if input.peek(syn::token::Paren) {
+            let content;
+            parenthesized!(content in input);
+            let args = parse_args(&content)?;
+            Ok(AttrArg::List(key, args))
+        } else {
+            Ok(AttrArg::Flag(key))
+        }
+    }
+}
+
+impl Parse for AttrValue {
+    fn parse(input: ParseStream) -> syn::Result<Self> {
+        if input.peek(syn::token::Brace) {
+            let content;
+            braced!(content in input);
+            let entries = Punctuated::<MapEntry, Token![,]>::parse_terminated(&content)?;
+            Ok(AttrValue::Map(entries.into_iter().map(|entry| (entry.key, entry.value)).collect()))
+        } else if input.peek(syn::token::Bracket) {
+            let content;
+            bracketed!(content in input);
+            let items = Punctuated::<Lit, Token![,]>::parse_terminated(&content)?;
+            Ok(AttrValue::Array(items.into_iter().collect()))
+        } else {
+            Ok(AttrValue::Lit(input.parse()?))
+        }
+    }
+}
+
+// 対応表の1要素 ("US" = "...")
+struct MapEntry {
+    key: Lit,
+    value: Lit,
+}
+
+impl Parse for MapEntry {
+    fn parse(input: ParseStream) -> syn::Result<Self> {
+        let key = input.parse()?;
+        input.parse::<Token![=]>()?;
+        let value = input.parse()?;
+        Ok(MapEntry { key, value })
+    }
+}
+
+// カンマ区切りの要素列を解析する
+pub fn parse_args(input: ParseStream) -> syn::Result<Vec<AttrArg>> {
+    let args = Punctuated::<AttrArg, Token![,]>::parse_terminated(input)?;
+    Ok(args.into_iter().collect())
+}
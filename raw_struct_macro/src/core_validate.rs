@@ -0,0 +1,171 @@
+// core_validate feature で生成する validate_core() の実装
+use quote::quote;
+use super::{bound_literal, error_code, field_error_key, get_inner_type, is_option_type, present_value, ContainerOptions, FlattenField, LengthUnit, RangeBound, StringValidation};
+
+// core の API だけで検査する validate_core() を生成する関数
+//
+// validate_core() の本体が std を使わないだけで、validate() などの他の生成コードは引き続き std に依存する。
+// 必須チェック・文字数・数値の解析と range(...) だけを対象にし、
+// エラーは validator::ValidationErrors の代わりに確保の要らない raw_struct::CoreError で返す
+pub(crate) fn core_impl(
+    raw_struct_ident: &syn::Ident,
+    fields: &syn::punctuated::Punctuated<syn::Field, syn::token::Comma>,
+    flatten_fields: &[FlattenField],
+    validations: &[StringValidation],
+    options: &ContainerOptions,
+) -> proc_macro2::TokenStream {
+    let flatten_idents = flatten_fields.iter().map(|flatten| &flatten.field.ident);
+    let checks = fields.iter().zip(validations).map(|(f, validation)| {
+        let field_name = &f.ident;
+        let error_key = field_error_key(f, options.serde_names);
+        let is_optional = is_option_type(&f.ty);
+        let base_type = get_inner_type(&f.ty).unwrap_or(&f.ty);
+        let type_str = quote!(#base_type).to_string();
+        let code_required = error_code(validation, "required");
+        let code_too_short = error_code(validation, "too_short");
+        let code_too_long = error_code(validation, "too_long");
+        let code_not_a_number = error_code(validation, "not_a_number");
+        let code_not_a_bool = error_code(validation, "not_a_bool");
+        let code_out_of_range = error_code(validation, "out_of_range");
+        let error = |code: &proc_macro2::TokenStream| quote! {
+            return Err(raw_struct::CoreError { field: #error_key, code: #code })
+        };
+
+        let required_check = if !is_optional && !options.all_optional && !options.strict_required {
+            let error = error(&code_required);
+            quote! {
+                if self.#field_name.is_none() {
+                    #error;
+                }
+            }
+        } else {
+            quote! {}
+        };
+        let present_value = present_value(quote! { self }, f, options.strict_required);
+
+        let is_float = matches!(type_str.as_str(), "f32" | "f64");
+        let value_check = match type_str.as_str() {
+            "String" => {
+                let len = match validation.length_unit {
+                    LengthUnit::Chars => quote! { value.chars().count() },
+                    LengthUnit::Bytes => quote! { value.len() },
+                    LengthUnit::Graphemes => quote! { raw_struct::unicode_segmentation::UnicodeSegmentation::graphemes(value.as_str(), true).count() },
+                    LengthUnit::Width => quote! { raw_struct::unicode_width::UnicodeWidthStr::width(value.as_str()) },
+                };
+                let min_check = validation.min_length.map(|min| {
+                    let error = error(&code_too_short);
+                    quote! {
+                        if #len < #min {
+                            #error;
+                        }
+                    }
+                });
+                let max_check = validation.max_length.map(|max| {
+                    let error = error(&code_too_long);
+                    quote! {
+                        if #len > #max {
+                            #error;
+                        }
+                    }
+                });
+                quote! {
+                    #min_check
+                    #max_check
+                }
+            },
+            "bool" => {
+                let error = error(&code_not_a_bool);
+                // validate() と同じく大文字・小文字を区別せず true / false / 1 / 0 を受け付ける
+                quote! {
+                    if !["true", "false", "1", "0"].iter().any(|b| value.eq_ignore_ascii_case(b)) {
+                        #error;
+                    }
+                }
+            },
+            // 基数や小数の切り捨ての指定がある整数は std の検証 (validate()) に任せる
+            "u8" | "u16" | "u32" | "u64" | "u128" | "i8" | "i16" | "i32" | "i64" | "i128" | "f32" | "f64"
+                if validation.radix.is_none() && !validation.coerce_float =>
+            {
+                let out_of_range = error(&code_out_of_range);
+                let range_checks = validation.range.iter().map(|(bound, lit)| {
+                    let limit = match bound_literal("range", lit, is_float) {
+                        Ok(limit) => limit,
+                        Err(e) => return e.to_compile_error(),
+                    };
+                    let condition = match bound {
+                        RangeBound::Ge => quote! { parsed < #limit },
+                        RangeBound::Le => quote! { parsed > #limit },
+                        RangeBound::Gt => quote! { parsed <= #limit },
+                        RangeBound::Lt => quote! { parsed >= #limit },
+                    };
+                    quote! {
+                        if #condition {
+                            #out_of_range;
+                        }
+                    }
+                });
+                let const_checks = validation.range_consts.iter().map(|(bound, path)| {
+                    let condition = match bound {
+                        RangeBound::Ge => quote! { parsed < #path },
+                        RangeBound::Le => quote! { parsed > #path },
+                        RangeBound::Gt => quote! { parsed <= #path },
+                        RangeBound::Lt => quote! { parsed >= #path },
+                    };
+                    quote! {
+                        if #condition {
+                            #out_of_range;
+                        }
+                    }
+                });
+                let not_a_number = error(&code_not_a_number);
+                if validation.range.is_empty() && validation.range_consts.is_empty() {
+                    quote! {
+                        if <#base_type as core::str::FromStr>::from_str(value).is_err() {
+                            #not_a_number;
+                        }
+                    }
+                } else {
+                    quote! {
+                        match <#base_type as core::str::FromStr>::from_str(value) {
+                            Ok(parsed) => {
+                                #(#range_checks)*
+                                #(#const_checks)*
+                            },
+                            Err(_) => {
+                                #not_a_number;
+                            }
+                        }
+                    }
+                }
+            },
+            _ => quote! {},
+        };
+        if value_check.is_empty() {
+            return required_check;
+        }
+        quote! {
+            #required_check
+            if let Some(value) = #present_value {
+                #value_check
+            }
+        }
+    });
+
+    quote! {
+        impl #raw_struct_ident {
+            /// core の API だけで行える範囲の検証を行い、最初に見つかったエラーを返す。
+            ///
+            /// このメソッドの本体が std を使わないだけで、生データ型や raw_struct クレートは
+            /// 引き続き std に依存するため、`#![no_std]` のクレートでは使えない。
+            ///
+            /// 必須チェック・文字数・数値の解析 (`core::str::FromStr`) と range(...) だけを対象にし、
+            /// それ以外の指定 (正規表現やフィールド間のチェックなど) は validate() でのみ検証する。
+            /// エラーはメモリを確保しない `raw_struct::CoreError` で返す。
+            pub fn validate_core(&self) -> Result<(), raw_struct::CoreError> {
+                #(#checks)*
+                #(self.#flatten_idents.validate_core()?;)*
+                Ok(())
+            }
+        }
+    }
+}
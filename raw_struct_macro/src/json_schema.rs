@@ -0,0 +1,117 @@
+// json_schema feature で生成する json_schema() の実装
+use quote::quote;
+use syn::Lit;
+use super::{bound_literal, flatten_raw_type, get_inner_type, is_option_type, FlattenField, RangeBound, StringValidation};
+
+// 宣言された型と制約を JSON Schema として返す json_schema() を生成する関数
+pub(crate) fn json_schema_impl(
+    raw_struct_ident: &syn::Ident,
+    fields: &syn::punctuated::Punctuated<syn::Field, syn::token::Comma>,
+    flatten_fields: &[FlattenField],
+    validations: &[StringValidation],
+    headers: &[String],
+    all_optional: bool,
+) -> proc_macro2::TokenStream {
+    let properties = fields.iter().zip(validations).map(|(f, validation)| {
+        let field_type = &f.ty;
+        let base_type = get_inner_type(field_type).unwrap_or(field_type);
+        let type_str = quote!(#base_type).to_string();
+        let is_float = matches!(type_str.as_str(), "f32" | "f64");
+
+        let mut keywords: Vec<(&str, proc_macro2::TokenStream)> = Vec::new();
+        match type_str.as_str() {
+            "u8" | "u16" | "u32" | "u64" | "u128" | "i8" | "i16" | "i32" | "i64" | "i128" => {
+                keywords.push(("type", quote! { "integer" }));
+                if type_str.starts_with('u') && validation.range.is_empty() {
+                    keywords.push(("minimum", quote! { 0 }));
+                }
+                if let Some(bits) = validation.bits {
+                    let max = proc_macro2::Literal::u128_unsuffixed((1u128 << bits) - 1);
+                    keywords.push(("maximum", quote! { #max }));
+                }
+            },
+            "f32" | "f64" => keywords.push(("type", quote! { "number" })),
+            "bool" => keywords.push(("type", quote! { "boolean" })),
+            _ => {
+                keywords.push(("type", quote! { "string" }));
+                if validation.uuid.is_some() {
+                    keywords.push(("format", quote! { "uuid" }));
+                }
+            },
+        }
+        for (bound, lit) in &validation.range {
+            let Ok(limit) = bound_literal("range", lit, is_float) else { continue };
+            let keyword = match bound {
+                RangeBound::Ge => "minimum",
+                RangeBound::Le => "maximum",
+                RangeBound::Gt => "exclusiveMinimum",
+                RangeBound::Lt => "exclusiveMaximum",
+            };
+            keywords.push((keyword, limit));
+        }
+        if let Some(min) = validation.min_length {
+            keywords.push(("minLength", quote! { #min }));
+        }
+        if let Some(max) = validation.max_length {
+            keywords.push(("maxLength", quote! { #max }));
+        }
+        if !validation.one_of.is_empty() {
+            let allowed = validation.one_of.iter().filter_map(|lit| match lit {
+                Lit::Str(_) => Some(quote! { #lit }),
+                _ => bound_literal("one_of", lit, is_float).ok(),
+            });
+            keywords.push(("enum", quote! { [#(#allowed),*] }));
+        }
+        match validation.regex_any.as_slice() {
+            [] => {},
+            [pattern] => keywords.push(("pattern", quote! { #pattern })),
+            patterns => keywords.push(("anyOf", quote! { [#({ "pattern": #patterns }),*] })),
+        }
+
+        let names = keywords.iter().map(|(name, _)| name);
+        let values = keywords.iter().map(|(_, value)| value);
+        quote! { { #(#names: #values),* } }
+    });
+    let required = fields
+        .iter()
+        .zip(headers)
+        .filter(|(f, _)| !all_optional && !is_option_type(&f.ty))
+        .map(|(_, header)| header);
+    let title = raw_struct_ident.to_string();
+    let flatten_raw_types = flatten_fields.iter().map(flatten_raw_type);
+    let flatten_prefixes = flatten_fields.iter().map(|flatten| &flatten.prefix);
+
+    quote! {
+        impl #raw_struct_ident {
+            /// フィールドの型・必須項目・制約を表す JSON Schema を返す。
+            ///
+            /// flatten したフィールドは、子のスキーマの列を prefix を付けて取り込む。
+            pub fn json_schema() -> serde_json::Value {
+                #[allow(unused_mut)]
+                let mut schema = serde_json::json!({
+                    "$schema": "https://json-schema.org/draft/2020-12/schema",
+                    "title": #title,
+                    "type": "object",
+                    "properties": {
+                        #(#headers: #properties),*
+                    },
+                    "required": [#(#required),*],
+                });
+                #({
+                    let child = <#flatten_raw_types>::json_schema();
+                    if let (Some(properties), Some(child_properties)) = (schema["properties"].as_object_mut(), child["properties"].as_object()) {
+                        for (name, property) in child_properties {
+                            properties.insert(format!("{}{}", #flatten_prefixes, name), property.clone());
+                        }
+                    }
+                    if let (Some(required), Some(child_required)) = (schema["required"].as_array_mut(), child["required"].as_array()) {
+                        for name in child_required.iter().filter_map(serde_json::Value::as_str) {
+                            required.push(serde_json::Value::from(format!("{}{}", #flatten_prefixes, name)));
+                        }
+                    }
+                })*
+                schema
+            }
+        }
+    }
+}
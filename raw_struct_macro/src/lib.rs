@@ -1,281 +1,5353 @@
+mod attr;
+mod row_validators;
+mod struct_checks;
+#[cfg(feature = "arbitrary")]
+mod arbitrary;
+#[cfg(feature = "json_schema")]
+mod json_schema;
+#[cfg(feature = "jsonschema")]
+mod schema_validation;
+#[cfg(feature = "core_validate")]
+mod core_validate;
+
 use proc_macro::TokenStream;
-use quote::quote;
-use syn::{parse_macro_input, DeriveInput, Data, Fields, Type, PathArguments, GenericArgument, Meta, NestedMeta, Lit, Attribute};
+use quote::{format_ident, quote};
+use syn::{parse_macro_input, DeriveInput, Data, Fields, Type, PathArguments, GenericArgument, Lit, Attribute};
+use syn::ext::IdentExt;
+use attr::{AttrArg, AttrValue};
+use row_validators::{outlier_detector, sequence_validator, unique_validator};
+use struct_checks::{checksum_checks, sum_checks};
+#[cfg(feature = "arbitrary")]
+use arbitrary::arbitrary_impl;
+#[cfg(feature = "json_schema")]
+use json_schema::json_schema_impl;
+#[cfg(feature = "jsonschema")]
+use schema_validation::schema_validation_impl;
+#[cfg(feature = "core_validate")]
+use core_validate::core_impl;
 
 // 文字列バリデーション用の設定を保持する構造体
+#[derive(Default)]
 struct StringValidation {
     min_length: Option<usize>,
     max_length: Option<usize>,
+    // min_length / max_length で数える単位
+    length_unit: LengthUnit,
+    // このフィールドが指定されている場合に必須となる他フィールド名
+    requires: Vec<syn::LitStr>,
+    // phone / phone(region = "JP") で指定された電話番号チェック
+    phone: Option<PhoneValidation>,
+    // 他フィールドの値に応じて切り替える形式チェック
+    format_by: Option<FormatByValidation>,
+    // semver のバージョン文字列 / バージョン要求文字列として検証するか
+    semver: bool,
+    semver_req: bool,
+    // in_set = "crate::SKU_SET" で指定された許可値の集合を返す式
+    in_set: Option<syn::Expr>,
+    // uuid / uuid(version = 4) で指定された UUID チェック
+    uuid: Option<UuidValidation>,
+    // IANA のタイムゾーン名 (例: "Asia/Tokyo") として検証するか
+    timezone: bool,
+    // Unix の glob パターン (例: "src/**/*.rs") として正しい書式かを検証するか
+    glob: bool,
+    // 符号なし整数が収まるべきビット幅
+    bits: Option<u32>,
+    // いずれか1つに一致すればよい正規表現の一覧
+    regex_any: Vec<String>,
+    // DB の VARCHAR(N) に収まるべき UTF-8 のバイト数
+    db_varchar: Option<usize>,
+    // 整数フィールドで "3.0" のような小数表記を受け付けるか
+    coerce_float: bool,
+    // mac / mac(normalize) で指定された MAC アドレスチェック
+    mac: Option<MacValidation>,
+    // socket / socket(port_range = "1024..=65535") で指定されたソケットアドレスチェック
+    socket: Option<SocketValidation>,
+    // date_range(min = "...", max = "...") で指定された日付の範囲 (下限, 上限)
+    date_range: Option<(Option<DateBound>, Option<DateBound>)>,
+    // 日付が営業日 (土日以外。休日は validate_with() の config で指定する) であるべきか
+    business_day: bool,
+    // 制御文字を含む値をエラーにするか (Some(true) ならタブは許可する)
+    no_control_chars: Option<bool>,
+    // 許可するファイルの拡張子 (ドットなし、大文字・小文字は区別しない)
+    file_ext: Vec<String>,
+    // 郵便番号 (123-4567) として検証するか
+    jp_postal_code: bool,
+    // 値の文字種 (全角カタカナ / 半角カタカナ / ひらがな)
+    kana: Option<Kana>,
+    // password(min_len = 8, require_upper, ...) で指定されたパスワードの強度チェック
+    password: Option<PasswordValidation>,
+    // hex_color / hex_color(hash_optional, lengths = [3, 6]) で指定されたカラーコードチェック
+    hex_color: Option<HexColorValidation>,
+    // card_expiry / card_expiry(allow_past) で指定されたカードの有効期限チェック
+    card_expiry: Option<CardExpiryValidation>,
+    // 整数を解析する基数 (例: 16)
+    radix: Option<u32>,
+    // normalized() で前後の空白を取り除くか
+    trim: bool,
+    // 前後に空白がある値をエラーにするか (値は変更しない)
+    no_surrounding_whitespace: bool,
+    // Luhn のチェックディジットを検証するか
+    luhn: bool,
+    // enum_name = "Status" で指定された、バリアント名で値を受け取る列挙型
+    enum_name: Option<syn::Path>,
+    // enum_name のバリアント名を大文字・小文字を区別せずに比べるか
+    case_insensitive: bool,
+    // IBAN (桁数と mod-97 のチェックサム) として検証するか
+    iban: bool,
+    // 値がちょうどこの桁数の ASCII 数字であるべきか (先頭の 0 も桁数に含める)
+    digits: Option<usize>,
+    // 数値の先頭に余分な 0 がある値 (007 など) をエラーにするか
+    no_leading_zeros: bool,
+    // エラーコードを検査の種類ごとのコードの代わりに使う値 (例: "name_too_long")
+    code: Option<syn::LitStr>,
+    // このフィールドに報告するエラーの上限 (超えた分は件数だけを知らせる)
+    max_errors: Option<usize>,
+    // RFC 1123 のホスト名として検証するか
+    hostname: bool,
+    // Option<String> で Some の場合に空白だけの値をエラーにするか
+    present_non_blank: bool,
+    // normalized() で未入力 (None) として扱う値 (例: "NULL", "N/A")
+    null_values: Vec<String>,
+    // normalized() で未入力の場合に補う値
+    default: Option<syn::LitStr>,
+    // normalized() で揃える大文字・小文字
+    case: Option<Case>,
+    // 値が一致すべき他フィールド名と、比較時に大文字・小文字 / 前後の空白を無視するか
+    // (ignore_case は starts_with / ends_with の比較にも使う)
+    same_as: Option<syn::LitStr>,
+    ignore_case: bool,
+    ignore_whitespace: bool,
+    // 値が始まるべき文字列・終わるべき文字列
+    starts_with: Option<String>,
+    ends_with: Option<String>,
+    // chrono::NaiveTime を解析する書式 (省略時は "%H:%M:%S")
+    time_format: Option<String>,
+    // range(min = 0, lt = 100) のような数値の範囲
+    range: Vec<(RangeBound, Lit)>,
+    // range(min_const = "crate::MIN_AGE") のような定数のパスで指定された範囲
+    // (定数はフィールドと同じ型である必要がある)
+    range_consts: Vec<(RangeBound, syn::Path)>,
+    // one_of = [1, 2, 5] / one_of = ["a", "b"] で指定された許可値
+    one_of: Vec<Lit>,
+    // 浮動小数点数が乗るべき刻み幅 (例: 0.05)
+    step: Option<Lit>,
+    // 行をまたいで値が単調増加することを要求するか
+    monotonic: bool,
+    // 列の値がデータ全体で重複していないかを検査するか
+    unique: bool,
+    // 列全体の平均から標準偏差の何倍離れた値を外れ値とするか (outlier(sigma = 3.0))
+    outlier: Option<f64>,
+    // NFC 正規化済みであることを要求するか
+    nfc: bool,
+    // normalize = "nfc" で指定された、変換時に NFC 正規化するか
+    normalize_nfc: bool,
+}
+
+// range(...) の各境界 (min / max は ge / le と同じ扱い)
+#[derive(Clone, Copy)]
+enum RangeBound {
+    Ge,
+    Le,
+    Gt,
+    Lt,
+}
+
+// ソケットアドレスのバリデーションの設定
+struct SocketValidation {
+    // 許可するポート番号の範囲 (両端を含む)
+    port_range: Option<(u16, u16)>,
+}
+
+// date_range(min = "1900-01-01", max = "today") の上限・下限
+enum DateBound {
+    // 固定の日付 (年, 月, 日)
+    Date(i32, u32, u32),
+    // 検証した時点の日付 (today / now)
+    Today,
+}
+
+// katakana / half_width_katakana / hiragana の指定
+#[derive(Clone, Copy)]
+enum Kana {
+    Katakana,
+    HalfWidthKatakana,
+    Hiragana,
+}
+
+// length_unit = "chars" / "bytes" / "graphemes" / "width" の指定
+#[derive(Clone, Copy, Default)]
+enum LengthUnit {
+    #[default]
+    Chars,
+    Bytes,
+    Graphemes,
+    // 端末などでの表示幅 (全角文字は 2 として数える)
+    Width,
+}
+
+// case = "lower" / "upper" の指定
+enum Case {
+    Lower,
+    Upper,
+}
+
+// 電話番号バリデーションの設定
+struct PhoneValidation {
+    // 国番号なしの番号を解釈するための地域 (例: JP)
+    region: Option<syn::Ident>,
+    // 変換後の値と normalized() で E.164 形式 (+819012345678) に揃えるか
+    normalize: bool,
+}
+
+// MAC アドレスバリデーションの設定
+struct MacValidation {
+    // 変換時に小文字のコロン区切り (aa:bb:cc:dd:ee:ff) に揃えるか
+    normalize: bool,
+}
+
+// カラーコード (#RRGGBB など) バリデーションの設定
+struct HexColorValidation {
+    // 先頭の # を省略してもよいか
+    hash_optional: bool,
+    // 許可する 16 進数の桁数 (省略時は 6 桁のみ)
+    lengths: Vec<usize>,
+}
+
+// パスワードの強度バリデーションの設定
+struct PasswordValidation {
+    // 最低限必要な文字数
+    min_len: usize,
+    // 英大文字を必須とするか
+    require_upper: bool,
+    // 英小文字を必須とするか
+    require_lower: bool,
+    // 数字を必須とするか
+    require_digit: bool,
+    // 記号 (英数字と空白以外の文字) を必須とするか
+    require_symbol: bool,
+}
+
+// カードの有効期限 (MM/YY) バリデーションの設定
+struct CardExpiryValidation {
+    // 過去の有効期限も受け付けるか (履歴データ向け)
+    allow_past: bool,
+}
+
+// UUID バリデーションの設定
+struct UuidValidation {
+    // 要求する UUID のバージョン (例: 4)
+    version: Option<usize>,
+}
+
+// format_by(field = "country", cases = { "US" = "..." }) の設定
+struct FormatByValidation {
+    // 形式を決めるフィールド名
+    field: syn::LitStr,
+    // そのフィールドの値と正規表現の組
+    cases: Vec<(String, String)>,
+}
+
+// raw_struct(sum(addends = ["subtotal", "tax"], equals = "total", epsilon = 0.01)) の設定
+struct SumCheck {
+    // 足し合わせるフィールド名
+    addends: Vec<syn::LitStr>,
+    // 合計と一致するべきフィールド名 (エラーはこのフィールドに付ける)
+    equals: syn::LitStr,
+    // 許容する誤差
+    epsilon: f64,
+}
+
+// raw_struct(percent_sum(fields = ["stocks", "bonds", "cash"], total = 100.0, epsilon = 0.01)) の設定
+struct PercentSumCheck {
+    // 合計を取るフィールド名 (エラーはこれらを + でつないだキーに付ける)
+    fields: Vec<syn::LitStr>,
+    // 合計が一致するべき値
+    total: f64,
+    // 許容する誤差
+    epsilon: f64,
+}
+
+// raw_struct(within_tolerance(actual = "reading", expected = "nominal", pct = 5.0)) の設定
+struct ToleranceCheck {
+    // 検査する値のフィールド名 (エラーはこのフィールドに付ける)
+    actual: syn::LitStr,
+    // 基準となる値のフィールド名
+    expected: syn::LitStr,
+    // 基準値に対して許容する割合 (%)
+    pct: f64,
+}
+
+// raw_struct(checksum(over = ["a", "b"], field = "crc", algo = "crc32")) の設定
+struct ChecksumCheck {
+    // チェックサムを計算する (連結する) フィールド名
+    over: Vec<syn::LitStr>,
+    // チェックサムの値 (16 進数) を持つフィールド名 (エラーはこのフィールドに付ける)
+    field: syn::LitStr,
+}
+
+// フィールドに raw_struct(flatten) / raw_struct(flatten(prefix = "home_")) を指定した、
+// RawStruct を derive した別の構造体を平坦に取り込むフィールド
+//
+// 生データ型には子の生データ型をそのまま持ち、列は子の列名に prefix を付けた名前で読み書きする
+// (列名で対応させるため、CSV の読み込みにはヘッダ行が必要になる)。
+// 検証エラーは子のエラーを親のフィールド名をキーとする入れ子 (ValidationErrorsKind::Struct) で返す
+struct FlattenField {
+    field: syn::Field,
+    // 子の列名に付ける接頭辞
+    prefix: String,
+}
+
+// 入れ子の指定 (uuid(...) など) とフィールドの raw_struct(...) に指定できるキーの一覧
+//
+// 綴りを誤ったキーが黙って無視されないよう、一覧にないキーはコンパイルエラーにする
+const UUID_KEYS: &[&str] = &["version"];
+const OUTLIER_KEYS: &[&str] = &["sigma"];
+const PHONE_KEYS: &[&str] = &["region", "normalize"];
+const MAC_KEYS: &[&str] = &["normalize"];
+const CARD_EXPIRY_KEYS: &[&str] = &["allow_past"];
+const PASSWORD_KEYS: &[&str] = &["min_len", "require_upper", "require_lower", "require_digit", "require_symbol"];
+const HEX_COLOR_KEYS: &[&str] = &["hash_optional", "lengths"];
+const SOCKET_KEYS: &[&str] = &["port_range"];
+const NO_CONTROL_CHARS_KEYS: &[&str] = &["allow_tab"];
+const DATE_RANGE_KEYS: &[&str] = &["min", "max"];
+const RANGE_KEYS: &[&str] = &[
+    "min", "max", "ge", "le", "gt", "lt",
+    "min_const", "max_const", "ge_const", "le_const", "gt_const", "lt_const",
+];
+const FORMAT_BY_KEYS: &[&str] = &["field", "cases"];
+const RAW_STRUCT_FIELD_KEYS: &[&str] = &["flatten"];
+const FLATTEN_KEYS: &[&str] = &["prefix"];
+const CHECKSUM_KEYS: &[&str] = &["over", "field", "algo"];
+const SUM_KEYS: &[&str] = &["addends", "equals", "epsilon"];
+const PERCENT_SUM_KEYS: &[&str] = &["fields", "total", "epsilon"];
+const TOLERANCE_KEYS: &[&str] = &["actual", "expected", "pct"];
+
+// 生データ型に生成する (またはトレイトから呼ばれる) メソッド名
+//
+// フィールド名から作るメソッド名がこれらと一致する場合は末尾に _value を付けて衝突を避ける
+const RESERVED_METHODS: &[&str] = &[
+    "normalized", "sanitize_all", "template", "try_into_lossy", "into_lenient_optional", "fields", "set_field", "from_map", "from_map_ci", "validate", "validate_fields", "validate_categorized", "validate_with", "validate_with_source", "lookup_values", "rules_markdown", "get_typed", "validate_with_observer",
+    "validate_anyhow", "validate_core", "validate_against_schema", "json_schema", "arbitrary_valid",
+    "clone", "default", "fmt", "deserialize",
+];
+// 生の識別子 (r#...) にもできないキーワード
+const NON_RAW_KEYWORDS: &[&str] = &["self", "Self", "super", "crate", "_"];
+
+// validate() を構成するチェック (borrowed の生データ型の validate() と共有する)
+struct ValidateChecks {
+    // フィールドごとのチェック
+    field_checks: Vec<proc_macro2::TokenStream>,
+    // 他のフィールドを参照するチェック
+    cross_field_checks: Vec<proc_macro2::TokenStream>,
+    // raw_struct(sum(...)) などの構造体全体のチェック
+    sum_checks: Vec<proc_macro2::TokenStream>,
+    checksum_checks: Vec<proc_macro2::TokenStream>,
+    // post_validate の呼び出し
+    post_validate: proc_macro2::TokenStream,
+    // max_errors を超えたエラーの切り詰め
+    error_caps: Vec<proc_macro2::TokenStream>,
+}
+
+// フィールドごとに生成するメソッドの名前
+struct MethodNames {
+    // 宣言された型に変換して返すメソッド
+    getter: syn::Ident,
+    // 値を単体で検証する関連関数
+    validate: syn::Ident,
+    // 検証の本体
+    check: syn::Ident,
+    // 検証せずに宣言された型に変換する関連関数
+    parse: syn::Ident,
+}
+// 解釈できなかった要素に対するコンパイルエラーを作る
+fn unexpected_arg(arg: &AttrArg, known: &[&str]) -> syn::Error {
+    let key = arg.key();
+    if known.contains(&key.to_string().as_str()) {
+        invalid_form(arg)
+    } else {
+        syn::Error::new_spanned(key, format!("不明なキーです: {}（指定可能なキー: {}）", key, known.join(", ")))
+    }
+}
+
+// キーは正しいが、値の形式 (フラグ・値・入れ子) が違う要素に対するコンパイルエラーを作る
+fn invalid_form(arg: &AttrArg) -> syn::Error {
+    let key = arg.key();
+    syn::Error::new_spanned(key, format!("{} の指定方法が正しくありません", key))
+}
+
+// max_errors の値 (1 以上の整数) を解析する
+fn parse_max_errors(key: &syn::Ident, value: &AttrValue) -> syn::Result<usize> {
+    let max = value.parse_int(key)?;
+    if max == 0 {
+        return Err(syn::Error::new_spanned(key, "max_errors には 1 以上の値を指定してください"));
+    }
+    Ok(max)
 }
 
 // 属性からバリデーション設定を解析する関数
-fn parse_string_validation(attrs: &[Attribute]) -> StringValidation {
-    let mut validation = StringValidation {
-        min_length: None,
-        max_length: None,
-    };
-
-    for attr in attrs {
-        if attr.path.is_ident("validate") {
-            if let Ok(Meta::List(meta_list)) = attr.parse_meta() {
-                for nested in meta_list.nested.iter() {
-                    if let NestedMeta::Meta(Meta::NameValue(name_value)) = nested {
-                        if name_value.path.is_ident("min_length") {
-                            if let Lit::Int(lit) = &name_value.lit {
-                                validation.min_length = lit.base10_parse().ok();
-                            }
-                        } else if name_value.path.is_ident("max_length") {
-                            if let Lit::Int(lit) = &name_value.lit {
-                                validation.max_length = lit.base10_parse().ok();
-                            }
-                        }
-                    }
-                }
+//
+// キーごとの解析は VALIDATE_RULES に任せる。値の型が違う・正規表現が不正などの誤りは、
+// 該当するリテラルの位置を指すコンパイルエラーとして返す (default の値はフィールドの型 ty に変換できるかも調べる)
+fn parse_string_validation(attrs: &[Attribute], ty: &Type) -> syn::Result<StringValidation> {
+    let mut validation = StringValidation::default();
+    let kind = type_kind(get_inner_type(ty).unwrap_or(ty));
+    for attr in attrs.iter().filter(|attr| attr.path.is_ident("validate")) {
+        for arg in attr.parse_args_with(attr::parse_args)? {
+            match VALIDATE_RULES.iter().find(|(key, _, _)| arg.key() == key) {
+                // 型に合わないキーは生成するチェックから外れてしまうため、黙って無視せずエラーにする
+                Some((_, accepts, _)) if !accepts.kinds.contains(&kind) => {
+                    return Err(syn::Error::new_spanned(
+                        arg.key(),
+                        format!("{} は{}のフィールドにのみ指定できます", arg.key(), accepts.label),
+                    ));
+                },
+                Some((_, _, parse)) => parse(&mut validation, arg)?,
+                None => return Err(unexpected_arg(&arg, &validate_keys())),
             }
         }
     }
 
-    validation
+    // radix などの指定は default より後に書かれることもあるため、すべて解析してから調べる
+    if let Some(lit) = &validation.default {
+        check_default_value(&validation, ty, lit)?;
+    }
+
+    Ok(validation)
 }
 
-#[proc_macro_derive(RawStruct, attributes(validate))]
-pub fn raw_struct_derive(input: TokenStream) -> TokenStream {
-    let input = parse_macro_input!(input as DeriveInput);
-    let struct_name = input.ident;
-    let raw_struct_name = format!("Raw{}", struct_name);
-    let raw_struct_ident = syn::Ident::new(&raw_struct_name, struct_name.span());
+// validate(...) のキー1つ分を解析して設定に反映する関数
+type ValidateRule = fn(&mut StringValidation, AttrArg) -> syn::Result<()>;
 
-    let fields = match input.data {
-        Data::Struct(ref data) => {
-            match data.fields {
-                Fields::Named(ref fields) => &fields.named,
-                _ => panic!("RawStruct は名前付きフィールドを持つ構造体のみサポートします"),
-            }
-        },
-        _ => panic!("RawStruct は構造体のみサポートします"),
-    };
+// validate(...) のキーを指定できるかを判定するための、フィールドの型 (Option<T> の場合は T) の分類
+#[derive(Clone, Copy, PartialEq)]
+enum TypeKind {
+    Unsigned,
+    Signed,
+    Float,
+    Bool,
+    String,
+    Date,
+    Time,
+    // 列挙型・uuid::Uuid・std::net::SocketAddr など、FromStr で解析するその他の型
+    Other,
+}
 
-    // すべてのフィールドをOption<String>として生成
-    let raw_fields = fields.iter().map(|f| {
-        let field_name = &f.ident;
-        quote! {
-            #field_name: Option<String>
-        }
-    });
+// 型を TypeKind に分類する関数
+fn type_kind(base_type: &Type) -> TypeKind {
+    match quote!(#base_type).to_string().as_str() {
+        "u8" | "u16" | "u32" | "u64" | "u128" => TypeKind::Unsigned,
+        "i8" | "i16" | "i32" | "i64" | "i128" => TypeKind::Signed,
+        "f32" | "f64" => TypeKind::Float,
+        "bool" => TypeKind::Bool,
+        "String" => TypeKind::String,
+        _ if is_naive_date(base_type) => TypeKind::Date,
+        _ if is_naive_time(base_type) => TypeKind::Time,
+        _ => TypeKind::Other,
+    }
+}
 
-    let validation_checks = fields.iter().map(|f| {
-        let field_name = &f.ident;
-        let field_type = &f.ty;
-        let validation = parse_string_validation(&f.attrs);
-        let is_optional = is_option_type(field_type);
-        let base_type = if is_optional {
-            get_inner_type(field_type).unwrap()
-        } else {
-            field_type
-        };
-        
-        let required_check = if !is_optional {
-            quote! {
-                if self.#field_name.is_none() {
-                    let mut err = validator::ValidationError::new(stringify!(#field_name));
-                    err.message = Some(format!("フィールド '{}' は必須項目です", stringify!(#field_name)).into());
-                    errors.add(stringify!(#field_name), err);
-                }
-            }
-        } else {
-            quote! {}
-        };
+// キーを指定できる型の一覧と、型が合わない場合のエラーメッセージに使う名前
+struct Accepts {
+    kinds: &'static [TypeKind],
+    label: &'static str,
+}
 
-        let type_str = quote!(#base_type).to_string();
-        let value_check = match type_str.as_str() {
-            "u8" | "u16" | "u32" | "u64" | "u128" => quote! {
-                if let Some(value) = &self.#field_name {
-                    match value {
-                        v if v.starts_with('-') => {
-                            let mut err = validator::ValidationError::new(stringify!(#field_name));
-                            err.message = Some(format!("フィールド '{}' に負の値 ({}) が指定されましたが、{}型は負の値を受け付けません", 
-                                stringify!(#field_name), 
-                                value,
-                                stringify!(#base_type)
-                            ).into());
-                            errors.add(stringify!(#field_name), err);
+const ANY: Accepts = Accepts {
+    kinds: &[TypeKind::Unsigned, TypeKind::Signed, TypeKind::Float, TypeKind::Bool, TypeKind::String, TypeKind::Date, TypeKind::Time, TypeKind::Other],
+    label: "",
+};
+const STRING: Accepts = Accepts { kinds: &[TypeKind::String], label: "文字列" };
+const UNSIGNED: Accepts = Accepts { kinds: &[TypeKind::Unsigned], label: "符号なし整数" };
+const INTEGER: Accepts = Accepts { kinds: &[TypeKind::Unsigned, TypeKind::Signed], label: "整数" };
+const INTEGER_OR_STRING: Accepts = Accepts { kinds: &[TypeKind::Unsigned, TypeKind::Signed, TypeKind::String], label: "整数・文字列" };
+const FLOAT: Accepts = Accepts { kinds: &[TypeKind::Float], label: "浮動小数点数" };
+const NUMBER: Accepts = Accepts { kinds: &[TypeKind::Unsigned, TypeKind::Signed, TypeKind::Float], label: "数値" };
+const NUMBER_OR_STRING: Accepts = Accepts {
+    kinds: &[TypeKind::Unsigned, TypeKind::Signed, TypeKind::Float, TypeKind::String],
+    label: "数値・文字列",
+};
+const DATE: Accepts = Accepts { kinds: &[TypeKind::Date], label: " chrono::NaiveDate " };
+const TIME: Accepts = Accepts { kinds: &[TypeKind::Time], label: " chrono::NaiveTime " };
+const ENUM: Accepts = Accepts { kinds: &[TypeKind::Other], label: "列挙型" };
+const UUID: Accepts = Accepts { kinds: &[TypeKind::String, TypeKind::Other], label: " String / uuid::Uuid " };
+const TIMEZONE: Accepts = Accepts { kinds: &[TypeKind::String, TypeKind::Other], label: " String / chrono_tz::Tz " };
+const GLOB: Accepts = Accepts { kinds: &[TypeKind::String, TypeKind::Other], label: " String / glob::Pattern " };
+const SOCKET: Accepts = Accepts { kinds: &[TypeKind::String, TypeKind::Other], label: " String / std::net::SocketAddr " };
+
+// validate(...) に指定できるキーと、そのキーを指定できる型と、解析方法の表
+//
+// 指定可能なキーの一覧 (validate_keys) もこの表から作るため、解析とキーの一覧がずれることはない
+const VALIDATE_RULES: &[(&str, Accepts, ValidateRule)] = &[
+    ("min_length", STRING, |validation, arg| {
+        match arg {
+            AttrArg::Value(key, value) => {
+                validation.min_length = Some(value.parse_int(&key)?);
+            },
+            other => return Err(invalid_form(&other)),
+        }
+        Ok(())
+    }),
+    ("max_length", STRING, |validation, arg| {
+        match arg {
+            AttrArg::Value(key, value) => {
+                validation.max_length = Some(value.parse_int(&key)?);
+            },
+            other => return Err(invalid_form(&other)),
+        }
+        Ok(())
+    }),
+    ("db_varchar", STRING, |validation, arg| {
+        match arg {
+            AttrArg::Value(key, value) => {
+                validation.db_varchar = Some(value.parse_int(&key)?);
+            },
+            other => return Err(invalid_form(&other)),
+        }
+        Ok(())
+    }),
+    ("bits", UNSIGNED, |validation, arg| {
+        match arg {
+            AttrArg::Value(key, value) => {
+                let bits = value.parse_int(&key)?;
+                if bits >= 128 {
+                    return Err(syn::Error::new_spanned(&key, "bits には 128 未満の値を指定してください"));
+                }
+                validation.bits = Some(bits);
+            },
+            other => return Err(invalid_form(&other)),
+        }
+        Ok(())
+    }),
+    ("requires", ANY, |validation, arg| {
+        match arg {
+            AttrArg::Value(key, value) => {
+                validation.requires.push(value.parse_str(&key)?);
+            },
+            other => return Err(invalid_form(&other)),
+        }
+        Ok(())
+    }),
+    ("regex_any", STRING, |validation, arg| {
+        match arg {
+            AttrArg::Value(key, value) => {
+                for pattern in value.parse_str_list(&key)? {
+                    if let Err(e) = regex::Regex::new(&pattern.value()) {
+                        return Err(syn::Error::new(pattern.span(), format!("regex_any の正規表現が不正です: {}", e)));
+                    }
+                    validation.regex_any.push(pattern.value());
+                }
+            },
+            other => return Err(invalid_form(&other)),
+        }
+        Ok(())
+    }),
+    ("radix", INTEGER, |validation, arg| {
+        match arg {
+            AttrArg::Value(key, value) => {
+                let radix = value.parse_int(&key)?;
+                if !(2..=36).contains(&radix) {
+                    return Err(syn::Error::new_spanned(&key, "radix には 2 ～ 36 の値を指定してください"));
+                }
+                validation.radix = Some(radix);
+            },
+            other => return Err(invalid_form(&other)),
+        }
+        Ok(())
+    }),
+    ("luhn", STRING, |validation, arg| {
+        match arg {
+            AttrArg::Flag(_) => {
+                validation.luhn = true;
+            },
+            other => return Err(invalid_form(&other)),
+        }
+        Ok(())
+    }),
+    ("enum_name", ENUM, |validation, arg| {
+        match arg {
+            AttrArg::Value(key, value) => {
+                let lit = value.parse_str(&key)?;
+                validation.enum_name = Some(lit.parse().map_err(|_| {
+                    syn::Error::new(lit.span(), "enum_name には列挙型の名前 (例: \"Status\") を指定してください")
+                })?);
+            },
+            other => return Err(invalid_form(&other)),
+        }
+        Ok(())
+    }),
+    ("case_insensitive", ENUM, |validation, arg| {
+        match arg {
+            AttrArg::Flag(_) => {
+                validation.case_insensitive = true;
+            },
+            other => return Err(invalid_form(&other)),
+        }
+        Ok(())
+    }),
+    ("iban", STRING, |validation, arg| {
+        match arg {
+            AttrArg::Flag(_) => {
+                validation.iban = true;
+            },
+            other => return Err(invalid_form(&other)),
+        }
+        Ok(())
+    }),
+    ("no_leading_zeros", NUMBER_OR_STRING, |validation, arg| {
+        match arg {
+            AttrArg::Flag(_) => {
+                validation.no_leading_zeros = true;
+            },
+            other => return Err(invalid_form(&other)),
+        }
+        Ok(())
+    }),
+    ("digits", INTEGER_OR_STRING, |validation, arg| {
+        match arg {
+            AttrArg::Value(key, value) => {
+                let digits = value.parse_int(&key)?;
+                if digits == 0 {
+                    return Err(syn::Error::new_spanned(&key, "digits には 1 以上の値を指定してください"));
+                }
+                validation.digits = Some(digits);
+            },
+            other => return Err(invalid_form(&other)),
+        }
+        Ok(())
+    }),
+    ("code", ANY, |validation, arg| {
+        match arg {
+            AttrArg::Value(key, value) => {
+                validation.code = Some(value.parse_str(&key)?);
+            },
+            other => return Err(invalid_form(&other)),
+        }
+        Ok(())
+    }),
+    ("max_errors", ANY, |validation, arg| {
+        match arg {
+            AttrArg::Value(key, value) => {
+                validation.max_errors = Some(parse_max_errors(&key, &value)?);
+            },
+            other => return Err(invalid_form(&other)),
+        }
+        Ok(())
+    }),
+    ("hostname", STRING, |validation, arg| {
+        match arg {
+            AttrArg::Flag(_) => {
+                validation.hostname = true;
+            },
+            other => return Err(invalid_form(&other)),
+        }
+        Ok(())
+    }),
+    ("present_non_blank", STRING, |validation, arg| {
+        match arg {
+            AttrArg::Flag(_) => {
+                validation.present_non_blank = true;
+            },
+            other => return Err(invalid_form(&other)),
+        }
+        Ok(())
+    }),
+    ("no_surrounding_whitespace", STRING, |validation, arg| {
+        match arg {
+            AttrArg::Flag(_) => {
+                validation.no_surrounding_whitespace = true;
+            },
+            other => return Err(invalid_form(&other)),
+        }
+        Ok(())
+    }),
+    ("trim", ANY, |validation, arg| {
+        match arg {
+            AttrArg::Flag(_) => {
+                validation.trim = true;
+            },
+            other => return Err(invalid_form(&other)),
+        }
+        Ok(())
+    }),
+    ("null_values", ANY, |validation, arg| {
+        match arg {
+            AttrArg::Value(key, value) => {
+                validation.null_values = value.parse_str_list(&key)?.iter().map(|lit| lit.value()).collect();
+            },
+            other => return Err(invalid_form(&other)),
+        }
+        Ok(())
+    }),
+    ("default", ANY, |validation, arg| {
+        match arg {
+            AttrArg::Value(key, value) => {
+                validation.default = Some(value.parse_str(&key)?);
+            },
+            other => return Err(invalid_form(&other)),
+        }
+        Ok(())
+    }),
+    ("length_unit", STRING, |validation, arg| {
+        match arg {
+            AttrArg::Value(key, value) => {
+                let lit = value.parse_str(&key)?;
+                validation.length_unit = match lit.value().as_str() {
+                    "chars" => LengthUnit::Chars,
+                    "bytes" => LengthUnit::Bytes,
+                    "graphemes" => LengthUnit::Graphemes,
+                    "width" => LengthUnit::Width,
+                    _ => return Err(syn::Error::new(lit.span(), "length_unit には \"chars\"、\"bytes\"、\"graphemes\"、\"width\" のいずれかを指定してください")),
+                };
+            },
+            other => return Err(invalid_form(&other)),
+        }
+        Ok(())
+    }),
+    ("case", ANY, |validation, arg| {
+        match arg {
+            AttrArg::Value(key, value) => {
+                let lit = value.parse_str(&key)?;
+                validation.case = Some(match lit.value().as_str() {
+                    "lower" => Case::Lower,
+                    "upper" => Case::Upper,
+                    _ => return Err(syn::Error::new(lit.span(), "case には \"lower\" または \"upper\" を指定してください")),
+                });
+            },
+            other => return Err(invalid_form(&other)),
+        }
+        Ok(())
+    }),
+    ("range", NUMBER, |validation, arg| {
+        match arg {
+            AttrArg::List(_, args) => {
+                for arg in args {
+                    let key = arg.key().to_string();
+                    let bound = match key.trim_end_matches("_const") {
+                        "min" | "ge" => RangeBound::Ge,
+                        "max" | "le" => RangeBound::Le,
+                        "gt" => RangeBound::Gt,
+                        "lt" => RangeBound::Lt,
+                        _ => return Err(unexpected_arg(&arg, RANGE_KEYS)),
+                    };
+                    if !RANGE_KEYS.contains(&key.as_str()) {
+                        return Err(unexpected_arg(&arg, RANGE_KEYS));
+                    }
+                    if key.ends_with("_const") {
+                        let AttrArg::Value(key, value) = arg else {
+                            return Err(unexpected_arg(&arg, RANGE_KEYS));
+                        };
+                        let lit = value.parse_str(&key)?;
+                        let path = lit.parse().map_err(|_| {
+                            syn::Error::new(lit.span(), format!("{} には定数のパス (例: \"crate::MIN_AGE\") を指定してください", key))
+                        })?;
+                        validation.range_consts.push((bound, path));
+                        continue;
+                    }
+                    match arg {
+                        AttrArg::Value(_, AttrValue::Lit(lit @ (Lit::Int(_) | Lit::Float(_)))) => {
+                            validation.range.push((bound, lit));
                         },
-                        v => match v.parse::<#base_type>() {
-                            Ok(_) => {},
-                            Err(e) => {
-                                let mut err = validator::ValidationError::new(stringify!(#field_name));
-                                err.message = Some(match e.to_string().contains("invalid digit") {
-                                    true => format!("フィールド '{}' の値 ({}) が数値ではありません", 
-                                        stringify!(#field_name),
-                                        value
-                                    ),
-                                    false => format!("フィールド '{}' の値 ({}) が {}型の範囲（0 ～ {}) を超えています", 
-                                        stringify!(#field_name),
-                                        value,
-                                        stringify!(#base_type),
-                                        #base_type::MAX
-                                    )
-                                }.into());
-                                errors.add(stringify!(#field_name), err);
-                            }
-                        }
+                        other => return Err(syn::Error::new_spanned(other.key(), format!("{} には数値を指定してください", other.key()))),
                     }
                 }
             },
-            "i8" | "i16" | "i32" | "i64" | "i128" => quote! {
-                if let Some(value) = &self.#field_name {
-                    match value.parse::<#base_type>() {
-                        Ok(_) => {},
-                        Err(e) => {
-                            let mut err = validator::ValidationError::new(stringify!(#field_name));
-                            err.message = Some(match e.to_string().contains("invalid digit") {
-                                true => format!("フィールド '{}' の値 ({}) が数値ではありません", 
-                                    stringify!(#field_name),
-                                    value
-                                ),
-                                false => format!("フィールド '{}' の値 ({}) が {}型の範囲（{} ～ {}) を超えています", 
-                                    stringify!(#field_name),
-                                    value,
-                                    stringify!(#base_type),
-                                    #base_type::MIN,
-                                    #base_type::MAX
-                                )
-                            }.into());
-                            errors.add(stringify!(#field_name), err);
-                        }
+            other => return Err(invalid_form(&other)),
+        }
+        Ok(())
+    }),
+    ("same_as", ANY, |validation, arg| {
+        match arg {
+            AttrArg::Value(key, value) => {
+                validation.same_as = Some(value.parse_str(&key)?);
+            },
+            other => return Err(invalid_form(&other)),
+        }
+        Ok(())
+    }),
+    ("starts_with", STRING, |validation, arg| {
+        match arg {
+            AttrArg::Value(key, value) => {
+                validation.starts_with = Some(value.parse_str(&key)?.value());
+            },
+            other => return Err(invalid_form(&other)),
+        }
+        Ok(())
+    }),
+    ("ends_with", STRING, |validation, arg| {
+        match arg {
+            AttrArg::Value(key, value) => {
+                validation.ends_with = Some(value.parse_str(&key)?.value());
+            },
+            other => return Err(invalid_form(&other)),
+        }
+        Ok(())
+    }),
+    ("ignore_case", ANY, |validation, arg| {
+        match arg {
+            AttrArg::Flag(_) => {
+                validation.ignore_case = true;
+            },
+            other => return Err(invalid_form(&other)),
+        }
+        Ok(())
+    }),
+    ("ignore_whitespace", ANY, |validation, arg| {
+        match arg {
+            AttrArg::Flag(_) => {
+                validation.ignore_whitespace = true;
+            },
+            other => return Err(invalid_form(&other)),
+        }
+        Ok(())
+    }),
+    ("time_format", TIME, |validation, arg| {
+        match arg {
+            AttrArg::Value(key, value) => {
+                validation.time_format = Some(value.parse_str(&key)?.value());
+            },
+            other => return Err(invalid_form(&other)),
+        }
+        Ok(())
+    }),
+    ("one_of", NUMBER_OR_STRING, |validation, arg| {
+        match arg {
+            AttrArg::Value(_, AttrValue::Array(items)) => {
+                for item in items {
+                    match item {
+                        Lit::Int(_) | Lit::Float(_) | Lit::Str(_) => validation.one_of.push(item),
+                        other => return Err(syn::Error::new(other.span(), "one_of の要素には数値または文字列を指定してください")),
                     }
                 }
             },
-            "f32" | "f64" => quote! {
-                if let Some(value) = &self.#field_name {
-                    match value.parse::<#base_type>() {
-                        Ok(_) => {},
-                        Err(_) => {
-                            let mut err = validator::ValidationError::new(stringify!(#field_name));
-                            err.message = Some(format!("フィールド '{}' の値 ({}) が有効な浮動小数点数ではありません", 
-                                stringify!(#field_name),
-                                value
-                            ).into());
-                            errors.add(stringify!(#field_name), err);
-                        }
+            other => return Err(invalid_form(&other)),
+        }
+        Ok(())
+    }),
+    ("step", FLOAT, |validation, arg| {
+        match arg {
+            AttrArg::Value(_, AttrValue::Lit(lit @ (Lit::Int(_) | Lit::Float(_)))) => {
+                let step = lit_as_f64(&lit)?;
+                if step <= 0.0 {
+                    return Err(syn::Error::new(lit.span(), "step には正の数値を指定してください"));
+                }
+                validation.step = Some(lit);
+            },
+            other => return Err(invalid_form(&other)),
+        }
+        Ok(())
+    }),
+    ("unique", ANY, |validation, arg| {
+        match arg {
+            AttrArg::Flag(_) => {
+                validation.unique = true;
+            },
+            other => return Err(invalid_form(&other)),
+        }
+        Ok(())
+    }),
+    ("outlier", NUMBER, |validation, arg| {
+        match arg {
+            AttrArg::Flag(_) => {
+                validation.outlier = Some(3.0);
+            },
+            AttrArg::List(_, args) => {
+                let mut sigma = 3.0;
+                for arg in args {
+                    match arg {
+                        AttrArg::Value(key, AttrValue::Lit(lit @ (Lit::Int(_) | Lit::Float(_)))) if key == "sigma" => {
+                            sigma = lit_as_f64(&lit)?;
+                            if sigma <= 0.0 {
+                                return Err(syn::Error::new(lit.span(), "sigma には正の数値を指定してください"));
+                            }
+                        },
+                        other => return Err(unexpected_arg(&other, OUTLIER_KEYS)),
                     }
                 }
+                validation.outlier = Some(sigma);
             },
-            "bool" => quote! {
-                if let Some(value) = &self.#field_name {
-                    match value.to_lowercase().as_str() {
-                        "true" | "false" | "1" | "0" => {},
-                        _ => {
-                            let mut err = validator::ValidationError::new(stringify!(#field_name));
-                            err.message = Some(format!("フィールド '{}' の値 ({}) が真偽値ではありません。'true'/'false' または '1'/'0' を使用してください", 
-                                stringify!(#field_name),
-                                value
-                            ).into());
-                            errors.add(stringify!(#field_name), err);
-                        }
+            other => return Err(invalid_form(&other)),
+        }
+        Ok(())
+    }),
+    ("monotonic", ANY, |validation, arg| {
+        match arg {
+            AttrArg::Flag(_) => {
+                validation.monotonic = true;
+            },
+            other => return Err(invalid_form(&other)),
+        }
+        Ok(())
+    }),
+    ("nfc", STRING, |validation, arg| {
+        match arg {
+            AttrArg::Flag(_) => {
+                validation.nfc = true;
+            },
+            other => return Err(invalid_form(&other)),
+        }
+        Ok(())
+    }),
+    ("normalize", STRING, |validation, arg| {
+        match arg {
+            AttrArg::Value(key, value) => {
+                let lit = value.parse_str(&key)?;
+                if lit.value() != "nfc" {
+                    return Err(syn::Error::new(lit.span(), "normalize には \"nfc\" を指定してください"));
+                }
+                validation.normalize_nfc = true;
+            },
+            other => return Err(invalid_form(&other)),
+        }
+        Ok(())
+    }),
+    ("coerce_float", INTEGER, |validation, arg| {
+        match arg {
+            AttrArg::Flag(_) => {
+                validation.coerce_float = true;
+            },
+            other => return Err(invalid_form(&other)),
+        }
+        Ok(())
+    }),
+    ("semver", STRING, |validation, arg| {
+        match arg {
+            AttrArg::Flag(_) => {
+                validation.semver = true;
+            },
+            other => return Err(invalid_form(&other)),
+        }
+        Ok(())
+    }),
+    ("semver_req", STRING, |validation, arg| {
+        match arg {
+            AttrArg::Flag(_) => {
+                validation.semver_req = true;
+            },
+            other => return Err(invalid_form(&other)),
+        }
+        Ok(())
+    }),
+    ("in_set", STRING, |validation, arg| {
+        match arg {
+            AttrArg::Value(key, value) => {
+                let lit = value.parse_str(&key)?;
+                validation.in_set = Some(lit.parse().map_err(|_| {
+                    syn::Error::new(lit.span(), "in_set には集合を返す式 (例: \"crate::SKU_SET\") を指定してください")
+                })?);
+            },
+            other => return Err(invalid_form(&other)),
+        }
+        Ok(())
+    }),
+    ("mac", STRING, |validation, arg| {
+        match arg {
+            AttrArg::Flag(_) => {
+                validation.mac = Some(MacValidation { normalize: false });
+            },
+            AttrArg::List(_, args) => {
+                let mut mac = MacValidation { normalize: false };
+                for arg in args {
+                    match arg {
+                        AttrArg::Flag(key) if key == "normalize" => mac.normalize = true,
+                        other => return Err(unexpected_arg(&other, MAC_KEYS)),
                     }
                 }
+                validation.mac = Some(mac);
             },
-            "String" => {
-                let length_validation = match (validation.min_length, validation.max_length) {
-                    (Some(min), Some(max)) => quote! {
-                        let len = value.chars().count();
-                        if len < #min || len > #max {
-                            let mut err = validator::ValidationError::new(stringify!(#field_name));
-                            err.message = Some(format!("フィールド '{}' の文字数が制限（{} ～ {} 文字）を超えています（現在: {} 文字）", 
-                                stringify!(#field_name),
-                                #min,
-                                #max,
-                                len
-                            ).into());
-                            errors.add(stringify!(#field_name), err);
-                        }
-                    },
-                    (Some(min), None) => quote! {
-                        let len = value.chars().count();
-                        if len < #min {
-                            let mut err = validator::ValidationError::new(stringify!(#field_name));
-                            err.message = Some(format!("フィールド '{}' の文字数が最小文字数（{} 文字）未満です（現在: {} 文字）", 
-                                stringify!(#field_name),
-                                #min,
-                                len
-                            ).into());
-                            errors.add(stringify!(#field_name), err);
-                        }
-                    },
-                    (None, Some(max)) => quote! {
-                        let len = value.chars().count();
-                        if len > #max {
-                            let mut err = validator::ValidationError::new(stringify!(#field_name));
-                            err.message = Some(format!("フィールド '{}' の文字数が最大文字数（{} 文字）を超えています（現在: {} 文字）", 
-                                stringify!(#field_name),
-                                #max,
-                                len
-                            ).into());
-                            errors.add(stringify!(#field_name), err);
-                        }
-                    },
-                    (None, None) => quote! {}
-                };
-
-                quote! {
-                    if let Some(value) = &self.#field_name {
-                        #length_validation
+            other => return Err(invalid_form(&other)),
+        }
+        Ok(())
+    }),
+    ("socket", SOCKET, |validation, arg| {
+        match arg {
+            AttrArg::Flag(_) => {
+                validation.socket = Some(SocketValidation { port_range: None });
+            },
+            AttrArg::List(_, args) => {
+                let mut socket = SocketValidation { port_range: None };
+                for arg in args {
+                    match arg {
+                        AttrArg::Value(key, value) if key == "port_range" => {
+                            let lit = value.parse_str(&key)?;
+                            socket.port_range = Some(parse_port_range(&lit.value()).ok_or_else(|| {
+                                syn::Error::new(lit.span(), "port_range には \"1024..=65535\" や \"8000..9000\" の形式で指定してください")
+                            })?);
+                        },
+                        other => return Err(unexpected_arg(&other, SOCKET_KEYS)),
                     }
                 }
+                validation.socket = Some(socket);
             },
-            _ => quote! {
-                if let Some(value) = &self.#field_name {
-                    match value.parse::<#base_type>() {
-                        Ok(_) => {},
-                        Err(_) => {
-                            let mut err = validator::ValidationError::new(stringify!(#field_name));
-                            err.message = Some(format!("フィールド '{}' の値 ({}) が {}型として無効です", 
-                                stringify!(#field_name),
-                                value,
-                                stringify!(#base_type)
-                            ).into());
-                            errors.add(stringify!(#field_name), err);
-                        }
+            other => return Err(invalid_form(&other)),
+        }
+        Ok(())
+    }),
+    ("date_range", DATE, |validation, arg| {
+        match arg {
+            AttrArg::List(_, args) => {
+                let (mut min, mut max) = (None, None);
+                for arg in args {
+                    match arg {
+                        AttrArg::Value(key, value) if key == "min" || key == "max" => {
+                            let lit = value.parse_str(&key)?;
+                            let bound = parse_date_bound(&lit.value()).ok_or_else(|| {
+                                syn::Error::new(lit.span(), format!("{} には \"1900-01-01\" の形式の日付か \"today\" を指定してください", key))
+                            })?;
+                            if key == "min" {
+                                min = Some(bound);
+                            } else {
+                                max = Some(bound);
+                            }
+                        },
+                        other => return Err(unexpected_arg(&other, DATE_RANGE_KEYS)),
                     }
                 }
-            }
-        };
-
-        quote! {
-            #required_check
-            #value_check
+                validation.date_range = Some((min, max));
+            },
+            other => return Err(invalid_form(&other)),
         }
-    });
-
-    let expanded = quote! {
-        #[derive(Debug, Clone, serde::Deserialize)]
-        pub struct #raw_struct_ident {
-            #(#raw_fields,)*
+        Ok(())
+    }),
+    ("business_day", DATE, |validation, arg| {
+        match arg {
+            AttrArg::Flag(_) => {
+                validation.business_day = true;
+            },
+            other => return Err(invalid_form(&other)),
         }
-
-        impl validator::Validate for #raw_struct_ident {
-            fn validate(&self) -> Result<(), validator::ValidationErrors> {
-                let mut errors = validator::ValidationErrors::new();
-                
-                #(#validation_checks)*
-
-                if errors.is_empty() {
-                    Ok(())
-                } else {
-                    Err(errors)
+        Ok(())
+    }),
+    ("no_control_chars", STRING, |validation, arg| {
+        match arg {
+            AttrArg::Flag(_) => {
+                validation.no_control_chars = Some(false);
+            },
+            AttrArg::List(_, args) => {
+                let mut allow_tab = false;
+                for arg in args {
+                    match arg {
+                        AttrArg::Flag(key) if key == "allow_tab" => allow_tab = true,
+                        other => return Err(unexpected_arg(&other, NO_CONTROL_CHARS_KEYS)),
+                    }
                 }
-            }
+                validation.no_control_chars = Some(allow_tab);
+            },
+            other => return Err(invalid_form(&other)),
         }
-    };
+        Ok(())
+    }),
+    ("file_ext", STRING, |validation, arg| {
+        match arg {
+            AttrArg::Value(key, value) => {
+                validation.file_ext = value
+                    .parse_str_list(&key)?
+                    .iter()
+                    .map(|ext| ext.value().trim_start_matches('.').to_lowercase())
+                    .collect();
+            },
+            other => return Err(invalid_form(&other)),
+        }
+        Ok(())
+    }),
+    ("jp_postal_code", STRING, |validation, arg| {
+        match arg {
+            AttrArg::Flag(_) => {
+                validation.jp_postal_code = true;
+            },
+            other => return Err(invalid_form(&other)),
+        }
+        Ok(())
+    }),
+    ("katakana", STRING, parse_kana),
+    ("half_width_katakana", STRING, parse_kana),
+    ("hiragana", STRING, parse_kana),
+    ("hex_color", STRING, |validation, arg| {
+        match arg {
+            AttrArg::Flag(_) => {
+                validation.hex_color = Some(HexColorValidation { hash_optional: false, lengths: vec![6] });
+            },
+            AttrArg::List(_, args) => {
+                let mut hex_color = HexColorValidation { hash_optional: false, lengths: vec![6] };
+                for arg in args {
+                    match arg {
+                        AttrArg::Flag(key) if key == "hash_optional" => hex_color.hash_optional = true,
+                        AttrArg::Value(key, value) if key == "lengths" => {
+                            let lengths = value.parse_int_list(&key)?;
+                            if lengths.is_empty() || lengths.iter().any(|length| ![3, 4, 6, 8].contains(length)) {
+                                return Err(syn::Error::new_spanned(&key, "lengths には 3, 4, 6, 8 のいずれかを指定してください"));
+                            }
+                            hex_color.lengths = lengths;
+                        },
+                        other => return Err(unexpected_arg(&other, HEX_COLOR_KEYS)),
+                    }
+                }
+                validation.hex_color = Some(hex_color);
+            },
+            other => return Err(invalid_form(&other)),
+        }
+        Ok(())
+    }),
+    ("password", STRING, |validation, arg| {
+        match arg {
+            AttrArg::List(_, args) => {
+                let mut password = PasswordValidation {
+                    min_len: 8,
+                    require_upper: false,
+                    require_lower: false,
+                    require_digit: false,
+                    require_symbol: false,
+                };
+                for arg in args {
+                    match arg {
+                        AttrArg::Value(key, value) if key == "min_len" => password.min_len = value.parse_int(&key)?,
+                        AttrArg::Flag(key) if key == "require_upper" => password.require_upper = true,
+                        AttrArg::Flag(key) if key == "require_lower" => password.require_lower = true,
+                        AttrArg::Flag(key) if key == "require_digit" => password.require_digit = true,
+                        AttrArg::Flag(key) if key == "require_symbol" => password.require_symbol = true,
+                        other => return Err(unexpected_arg(&other, PASSWORD_KEYS)),
+                    }
+                }
+                validation.password = Some(password);
+            },
+            other => return Err(invalid_form(&other)),
+        }
+        Ok(())
+    }),
+    ("card_expiry", STRING, |validation, arg| {
+        match arg {
+            AttrArg::Flag(_) => {
+                validation.card_expiry = Some(CardExpiryValidation { allow_past: false });
+            },
+            AttrArg::List(_, args) => {
+                let mut card_expiry = CardExpiryValidation { allow_past: false };
+                for arg in args {
+                    match arg {
+                        AttrArg::Flag(key) if key == "allow_past" => card_expiry.allow_past = true,
+                        other => return Err(unexpected_arg(&other, CARD_EXPIRY_KEYS)),
+                    }
+                }
+                validation.card_expiry = Some(card_expiry);
+            },
+            other => return Err(invalid_form(&other)),
+        }
+        Ok(())
+    }),
+    ("timezone", TIMEZONE, |validation, arg| {
+        match arg {
+            AttrArg::Flag(_) => {
+                validation.timezone = true;
+            },
+            other => return Err(invalid_form(&other)),
+        }
+        Ok(())
+    }),
+    ("glob", GLOB, |validation, arg| {
+        match arg {
+            AttrArg::Flag(_) => {
+                validation.glob = true;
+            },
+            other => return Err(invalid_form(&other)),
+        }
+        Ok(())
+    }),
+    ("uuid", UUID, |validation, arg| {
+        match arg {
+            AttrArg::Flag(_) => {
+                validation.uuid = Some(UuidValidation { version: None });
+            },
+            AttrArg::List(_, args) => {
+                let mut uuid = UuidValidation { version: None };
+                for arg in args {
+                    match arg {
+                        AttrArg::Value(key, value) if key == "version" => {
+                            uuid.version = Some(value.parse_int(&key)?);
+                        },
+                        other => return Err(unexpected_arg(&other, UUID_KEYS)),
+                    }
+                }
+                validation.uuid = Some(uuid);
+            },
+            other => return Err(invalid_form(&other)),
+        }
+        Ok(())
+    }),
+    ("phone", STRING, |validation, arg| {
+        match arg {
+            AttrArg::Flag(_) => {
+                validation.phone = Some(PhoneValidation { region: None, normalize: false });
+            },
+            AttrArg::List(_, args) => {
+                let mut phone = PhoneValidation { region: None, normalize: false };
+                for arg in args {
+                    match arg {
+                        AttrArg::Value(key, value) if key == "region" => {
+                            let lit = value.parse_str(&key)?;
+                            phone.region = Some(lit.parse().map_err(|_| {
+                                syn::Error::new(lit.span(), "region には地域コード (例: \"JP\") を指定してください")
+                            })?);
+                        },
+                        AttrArg::Flag(key) if key == "normalize" => phone.normalize = true,
+                        other => return Err(unexpected_arg(&other, PHONE_KEYS)),
+                    }
+                }
+                validation.phone = Some(phone);
+            },
+            other => return Err(invalid_form(&other)),
+        }
+        Ok(())
+    }),
+    ("format_by", ANY, |validation, arg| {
+        match arg {
+            AttrArg::List(key, args) => {
+                let mut field = None;
+                let mut cases = Vec::new();
+                for arg in args {
+                    match arg {
+                        AttrArg::Value(key, value) if key == "field" => {
+                            field = Some(value.parse_str(&key)?);
+                        },
+                        AttrArg::Value(key, value) if key == "cases" => {
+                            for (case, pattern) in value.parse_str_map(&key)? {
+                                if let Err(e) = regex::Regex::new(&pattern.value()) {
+                                    return Err(syn::Error::new(pattern.span(), format!("format_by の正規表現が不正です: {}", e)));
+                                }
+                                cases.push((case.value(), pattern.value()));
+                            }
+                        },
+                        other => return Err(unexpected_arg(&other, FORMAT_BY_KEYS)),
+                    }
+                }
+                match field {
+                    Some(field) => validation.format_by = Some(FormatByValidation { field, cases }),
+                    None => return Err(syn::Error::new_spanned(&key, "format_by には field = \"...\" を指定してください")),
+                }
+            },
+            other => return Err(invalid_form(&other)),
+        }
+        Ok(())
+    }),
+];
+
+// validate(...) に指定できるキーの一覧
+//
+// 綴りを誤ったキーが黙って無視されないよう、一覧にないキーはコンパイルエラーにする
+fn validate_keys() -> Vec<&'static str> {
+    VALIDATE_RULES.iter().map(|(key, _, _)| *key).collect()
+}
+
+// katakana / half_width_katakana / hiragana を解析する関数 (3つのキーで共有する)
+fn parse_kana(validation: &mut StringValidation, arg: AttrArg) -> syn::Result<()> {
+    match arg {
+        AttrArg::Flag(key) => {
+            if validation.kana.is_some() {
+                return Err(syn::Error::new_spanned(&key, "katakana / half_width_katakana / hiragana はいずれか1つだけ指定してください"));
+            }
+            validation.kana = Some(match key.to_string().as_str() {
+                "katakana" => Kana::Katakana,
+                "half_width_katakana" => Kana::HalfWidthKatakana,
+                _ => Kana::Hiragana,
+            });
+        },
+        other => return Err(invalid_form(&other)),
+    }
+    Ok(())
+}
+
+// default の値をフィールドの型に変換できるか、生成時に値のわかる制約を満たすかを調べる関数
+//
+// 変換できない値や range / one_of / 文字数などに反する値は定義の誤りとしてコンパイル時に報告する。
+// in_set や定数の range のように生成時に調べられない制約は、try_into_lossy() では解析だけして補う
+fn check_default_value(validation: &StringValidation, ty: &Type, lit: &syn::LitStr) -> syn::Result<()> {
+    let base_type = get_inner_type(ty).unwrap_or(ty);
+    let type_str = quote!(#base_type).to_string();
+    let value = lit.value();
+    // Option<T> の空文字は未入力 (None) になる
+    if value.is_empty() && is_option_type(ty) {
+        return Ok(());
+    }
+    let (digits, radix) = match validation.radix {
+        Some(radix) => {
+            let prefix = match radix {
+                16 => "0x",
+                8 => "0o",
+                2 => "0b",
+                _ => "",
+            };
+            let digits = value
+                .strip_prefix(prefix)
+                .or_else(|| value.strip_prefix(&prefix.to_uppercase()))
+                .unwrap_or(&value);
+            (digits, radix)
+        },
+        None if validation.coerce_float => (value.split_once('.').map_or(value.as_str(), |(int_part, _)| int_part), 10),
+        None => (value.as_str(), 10),
+    };
+    let valid = match type_str.as_str() {
+        "u8" => u8::from_str_radix(digits, radix).is_ok(),
+        "u16" => u16::from_str_radix(digits, radix).is_ok(),
+        "u32" => u32::from_str_radix(digits, radix).is_ok(),
+        "u64" => u64::from_str_radix(digits, radix).is_ok(),
+        "u128" => u128::from_str_radix(digits, radix).is_ok(),
+        "i8" => i8::from_str_radix(digits, radix).is_ok(),
+        "i16" => i16::from_str_radix(digits, radix).is_ok(),
+        "i32" => i32::from_str_radix(digits, radix).is_ok(),
+        "i64" => i64::from_str_radix(digits, radix).is_ok(),
+        "i128" => i128::from_str_radix(digits, radix).is_ok(),
+        "f32" => value.parse::<f32>().is_ok_and(f32::is_finite),
+        "f64" => value.parse::<f64>().is_ok_and(f64::is_finite),
+        "bool" => ["true", "false", "1", "0"].iter().any(|b| value.eq_ignore_ascii_case(b)),
+        _ => true,
+    };
+    if !valid {
+        return Err(syn::Error::new(lit.span(), format!("default の値 ({}) を {} に変換できません", value, type_str)));
+    }
+
+    // 満たしていない制約のキーを求める (型ごとのチェックと同じ条件で比べる)
+    let violated = if let Some(number) = default_number(&type_str, digits, radix, &value) {
+        numeric_default_violation(validation, &type_str, number)?
+    } else if type_str == "String" {
+        string_default_violation(validation, &value)
+    } else {
+        None
+    };
+    let digits_violated = validation.digits.is_some_and(|n| value.len() != n || !value.bytes().all(|b| b.is_ascii_digit()));
+    let unsigned = value.strip_prefix(['+', '-']).unwrap_or(&value).as_bytes();
+    let leading_zeros = validation.no_leading_zeros && unsigned.len() > 1 && unsigned[0] == b'0' && unsigned[1].is_ascii_digit();
+    let violated = violated
+        .or(digits_violated.then_some("digits"))
+        .or(leading_zeros.then_some("no_leading_zeros"));
+    match violated {
+        Some(key) => Err(syn::Error::new(lit.span(), format!("default の値 ({}) が {} の指定を満たしていません", value, key))),
+        None => Ok(()),
+    }
+}
+
+// 数値のフィールドの default の値を f64 で求める (数値の型でなければ None)
+fn default_number(type_str: &str, digits: &str, radix: u32, value: &str) -> Option<f64> {
+    match type_str {
+        "u8" | "u16" | "u32" | "u64" | "u128" | "i8" | "i16" | "i32" | "i64" | "i128" => i128::from_str_radix(digits, radix)
+            .map(|n| n as f64)
+            .or_else(|_| u128::from_str_radix(digits, radix).map(|n| n as f64))
+            .ok(),
+        "f32" | "f64" => value.parse().ok(),
+        _ => None,
+    }
+}
+
+// 数値の default の値が満たしていない制約 (range / one_of / bits) のキーを求める
+fn numeric_default_violation(validation: &StringValidation, type_str: &str, number: f64) -> syn::Result<Option<&'static str>> {
+    // 文字列の one_of はどの数値とも一致しないよう NaN として扱う
+    let lit_number = |lit: &Lit| -> syn::Result<f64> {
+        match lit {
+            Lit::Str(_) => Ok(f64::NAN),
+            _ => lit_as_f64(lit),
+        }
+    };
+    for (bound, lit) in &validation.range {
+        let limit = lit_number(lit)?;
+        let in_range = match bound {
+            RangeBound::Ge => number >= limit,
+            RangeBound::Le => number <= limit,
+            RangeBound::Gt => number > limit,
+            RangeBound::Lt => number < limit,
+        };
+        if !in_range {
+            return Ok(Some("range"));
+        }
+    }
+    if !validation.one_of.is_empty() {
+        let mut allowed = validation.one_of.iter().map(lit_number);
+        if !allowed.any(|limit| limit.is_ok_and(|limit| limit == number)) {
+            return Ok(Some("one_of"));
+        }
+    }
+    if let Some(bits) = validation.bits.filter(|_| type_str.starts_with('u')) {
+        if number >= 2f64.powi(bits as i32) {
+            return Ok(Some("bits"));
+        }
+    }
+    Ok(None)
+}
+
+// 文字列の default の値が満たしていない制約のキーを求める
+//
+// graphemes / width の文字数は外部のクレートが必要なため調べない
+fn string_default_violation(validation: &StringValidation, value: &str) -> Option<&'static str> {
+    let len = match validation.length_unit {
+        LengthUnit::Chars => Some(value.chars().count()),
+        LengthUnit::Bytes => Some(value.len()),
+        LengthUnit::Graphemes | LengthUnit::Width => None,
+    };
+    if let Some(len) = len {
+        if validation.min_length.is_some_and(|min| len < min) {
+            return Some("min_length");
+        }
+        if validation.max_length.is_some_and(|max| len > max) {
+            return Some("max_length");
+        }
+    }
+    if validation.db_varchar.is_some_and(|max| value.len() > max) {
+        return Some("db_varchar");
+    }
+    if !validation.one_of.is_empty() && !validation.one_of.iter().any(|lit| matches!(lit, Lit::Str(s) if s.value() == value)) {
+        return Some("one_of");
+    }
+    let affix_value = if validation.ignore_case { value.to_lowercase() } else { value.to_string() };
+    let affix_pattern = |affix: &String| if validation.ignore_case { affix.to_lowercase() } else { affix.clone() };
+    if validation.starts_with.as_ref().is_some_and(|prefix| !affix_value.starts_with(&affix_pattern(prefix))) {
+        return Some("starts_with");
+    }
+    if validation.ends_with.as_ref().is_some_and(|suffix| !affix_value.ends_with(&affix_pattern(suffix))) {
+        return Some("ends_with");
+    }
+    if validation.no_surrounding_whitespace && value != value.trim() {
+        return Some("no_surrounding_whitespace");
+    }
+    // 正規表現は解析時に正しいことを確かめてある
+    let regex_matches = |pattern: &String| regex::Regex::new(pattern).is_ok_and(|re| re.is_match(value));
+    if !validation.regex_any.is_empty() && !validation.regex_any.iter().any(regex_matches) {
+        return Some("regex_any");
+    }
+    None
+}
+
+// 構造体全体に対する設定 (#[raw_struct(...)])
+#[derive(Default)]
+struct ContainerOptions {
+    // 生データ型と関連トレイトを再エクスポートするモジュール名
+    export_in: Option<syn::Ident>,
+    // 全フィールドの検証後に呼ぶ fn(&RawX, &mut ValidationErrors) のパス
+    post_validate: Option<syn::Path>,
+    // エラーのキーに #[serde(rename = "...")] の名前 (CSV のヘッダ名) を使うか
+    serde_names: bool,
+    // 生データ型に #[non_exhaustive] を付けるか
+    non_exhaustive: bool,
+    // Option でないフィールドも validate() では必須としないか
+    all_optional: bool,
+    // フィールドごとのメソッド名をスネークケースに揃えるか
+    snake_case_methods: bool,
+    // 必須のフィールドを生データ型で Option<String> ではなく String にするか
+    strict_required: bool,
+    // try_into_lossy() を生成するか (Option でないフィールドの型には Default が必要になる)
+    lossy: bool,
+    // フィールドの合計が別のフィールドと一致するかのチェック
+    sums: Vec<SumCheck>,
+    // フィールドの合計が決まった値 (100 など) になるかのチェック
+    percent_sums: Vec<PercentSumCheck>,
+    // フィールドの値が別のフィールドの値から一定の割合の範囲に収まるかのチェック
+    tolerances: Vec<ToleranceCheck>,
+    // 他のフィールドのチェックサムが一致するかのチェック
+    checksums: Vec<ChecksumCheck>,
+    // 入力を借用する生データ型 (RawXRef<'a>) も生成するか
+    borrowed: bool,
+    // 各フィールドに報告するエラーの上限 (フィールドの validate(max_errors) が優先)
+    max_errors: Option<usize>,
+    // validate() を呼ぶ自由関数 validate_raw_xxx() も生成するか
+    free_fn: bool,
+}
+
+// 構造体の属性から設定を解析する関数
+fn parse_container_options(attrs: &[Attribute]) -> syn::Result<ContainerOptions> {
+    let mut options = ContainerOptions::default();
+    for attr in attrs.iter().filter(|attr| attr.path.is_ident("raw_struct")) {
+        for arg in attr.parse_args_with(attr::parse_args)? {
+            match CONTAINER_RULES.iter().find(|(key, _)| arg.key() == key) {
+                Some((_, parse)) => parse(&mut options, arg)?,
+                None => return Err(unexpected_arg(&arg, &container_keys())),
+            }
+        }
+    }
+
+    if options.strict_required && options.all_optional {
+        return Err(syn::Error::new(proc_macro2::Span::call_site(), "strict_required と all_optional は同時に指定できません"));
+    }
+
+    Ok(options)
+}
+
+// raw_struct(...) のキー1つ分を解析して設定に反映する関数
+type ContainerRule = fn(&mut ContainerOptions, AttrArg) -> syn::Result<()>;
+
+// 構造体の raw_struct(...) に指定できるキーと、その解析方法の表
+const CONTAINER_RULES: &[(&str, ContainerRule)] = &[
+    ("export_in", |options, arg| {
+        match arg {
+            AttrArg::Value(key, value) => {
+                // derive マクロは別のモジュールに項目を追加できないため、
+                // 構造体と同じ場所に生成するモジュールの名前として扱う
+                let lit = value.parse_str(&key)?;
+                let path = lit.parse::<syn::Path>().map_err(|_| {
+                    syn::Error::new(lit.span(), "export_in にはモジュール名 (例: \"raw\") を指定してください")
+                })?;
+                match path.get_ident() {
+                    Some(module) => options.export_in = Some(module.clone()),
+                    None => return Err(syn::Error::new(lit.span(),
+                        "export_in にはパス (crate::raw など) を指定できません。derive マクロは構造体と同じ場所にしか項目を生成できないため、\
+                         構造体と同じ場所に作るモジュールの名前 (例: \"raw\") を指定し、必要なら pub use で別のモジュールから再エクスポートしてください"
+                    )),
+                }
+            },
+            other => return Err(invalid_form(&other)),
+        }
+        Ok(())
+    }),
+    ("all_optional", |options, arg| {
+        match arg {
+            AttrArg::Flag(_) => {
+                options.all_optional = true;
+            },
+            other => return Err(invalid_form(&other)),
+        }
+        Ok(())
+    }),
+    ("strict_required", |options, arg| {
+        match arg {
+            AttrArg::Flag(_) => {
+                options.strict_required = true;
+            },
+            other => return Err(invalid_form(&other)),
+        }
+        Ok(())
+    }),
+    ("sum", |options, arg| {
+        match arg {
+            AttrArg::List(key, args) => {
+                let mut addends = Vec::new();
+                let mut equals = None;
+                let mut epsilon = 0.0;
+                for arg in args {
+                    match arg {
+                        AttrArg::Value(key, value) if key == "addends" => {
+                            addends = value.parse_str_list(&key)?;
+                        },
+                        AttrArg::Value(key, value) if key == "equals" => {
+                            equals = Some(value.parse_str(&key)?);
+                        },
+                        AttrArg::Value(key, AttrValue::Lit(lit @ (Lit::Int(_) | Lit::Float(_)))) if key == "epsilon" => {
+                            epsilon = lit_as_f64(&lit)?;
+                            if epsilon < 0.0 {
+                                return Err(syn::Error::new(lit.span(), "epsilon には 0 以上の数値を指定してください"));
+                            }
+                        },
+                        other => return Err(unexpected_arg(&other, SUM_KEYS)),
+                    }
+                }
+                if addends.is_empty() {
+                    return Err(syn::Error::new_spanned(&key, "sum には addends で足し合わせるフィールドを指定してください"));
+                }
+                let equals = equals.ok_or_else(|| {
+                    syn::Error::new_spanned(&key, "sum には equals で合計と比べるフィールドを指定してください")
+                })?;
+                options.sums.push(SumCheck { addends, equals, epsilon });
+            },
+            other => return Err(invalid_form(&other)),
+        }
+        Ok(())
+    }),
+    ("percent_sum", |options, arg| {
+        match arg {
+            AttrArg::List(key, args) => {
+                let mut percent_fields = Vec::new();
+                let mut total = 100.0;
+                let mut epsilon = 0.0;
+                for arg in args {
+                    match arg {
+                        AttrArg::Value(key, value) if key == "fields" => {
+                            percent_fields = value.parse_str_list(&key)?;
+                        },
+                        AttrArg::Value(key, AttrValue::Lit(lit @ (Lit::Int(_) | Lit::Float(_)))) if key == "total" => {
+                            total = lit_as_f64(&lit)?;
+                        },
+                        AttrArg::Value(key, AttrValue::Lit(lit @ (Lit::Int(_) | Lit::Float(_)))) if key == "epsilon" => {
+                            epsilon = lit_as_f64(&lit)?;
+                            if epsilon < 0.0 {
+                                return Err(syn::Error::new(lit.span(), "epsilon には 0 以上の数値を指定してください"));
+                            }
+                        },
+                        other => return Err(unexpected_arg(&other, PERCENT_SUM_KEYS)),
+                    }
+                }
+                if percent_fields.len() < 2 {
+                    return Err(syn::Error::new_spanned(&key, "percent_sum には fields で 2 つ以上のフィールドを指定してください"));
+                }
+                options.percent_sums.push(PercentSumCheck { fields: percent_fields, total, epsilon });
+            },
+            other => return Err(invalid_form(&other)),
+        }
+        Ok(())
+    }),
+    ("within_tolerance", |options, arg| {
+        match arg {
+            AttrArg::List(key, args) => {
+                let mut actual = None;
+                let mut expected = None;
+                let mut pct = None;
+                for arg in args {
+                    match arg {
+                        AttrArg::Value(key, value) if key == "actual" => {
+                            actual = Some(value.parse_str(&key)?);
+                        },
+                        AttrArg::Value(key, value) if key == "expected" => {
+                            expected = Some(value.parse_str(&key)?);
+                        },
+                        AttrArg::Value(key, AttrValue::Lit(lit @ (Lit::Int(_) | Lit::Float(_)))) if key == "pct" => {
+                            let value = lit_as_f64(&lit)?;
+                            if value < 0.0 {
+                                return Err(syn::Error::new(lit.span(), "pct には 0 以上の数値を指定してください"));
+                            }
+                            pct = Some(value);
+                        },
+                        other => return Err(unexpected_arg(&other, TOLERANCE_KEYS)),
+                    }
+                }
+                let actual = actual.ok_or_else(|| {
+                    syn::Error::new_spanned(&key, "within_tolerance には actual で検査するフィールドを指定してください")
+                })?;
+                let expected = expected.ok_or_else(|| {
+                    syn::Error::new_spanned(&key, "within_tolerance には expected で基準となるフィールドを指定してください")
+                })?;
+                let pct = pct.ok_or_else(|| {
+                    syn::Error::new_spanned(&key, "within_tolerance には pct で許容する割合 (%) を指定してください")
+                })?;
+                options.tolerances.push(ToleranceCheck { actual, expected, pct });
+            },
+            other => return Err(invalid_form(&other)),
+        }
+        Ok(())
+    }),
+    ("checksum", |options, arg| {
+        match arg {
+            AttrArg::List(key, args) => {
+                let mut over = Vec::new();
+                let mut field = None;
+                for arg in args {
+                    match arg {
+                        AttrArg::Value(key, value) if key == "over" => {
+                            over = value.parse_str_list(&key)?;
+                        },
+                        AttrArg::Value(key, value) if key == "field" => {
+                            field = Some(value.parse_str(&key)?);
+                        },
+                        // 今のところ CRC-32 のみに対応する
+                        AttrArg::Value(key, value) if key == "algo" => {
+                            let lit = value.parse_str(&key)?;
+                            if lit.value() != "crc32" {
+                                return Err(syn::Error::new(lit.span(), "algo には \"crc32\" を指定してください"));
+                            }
+                        },
+                        other => return Err(unexpected_arg(&other, CHECKSUM_KEYS)),
+                    }
+                }
+                if over.is_empty() {
+                    return Err(syn::Error::new_spanned(&key, "checksum には over でチェックサムを計算するフィールドを指定してください"));
+                }
+                let field = field.ok_or_else(|| {
+                    syn::Error::new_spanned(&key, "checksum には field でチェックサムの値を持つフィールドを指定してください")
+                })?;
+                options.checksums.push(ChecksumCheck { over, field });
+            },
+            other => return Err(invalid_form(&other)),
+        }
+        Ok(())
+    }),
+    ("borrowed", |options, arg| {
+        match arg {
+            AttrArg::Flag(_) => {
+                options.borrowed = true;
+            },
+            other => return Err(invalid_form(&other)),
+        }
+        Ok(())
+    }),
+    ("lossy", |options, arg| {
+        match arg {
+            AttrArg::Flag(_) => {
+                options.lossy = true;
+            },
+            other => return Err(invalid_form(&other)),
+        }
+        Ok(())
+    }),
+    ("free_fn", |options, arg| {
+        match arg {
+            AttrArg::Flag(_) => {
+                options.free_fn = true;
+            },
+            other => return Err(invalid_form(&other)),
+        }
+        Ok(())
+    }),
+    ("snake_case_methods", |options, arg| {
+        match arg {
+            AttrArg::Flag(_) => {
+                options.snake_case_methods = true;
+            },
+            other => return Err(invalid_form(&other)),
+        }
+        Ok(())
+    }),
+    ("non_exhaustive", |options, arg| {
+        match arg {
+            AttrArg::Flag(_) => {
+                options.non_exhaustive = true;
+            },
+            other => return Err(invalid_form(&other)),
+        }
+        Ok(())
+    }),
+    ("serde_names", |options, arg| {
+        match arg {
+            AttrArg::Flag(_) => {
+                options.serde_names = true;
+            },
+            other => return Err(invalid_form(&other)),
+        }
+        Ok(())
+    }),
+    ("max_errors", |options, arg| {
+        match arg {
+            AttrArg::Value(key, value) => {
+                options.max_errors = Some(parse_max_errors(&key, &value)?);
+            },
+            other => return Err(invalid_form(&other)),
+        }
+        Ok(())
+    }),
+    ("post_validate", |options, arg| {
+        match arg {
+            AttrArg::Value(key, value) => {
+                let lit = value.parse_str(&key)?;
+                options.post_validate = Some(lit.parse().map_err(|_| {
+                    syn::Error::new(lit.span(), "post_validate には関数のパス (例: \"crate::checks::verify_record\") を指定してください")
+                })?);
+            },
+            other => return Err(invalid_form(&other)),
+        }
+        Ok(())
+    }),
+];
+
+// 構造体の raw_struct(...) に指定できるキーの一覧 (CONTAINER_RULES から作る)
+fn container_keys() -> Vec<&'static str> {
+    CONTAINER_RULES.iter().map(|(key, _)| *key).collect()
+}
+
+// フィールドを raw_struct(flatten) の指定のあるものとないものに分ける関数
+fn split_flatten_fields(
+    fields: &syn::punctuated::Punctuated<syn::Field, syn::token::Comma>,
+) -> syn::Result<(Vec<FlattenField>, syn::punctuated::Punctuated<syn::Field, syn::token::Comma>)> {
+    let mut flatten_fields = Vec::new();
+    let mut scalar_fields = syn::punctuated::Punctuated::new();
+    for f in fields {
+        let mut prefix = None;
+        for attr in f.attrs.iter().filter(|attr| attr.path.is_ident("raw_struct")) {
+            for arg in attr.parse_args_with(attr::parse_args)? {
+                match arg {
+                    AttrArg::Flag(key) if key == "flatten" => {
+                        prefix = Some(String::new());
+                    },
+                    AttrArg::List(key, args) if key == "flatten" => {
+                        let mut value = String::new();
+                        for arg in args {
+                            match arg {
+                                AttrArg::Value(key, lit) if key == "prefix" => {
+                                    value = lit.parse_str(&key)?.value();
+                                },
+                                other => return Err(unexpected_arg(&other, FLATTEN_KEYS)),
+                            }
+                        }
+                        prefix = Some(value);
+                    },
+                    other => return Err(unexpected_arg(&other, RAW_STRUCT_FIELD_KEYS)),
+                }
+            }
+        }
+        match prefix {
+            Some(prefix) => {
+                if is_option_type(&f.ty) {
+                    return Err(syn::Error::new_spanned(&f.ty, "flatten は Option でないフィールドにのみ指定できます"));
+                }
+                if let Some(attr) = f.attrs.iter().find(|attr| attr.path.is_ident("validate")) {
+                    return Err(syn::Error::new_spanned(attr, "flatten したフィールドには validate を指定できません (子の構造体に指定してください)"));
+                }
+                flatten_fields.push(FlattenField { field: f.clone(), prefix });
+            },
+            None => scalar_fields.push(f.clone()),
+        }
+    }
+    Ok((flatten_fields, scalar_fields))
+}
+
+// flatten したフィールドの生データ型 (<Child as raw_struct::HasRaw>::Raw)
+fn flatten_raw_type(flatten: &FlattenField) -> proc_macro2::TokenStream {
+    let ty = &flatten.field.ty;
+    quote! { <#ty as raw_struct::HasRaw>::Raw }
+}
+
+#[proc_macro_derive(RawStruct, attributes(validate, raw_struct, serde))]
+pub fn raw_struct_derive(input: TokenStream) -> TokenStream {
+    let input = parse_macro_input!(input as DeriveInput);
+    match expand(input) {
+        Ok(expanded) => TokenStream::from(expanded),
+        Err(e) => TokenStream::from(e.to_compile_error()),
+    }
+}
+
+// ユニットバリアントだけの列挙型に raw_struct::EnumNames を実装する
+#[proc_macro_derive(EnumNames)]
+pub fn enum_names_derive(input: TokenStream) -> TokenStream {
+    let input = parse_macro_input!(input as DeriveInput);
+    match expand_enum_names(input) {
+        Ok(expanded) => TokenStream::from(expanded),
+        Err(e) => TokenStream::from(e.to_compile_error()),
+    }
+}
+
+// EnumNames derive の本体。バリアント名はそのまま (r# を除いて) 使う
+fn expand_enum_names(input: DeriveInput) -> syn::Result<proc_macro2::TokenStream> {
+    let enum_name = &input.ident;
+    let variants = match &input.data {
+        Data::Enum(data) => &data.variants,
+        _ => return Err(syn::Error::new_spanned(enum_name, "EnumNames は列挙型のみサポートします")),
+    };
+    for variant in variants {
+        if !matches!(variant.fields, Fields::Unit) {
+            return Err(syn::Error::new_spanned(variant, "EnumNames はフィールドを持たないバリアントのみサポートします"));
+        }
+    }
+    let variant_idents = variants.iter().map(|variant| &variant.ident).collect::<Vec<_>>();
+    let names = variant_idents.iter().map(|ident| ident.unraw().to_string()).collect::<Vec<_>>();
+    let (impl_generics, ty_generics, where_clause) = input.generics.split_for_impl();
+
+    Ok(quote! {
+        impl #impl_generics raw_struct::EnumNames for #enum_name #ty_generics #where_clause {
+            const NAMES: &'static [&'static str] = &[#(#names),*];
+
+            fn from_name(name: &str) -> Option<Self> {
+                match name {
+                    #(#names => Some(Self::#variant_idents),)*
+                    _ => None,
+                }
+            }
+        }
+    })
+}
+
+// 指定に必要な raw_struct の feature のうち、有効になっていないものを (feature 名, 指定) として返す関数
+//
+// 生成コードは raw_struct が feature ごとに再エクスポートするクレートを参照するため、
+// 解決できないパスのエラーになる前に、どの feature が必要かをここで知らせる
+fn missing_feature(base_type: &Type, validation: &StringValidation) -> Option<(&'static str, &'static str)> {
+    let uses_chrono = is_naive_time(base_type)
+        || validation.date_range.is_some()
+        || validation.business_day
+        || validation.card_expiry.as_ref().is_some_and(|card_expiry| !card_expiry.allow_past)
+        || (cfg!(feature = "arbitrary") && is_naive_date(base_type));
+    [
+        (uses_chrono, cfg!(feature = "chrono"), "chrono", "chrono::NaiveTime のフィールドや date_range / business_day / card_expiry"),
+        (validation.timezone, cfg!(feature = "timezone"), "timezone", "validate(timezone)"),
+        (validation.glob, cfg!(feature = "glob"), "glob", "validate(glob)"),
+        (validation.phone.is_some(), cfg!(feature = "phone"), "phone", "validate(phone)"),
+        (validation.semver || validation.semver_req, cfg!(feature = "semver"), "semver", "validate(semver) / validate(semver_req)"),
+        (validation.uuid.is_some(), cfg!(feature = "uuid"), "uuid", "validate(uuid)"),
+        (validation.nfc || validation.normalize_nfc, cfg!(feature = "unicode"), "unicode", "validate(nfc) / validate(normalize = \"nfc\")"),
+        (matches!(validation.length_unit, LengthUnit::Graphemes), cfg!(feature = "graphemes"), "graphemes", "validate(length_unit = \"graphemes\")"),
+        (matches!(validation.length_unit, LengthUnit::Width), cfg!(feature = "width"), "width", "validate(length_unit = \"width\")"),
+    ]
+    .into_iter()
+    .find(|(used, enabled, _, _)| *used && !enabled)
+    .map(|(_, _, feature, usage)| (feature, usage))
+}
+
+// derive の本体。属性の誤りは位置付きの syn::Error として返す
+fn expand(input: DeriveInput) -> syn::Result<proc_macro2::TokenStream> {
+    let struct_name = input.ident;
+    let raw_struct_name = format!("Raw{}", struct_name);
+    let raw_struct_ident = syn::Ident::new(&raw_struct_name, struct_name.span());
+    let options = parse_container_options(&input.attrs)?;
+
+    let fields = match input.data {
+        Data::Struct(ref data) => {
+            match data.fields {
+                Fields::Named(ref fields) => &fields.named,
+                _ => return Err(syn::Error::new_spanned(&data.fields, "RawStruct は名前付きフィールドを持つ構造体のみサポートします")),
+            }
+        },
+        _ => return Err(syn::Error::new_spanned(&struct_name, "RawStruct は構造体のみサポートします")),
+    };
+    // flatten したフィールドは子の生データ型に任せ、以降の生成はそれ以外のフィールドだけを対象にする
+    let (flatten_fields, fields) = split_flatten_fields(fields)?;
+    let fields = &fields;
+    if !flatten_fields.is_empty() && (options.lossy || options.borrowed) {
+        return Err(syn::Error::new_spanned(&flatten_fields[0].field, "flatten は lossy / borrowed と同時に指定できません"));
+    }
+
+    // 各フィールドの属性はここで一度だけ解析する
+    let validations = fields
+        .iter()
+        .map(|f| parse_string_validation(&f.attrs, &f.ty))
+        .collect::<syn::Result<Vec<_>>>()?;
+    for (f, validation) in fields.iter().zip(&validations) {
+        let base_type = get_inner_type(&f.ty).unwrap_or(&f.ty);
+        if validation.case_insensitive && validation.enum_name.is_none() {
+            return Err(syn::Error::new_spanned(&f.ty, "case_insensitive は enum_name と一緒に指定してください"));
+        }
+        if let Some((feature, usage)) = missing_feature(base_type, validation) {
+            return Err(syn::Error::new_spanned(&f.ty, format!("{} を使うには raw_struct の {} feature を有効にしてください", usage, feature)));
+        }
+    }
+    if let (Some(checksum), false) = (options.checksums.first(), cfg!(feature = "checksum")) {
+        return Err(syn::Error::new(checksum.field.span(), "raw_struct(checksum(...)) を使うには raw_struct の checksum feature を有効にしてください"));
+    }
+
+    // すべてのフィールドをOption<String>として生成
+    // (CSV のヘッダ名と対応させるため serde の rename は引き継ぐ)
+    //
+    // strict_required の場合、必須のフィールドは String になり、列がなければデシリアライズ自体が失敗する
+    // (列はあるが空の場合は空文字としてデシリアライズされ、型変換時のエラーになる)
+    let raw_fields = fields.iter().map(|f| {
+        let field_name = &f.ident;
+        let vis = &f.vis;
+        // flatten がある場合は serde の実装を手で書くため、rename は列名の対応表 (headers) で扱う
+        let rename = serde_rename(f)
+            .filter(|_| flatten_fields.is_empty())
+            .map(|name| quote! { #[serde(rename = #name)] });
+        let raw_type = if is_strict(f, options.strict_required) {
+            quote! { String }
+        } else {
+            quote! { Option<String> }
+        };
+        quote! {
+            #rename
+            #vis #field_name: #raw_type
+        }
+    });
+
+    // 生成するメソッド名 (予約された名前やキーワードとの衝突を避ける)
+    let method_names = method_names(fields, options.snake_case_methods)?;
+
+    let (validation_checks, field_validators): (Vec<_>, Vec<_>) = fields
+        .iter()
+        .zip(&validations)
+        .zip(&method_names)
+        .map(|((f, validation), names)| field_check(&raw_struct_ident, f, validation, names, &options))
+        .unzip();
+
+    // 複数フィールドにまたがるチェック
+    let cross_field_checks = fields
+        .iter()
+        .zip(&validations)
+        .map(|(f, validation)| cross_field_check(fields, f, validation, &options))
+        .collect::<syn::Result<Vec<_>>>()?;
+
+    let (normalized_fields, sanitize_fields) = normalize_fields(fields, &validations, &options);
+
+    // 行をまたぐ単調増加のチェックは前の行の値を覚えておく必要があるため、
+    // validate() とは別の状態を持つ型として生成する
+    let sequence_validator = sequence_validator(&struct_name, &raw_struct_ident, fields, &validations, &method_names, options.serde_names);
+    let unique_validator = unique_validator(&struct_name, &raw_struct_ident, fields, &validations, &method_names, &options);
+    let outlier_detector = outlier_detector(&struct_name, &raw_struct_ident, fields, &validations, &method_names)?;
+
+    let field_names = fields.iter().map(|f| field_error_name(f, options.serde_names)).collect::<Vec<_>>();
+    let structural_code_checks = fields.iter().zip(&validations).map(|(f, validation)| {
+        match structural_codes(f, validation).as_slice() {
+            [] => quote! { false },
+            codes => quote! { matches!(code, #(#codes)|*) },
+        }
+    });
+    let self_field_refs = fields.iter().map(|f| raw_field_ref(quote! { self }, f, options.strict_required)).collect::<Vec<_>>();
+
+    let (runtime_checks, lookup_captures) = runtime_checks(fields, &validations, &method_names, &options);
+
+    let try_from_impls = try_from_impls(&struct_name, &raw_struct_ident, fields, &flatten_fields, &validations, &method_names, &options);
+
+    let lossy_fn = lossy_fn(&struct_name, fields, &validations, &method_names, &options);
+
+    let lenient_fn = lenient_fn(&struct_name, fields, &flatten_fields, &method_names, &options);
+
+    // set_field() で列の値を設定する代入 (strict_required のフィールドは未入力を空文字にする)
+    let set_field_assigns = fields.iter().map(|f| {
+        let field_name = &f.ident;
+        if is_strict(f, options.strict_required) {
+            quote! { self.#field_name = value.unwrap_or_default() }
+        } else {
+            quote! { self.#field_name = value }
+        }
+    });
+
+    // 他のクレートからは構造体リテラルで作れなくなるため、
+    // Default::default() から各フィールドを設定して作成する
+    let non_exhaustive = if options.non_exhaustive {
+        quote! { #[non_exhaustive] }
+    } else {
+        quote! {}
+    };
+
+    // 生データ型の別名 (RawOfRecord など)
+    let raw_alias_ident = format_ident!("RawOf{}", struct_name);
+    let raw_alias_doc = format!(" 生成された生データ型の別名。`<{} as HasRaw>::Raw` と同じ型になる。", struct_name);
+
+    let field_count = fields.len();
+    let field_type_tags = fields.iter().map(|f| field_type_tag(&f.ty));
+    let headers = fields
+        .iter()
+        .map(|f| match serde_rename(f) {
+            Some(name) => name.value(),
+            None => f.ident.as_ref().unwrap().unraw().to_string(),
+        })
+        .collect::<Vec<_>>();
+    let field_enum = field_enum(&struct_name, &raw_struct_ident, fields, &headers);
+
+    let sum_checks = sum_checks(fields, &validations, &method_names, &options)?;
+    let checksum_checks = checksum_checks(fields, &validations, &options)?;
+    // max_errors を超えたエラーは、全てのチェックの後でまとめて切り詰める
+    let error_caps = fields.iter().zip(&validations).filter_map(|(f, validation)| {
+        let max = validation.max_errors.or(options.max_errors)?;
+        let error_key = field_error_key(f, options.serde_names);
+        Some(quote! { raw_struct::cap_field_errors(&mut errors, #error_key, #max); })
+    }).collect::<Vec<_>>();
+
+    // 各フィールドの検証がすべて通った場合のみ呼び、フィールドの存在を前提にできるようにする
+    let post_validate = match &options.post_validate {
+        Some(path) => quote! {
+            if errors.is_empty() {
+                #path(self, &mut errors);
+            }
+        },
+        None => quote! {}
+    };
+
+    // メソッド名をスネークケースに揃える場合、フィールド名はそのまま引き継ぐため警告を抑える
+    let allow_non_snake_case = if options.snake_case_methods {
+        quote! { #[allow(non_snake_case)] }
+    } else {
+        quote! {}
+    };
+
+    let checks = ValidateChecks { field_checks: validation_checks, cross_field_checks, sum_checks, checksum_checks, post_validate, error_caps };
+    let borrowed_impl = borrowed_impl(&struct_name, &raw_struct_ident, fields, &options, &checks, quote! { #non_exhaustive #allow_non_snake_case });
+    let ValidateChecks { field_checks: validation_checks, cross_field_checks, sum_checks, checksum_checks, post_validate, error_caps } = checks;
+
+    // Validate トレイトを経由できない呼び出し元向けに、検証を自由関数としても公開する
+    let free_fn_ident = format_ident!("validate_raw_{}", to_snake_case(&struct_name.unraw().to_string()));
+    let (free_fn_impl, free_fn_export) = if options.free_fn {
+        let free_fn_doc = format!(" `{}` を検証する。`validator::Validate::validate()` と同じ結果を返す。", raw_struct_ident);
+        (
+            quote! {
+                #[doc = #free_fn_doc]
+                pub fn #free_fn_ident(raw: &#raw_struct_ident) -> Result<(), validator::ValidationErrors> {
+                    validator::Validate::validate(raw)
+                }
+            },
+            Some(free_fn_ident),
+        )
+    } else {
+        (quote! {}, None)
+    };
+
+    // use record::raw::* で生成したすべての項目とトレイトをまとめて取り込めるようにする
+    // (json_schema() などの関連関数は生データ型から呼べる)
+    let export_module = match &options.export_in {
+        Some(module) => {
+            let mut exports = vec![raw_struct_ident.clone(), raw_alias_ident.clone(), format_ident!("{}Field", struct_name)];
+            if validations.iter().any(|validation| validation.monotonic) {
+                exports.push(format_ident!("{}SequenceValidator", struct_name));
+            }
+            if validations.iter().any(|validation| validation.unique) {
+                exports.push(format_ident!("{}UniqueValidator", struct_name));
+            }
+            if validations.iter().any(|validation| validation.outlier.is_some()) {
+                exports.push(format_ident!("{}OutlierDetector", struct_name));
+            }
+            if options.borrowed {
+                exports.push(format_ident!("{}Ref", raw_struct_ident));
+            }
+            exports.extend(free_fn_export);
+            #[cfg(feature = "json_schema")]
+            let json_schema_export = {
+                let doc = format!(" `{}::json_schema()` と同じ JSON Schema を返す。", raw_struct_ident);
+                quote! {
+                    #[doc = #doc]
+                    pub fn json_schema() -> serde_json::Value {
+                        super::#raw_struct_ident::json_schema()
+                    }
+                }
+            };
+            #[cfg(not(feature = "json_schema"))]
+            let json_schema_export = quote! {};
+            let module_doc = format!(" `{}` のために生成した型・関数と、検証に使うトレイトをまとめて再エクスポートするモジュール。", struct_name);
+            quote! {
+                #[doc = #module_doc]
+                pub mod #module {
+                    pub use super::{#(#exports),*};
+                    pub use raw_struct::{HasFields, HasRaw};
+                    pub use validator::Validate;
+                    #json_schema_export
+                }
+            }
+        },
+        None => quote! {}
+    };
+
+    #[cfg(feature = "arbitrary")]
+    let arbitrary_impl = arbitrary_impl(&raw_struct_ident, fields, &flatten_fields, &validations, options.strict_required)?;
+    #[cfg(not(feature = "arbitrary"))]
+    let arbitrary_impl = quote! {};
+
+    #[cfg(feature = "json_schema")]
+    let json_schema_impl = json_schema_impl(&raw_struct_ident, fields, &flatten_fields, &validations, &headers, options.all_optional);
+    #[cfg(not(feature = "json_schema"))]
+    let json_schema_impl = quote! {};
+
+    #[cfg(feature = "jsonschema")]
+    let schema_validation_impl = schema_validation_impl(&raw_struct_ident, fields, &flatten_fields, &validations, &headers, &options);
+    #[cfg(not(feature = "jsonschema"))]
+    let schema_validation_impl = quote! {};
+
+    #[cfg(feature = "anyhow")]
+    let anyhow_impl = anyhow_impl(&raw_struct_ident);
+    #[cfg(not(feature = "anyhow"))]
+    let anyhow_impl = quote! {};
+
+    #[cfg(feature = "core_validate")]
+    let core_impl = core_impl(&raw_struct_ident, fields, &flatten_fields, &validations, &options);
+    #[cfg(not(feature = "core_validate"))]
+    let core_impl = quote! {};
+
+    // template() の各フィールドの値
+    // default があればその値、なければ必須のフィールドは空文字、Option のフィールドは None にする
+    let template_fields = fields.iter().zip(&validations).map(|(f, validation)| {
+        let field_name = &f.ident;
+        let value = match &validation.default {
+            Some(default) => quote! { #default.to_string() },
+            None => quote! { String::new() },
+        };
+        if is_strict(f, options.strict_required) {
+            quote! { #field_name: #value }
+        } else if validation.default.is_none() && is_option_type(&f.ty) {
+            quote! { #field_name: None }
+        } else {
+            quote! { #field_name: Some(#value) }
+        }
+    });
+
+    // flatten したフィールド (子の生データ型をそのまま持つ)
+    let flatten_idents = flatten_fields.iter().map(|flatten| &flatten.field.ident).collect::<Vec<_>>();
+    let flatten_vis = flatten_fields.iter().map(|flatten| &flatten.field.vis);
+    let flatten_raw_types = flatten_fields.iter().map(flatten_raw_type).collect::<Vec<_>>();
+    let flatten_prefixes = flatten_fields.iter().map(|flatten| &flatten.prefix).collect::<Vec<_>>();
+    let flatten_keys = flatten_fields.iter().map(|flatten| field_error_key(&flatten.field, options.serde_names)).collect::<Vec<_>>();
+    // rules_markdown() の表の行 (列名の後ろの「型 | 必須 | 制約」の部分)。セルの中の | はエスケープする
+    let rule_cells = fields.iter().zip(&validations).map(|(f, validation)| {
+        let required = if !is_option_type(&f.ty) && !options.all_optional { "はい" } else { "いいえ" };
+        let rules = rule_descriptions(validation).join("、");
+        format!(" | {} | {} | {} |", field_type_tag(&f.ty), required, rules.replace('|', "\\|"))
+    }).collect::<Vec<_>>();
+    let header_cells = headers.iter().map(|header| header.replace('|', "\\|")).collect::<Vec<_>>();
+    // 子の検証エラーは親のフィールド名をキーとする入れ子のエラーにする
+    let flatten_checks = flatten_idents.iter().zip(&flatten_keys).map(|(ident, key)| quote! {
+        if let Err(child_errors) = validator::Validate::validate(&self.#ident) {
+            errors.errors_mut().insert(#key, validator::ValidationErrorsKind::Struct(Box::new(child_errors)));
+        }
+    }).collect::<Vec<_>>();
+
+    // "親.子" のキーは先頭のキーで flatten したフィールドを選び、残りのキーで子の値を探す
+    let nested_raw_value = if flatten_fields.is_empty() {
+        quote! { _ => None, }
+    } else {
+        quote! {
+            _ => {
+                let (head, rest) = key.split_once('.')?;
+                match head {
+                    #(#flatten_keys => self.#flatten_idents.__raw_value(rest),)*
+                    _ => None,
+                }
+            },
+        }
+    };
+
+    let (serde_derives, flatten_serde_impl) = flatten_serde_impl(&raw_struct_ident, &flatten_fields);
+    // set_field() で自身の列に当たらない名前は、prefix を取り除いて子に渡す
+    let set_field_fallback = quote! {
+        #(
+            if let Some(name) = name.strip_prefix(#flatten_prefixes) {
+                if self.#flatten_idents.set_field(name, value.clone()) {
+                    return true;
+                }
+            }
+        )*
+        return false;
+    };
+
+    // all_optional / strict_required で変わる、未入力の扱いを生成する型と validate() の説明に載せる
+    let mut raw_doc = format!(" `{}` の各列を解析前の文字列のまま保持する生データ型。", struct_name);
+    let mut validate_doc = " 属性で指定したチェックをすべて行い、見つかったエラーをまとめて返す。".to_string();
+    if options.all_optional {
+        raw_doc.push_str("\n\n `raw_struct(all_optional)` を指定しているため、`validate()` は Option でないフィールドが未入力でもエラーにしない。\
+            値がある場合は通常どおり検証する。未入力のまま `TryFrom` で変換すると、宣言された型の値を作れないため、\
+            そのフィールドの \"required\" のエラーになる。");
+        validate_doc.push_str("\n\n all_optional を指定しているため、Option でないフィールドの未入力はエラーにしない \
+            (`TryFrom` で変換する時点で \"required\" のエラーになる)。");
+    }
+    if options.strict_required {
+        raw_doc.push_str("\n\n `raw_struct(strict_required)` を指定しているため、Option でないフィールドは `Option<String>` ではなく `String` になる。\
+            serde でデシリアライズする際にそのフィールドの列がなければ、`validate()` まで進まずに missing field の\
+            デシリアライズエラーになる。列はあるが値が空の場合は空文字として読み込まれ、型変換・検証のエラーになる。\
+            Option のフィールドは列がなくても `None` として読み込まれる。");
+        validate_doc.push_str("\n\n strict_required を指定しているため、Option でないフィールドの存在は検査しない \
+            (列がなければデシリアライズの時点でエラーになる)。");
+    }
+
+    let expanded = quote! {
+        #[doc = #raw_doc]
+        #[derive(Debug, Clone, Default, #serde_derives)]
+        #non_exhaustive
+        #allow_non_snake_case
+        pub struct #raw_struct_ident {
+            #(#raw_fields,)*
+            #(#flatten_vis #flatten_idents: #flatten_raw_types,)*
+        }
+
+        #flatten_serde_impl
+
+        impl validator::Validate for #raw_struct_ident {
+            #[doc = #validate_doc]
+            fn validate(&self) -> Result<(), validator::ValidationErrors> {
+                #[allow(unused_mut)]
+                let mut errors = self.__validate_selected(&|_| true);
+
+                #(#sum_checks)*
+                #(#checksum_checks)*
+                #post_validate
+                #(#error_caps)*
+
+                if errors.is_empty() {
+                    Ok(())
+                } else {
+                    Err(errors)
+                }
+            }
+        }
+
+        #field_enum
+
+        impl #raw_struct_ident {
+            /// フィールド (列) の数。
+            pub const FIELD_COUNT: usize = #field_count #(+ <#flatten_raw_types>::FIELD_COUNT)*;
+
+            /// (フィールド名, 型の種類) の組を宣言順に並べた一覧。
+            ///
+            /// 型の種類は宣言された型から決まる `"integer"` / `"number"` / `"boolean"` /
+            /// `"date"` / `"time"` / `"datetime"` / `"string"` のいずれか。flatten したフィールドは含まない。
+            pub const FIELD_TYPES: &'static [(&'static str, &'static str)] = &[#((#field_names, #field_type_tags)),*];
+
+            /// (フィールド名, 解析前の値) の組を宣言順に返す。
+            ///
+            /// フィールド名はエラーのキーと同じ名前になる。flatten したフィールドは子のフィールドを続けて返す。
+            pub fn fields(&self) -> impl Iterator<Item = (&'static str, Option<&str>)> + '_ {
+                [#((#field_names, #self_field_refs.map(String::as_str))),*].into_iter()
+                    #(.chain(self.#flatten_idents.fields()))*
+            }
+
+            /// 名前で指定したフィールドの値を、呼び出し側が選んだ型 T として解析して返す。
+            ///
+            /// 宣言された型や属性によるチェックは使わず、T の FromStr だけで解析する。
+            /// フィールド名はエラーのキーと同じ名前で指定する。未入力と空文字は None を返し、
+            /// 存在しない名前は "unknown_field"、解析できない値は "invalid_value" のエラーを返す。
+            pub fn get_typed<T>(&self, field: &str) -> Result<Option<T>, validator::ValidationError>
+            where
+                T: std::str::FromStr,
+                T::Err: std::fmt::Display,
+            {
+                let Some((name, value)) = self.fields().find(|(name, _)| *name == field) else {
+                    let mut err = validator::ValidationError::new("unknown_field");
+                    err.message = Some(format!("フィールド '{}' は存在しません", field).into());
+                    return Err(err);
+                };
+                match value.filter(|v| !v.is_empty()) {
+                    Some(value) => value.parse::<T>().map(Some).map_err(|e| {
+                        let mut err = validator::ValidationError::new("invalid_value");
+                        err.add_param("value".into(), &value);
+                        err.message = Some(format!("フィールド '{}' の値 ({}) を {} として解析できません: {}",
+                            name,
+                            value,
+                            std::any::type_name::<T>(),
+                            e
+                        ).into());
+                        err
+                    }),
+                    None => Ok(None),
+                }
+            }
+
+            // エラーのキー ("親.子" を含む) に対応する解析前の値を返す (validate_with_observer から使う)
+            #[doc(hidden)]
+            pub fn __raw_value(&self, key: &str) -> Option<&str> {
+                match key {
+                    #(#field_names => #self_field_refs.map(String::as_str),)*
+                    #nested_raw_value
+                }
+            }
+
+            // (列名, 解析前の値) の組を prefix を付けて columns に追加する (flatten の serde の実装から使う)
+            #[doc(hidden)]
+            pub fn __flat_columns<'a>(&'a self, prefix: &str, columns: &mut Vec<(String, Option<&'a str>)>) {
+                #(columns.push((format!("{}{}", prefix, #headers), #self_field_refs.map(String::as_str)));)*
+                #(self.#flatten_idents.__flat_columns(&format!("{}{}", prefix, #flatten_prefixes), columns);)*
+            }
+
+            /// 列ごとの検証ルールを Markdown の表 (列名・型・必須・制約) にして返す。
+            ///
+            /// 属性から生成した文字列をつなぐだけなので、データの提供者に渡す仕様書などに使える。
+            /// flatten したフィールドは子の列を prefix を付けた列名で続けて載せる。
+            pub fn rules_markdown() -> String {
+                let mut table = String::from("| 列名 | 型 | 必須 | 制約 |\n| --- | --- | --- | --- |\n");
+                Self::__rules_rows("", &mut table);
+                table
+            }
+
+            // rules_markdown() の表の行を prefix を付けた列名で追加する
+            #[doc(hidden)]
+            pub fn __rules_rows(prefix: &str, table: &mut String) {
+                #(table.push_str(&format!("| {}{}{}\n", prefix, #header_cells, #rule_cells));)*
+                #(<#flatten_raw_types>::__rules_rows(&format!("{}{}", prefix, #flatten_prefixes), table);)*
+            }
+
+            /// ヘッダ名 (serde の rename があればその名前) に対応するフィールドに値を設定する。
+            ///
+            /// 列の順序が宣言順と異なるパーサーから1列ずつ組み立てる場合に使う。
+            /// 対応するフィールドがなければ何もせず `false` を返す。
+            /// flatten したフィールドの列は、子の列名に prefix を付けた名前で指定する。
+            pub fn set_field(&mut self, name: &str, value: Option<String>) -> bool {
+                match name {
+                    #(#headers => #set_field_assigns,)*
+                    _ => {
+                        #set_field_fallback
+                    },
+                }
+                true
+            }
+
+            /// 列名をキーとする対応表から生データを作る。
+            ///
+            /// キーは set_field() と同じくヘッダ名 (flatten したフィールドは prefix 付き) と完全に一致させる。
+            /// CSV の読み込みと同じく空文字の値は未入力 (`None`) として扱い、対応しないキーは無視する。
+            pub fn from_map(map: &std::collections::HashMap<String, String>) -> #raw_struct_ident {
+                let mut raw = #raw_struct_ident::default();
+                for (name, value) in map {
+                    raw.set_field(name, Some(value.clone()).filter(|value| !value.is_empty()));
+                }
+                raw
+            }
+
+            /// from_map() と同じく対応表から生データを作るが、キーの大文字・小文字と前後の空白を無視する。
+            ///
+            /// 取り込み元によって `Email` と `email` のように列名の書き方が揃わない場合に使う。
+            pub fn from_map_ci(map: &std::collections::HashMap<String, String>) -> #raw_struct_ident {
+                let mut raw = #raw_struct_ident::default();
+                let mut columns = Vec::new();
+                raw.__flat_columns("", &mut columns);
+                let headers = columns
+                    .into_iter()
+                    .map(|(header, _)| (header.to_lowercase(), header))
+                    .collect::<std::collections::HashMap<_, _>>();
+                for (name, value) in map {
+                    if let Some(header) = headers.get(&name.trim().to_lowercase()) {
+                        raw.set_field(header, Some(value.clone()).filter(|value| !value.is_empty()));
+                    }
+                }
+                raw
+            }
+
+            /// trim・null_values・normalize・phone(normalize)・default・case の指定に従って文字列を整形した生データを返す。
+            ///
+            /// 型変換や検証は行わないため、整形結果を確認してから validate() を呼べる。
+            pub fn normalized(self) -> #raw_struct_ident {
+                #raw_struct_ident {
+                    #(#normalized_fields,)*
+                    #(#flatten_idents: self.#flatten_idents.normalized(),)*
+                }
+            }
+
+            /// normalized() と同じ整形を、複数の生データに対してその場で行う。
+            ///
+            /// 整形してからログ出力などで内容を確認し、そのあとで検証する場合に使う。
+            /// 整形の指定がないフィールドは変更しない。
+            pub fn sanitize_all(records: &mut [#raw_struct_ident]) {
+                #[allow(unused_variables)]
+                for record in records.iter_mut() {
+                    #(#sanitize_fields)*
+                    #(<#flatten_raw_types>::sanitize_all(std::slice::from_mut(&mut record.#flatten_idents));)*
+                }
+            }
+
+            /// 見本となる生データを返す。
+            ///
+            /// default を指定したフィールドはその値、指定のない必須のフィールドは空文字 (`Some("")`)、
+            /// 指定のない Option のフィールドは `None` になる。
+            /// CSV に書き出すと、ヘッダと見本の行を持つ空のテンプレートとして使える。
+            pub fn template() -> #raw_struct_ident {
+                #raw_struct_ident {
+                    #(#template_fields,)*
+                    #(#flatten_idents: <#flatten_raw_types>::template(),)*
+                }
+            }
+
+            /// 指定したフィールドに対するチェックだけを実行する。
+            ///
+            /// フィールド名はエラーのキーと同じ名前で指定する。存在しない名前は無視し、
+            /// post_validate も呼ばない。
+            pub fn validate_fields(&self, fields: &[&str]) -> Result<(), validator::ValidationErrors> {
+                #[allow(unused_mut)]
+                let mut errors = self.__validate_selected(&|name| fields.contains(&name));
+                #(#error_caps)*
+
+                if errors.is_empty() {
+                    Ok(())
+                } else {
+                    Err(errors)
+                }
+            }
+
+            // include が true を返すフィールド (エラーのキーで判定する) のチェックと、
+            // そのフィールドから他のフィールドを参照するチェックを実行する (validate() と validate_fields() で共有する)
+            #[allow(unused_variables, unused_mut)]
+            fn __validate_selected(&self, include: &dyn Fn(&str) -> bool) -> validator::ValidationErrors {
+                let mut errors = validator::ValidationErrors::new();
+                #(if include(#field_names) {
+                    #validation_checks
+                })*
+                #(if include(#flatten_keys) {
+                    #flatten_checks
+                })*
+                #(if include(#field_names) {
+                    #cross_field_checks
+                })*
+                errors
+            }
+
+            /// validate() のエラーを、必須項目・型の誤り (structural) と制約の違反 (semantic) に分けて返す。
+            ///
+            /// 型の誤りを先に直させるなど、画面で種類ごとに表示を変える場合に使う。
+            /// code = "..." でエラーコードを置き換えたフィールドのエラーと、
+            /// フィールド間・構造体全体のチェックのエラーはすべて semantic になる。
+            pub fn validate_categorized(&self) -> Result<(), raw_struct::CategorizedErrors> {
+                #[allow(unused_mut)]
+                let mut categorized = match validator::Validate::validate(self) {
+                    Ok(()) => return Ok(()),
+                    Err(errors) => raw_struct::CategorizedErrors::split(errors, |key, code| match key {
+                        #(#field_names => #structural_code_checks,)*
+                        _ => false,
+                    }),
+                };
+                #(
+                    if let Err(child) = self.#flatten_idents.validate_categorized() {
+                        categorized.nest(#flatten_keys, child);
+                    }
+                )*
+                Err(categorized)
+            }
+
+            #lossy_fn
+
+            #lenient_fn
+
+            /// validate() に加えて、実行時の設定 (config) に従った検証を行う。
+            ///
+            /// config で必須に指定されたフィールドは、Option として宣言されていても未入力をエラーにする。
+            /// config に登録されたクロージャは、値のあるフィールドに対して組み込みのチェックの後に呼ぶ。
+            /// business_day のフィールドは config に登録された休日もエラーにする。
+            pub fn validate_with(&self, config: &raw_struct::ValidationConfig) -> Result<(), validator::ValidationErrors> {
+                let mut errors = validator::Validate::validate(self).err().unwrap_or_default();
+                #runtime_checks
+                #(#error_caps)*
+                if errors.is_empty() {
+                    Ok(())
+                } else {
+                    Err(errors)
+                }
+            }
+
+            /// validate_with() で検証し、config に参照データを登録したフィールドについて、
+            /// 値に対応する参照データの値をフィールド名 (エラーのキー) ごとに返す。
+            ///
+            /// 通貨コードから小数点以下の桁数を引くなど、取り込み時に参照データを結合するために使う。
+            /// 値が未入力のフィールドは結果に含めない。
+            pub fn lookup_values(&self, config: &raw_struct::ValidationConfig) -> Result<std::collections::HashMap<&'static str, String>, validator::ValidationErrors> {
+                self.validate_with(config)?;
+                let mut values = std::collections::HashMap::new();
+                #(#lookup_captures)*
+                Ok(values)
+            }
+
+            /// validate() を実行し、エラーに元の CSV の行 (raw_line) と行番号 (row) を付けて返す。
+            ///
+            /// 各エラーには "row" と "source" のパラメータを追加し、メッセージの先頭に
+            /// 「行3 『...』 の」のように行番号と元の行を付ける。行番号は呼び出し側の数え方のまま使う。
+            pub fn validate_with_source(&self, raw_line: &str, row: usize) -> Result<(), validator::ValidationErrors> {
+                let mut errors = match validator::Validate::validate(self) {
+                    Ok(()) => return Ok(()),
+                    Err(errors) => errors,
+                };
+                raw_struct::for_each_error_mut(&mut errors, &mut |err| {
+                    err.add_param("row".into(), &row);
+                    err.add_param("source".into(), &raw_line);
+                    if let Some(message) = &err.message {
+                        err.message = Some(format!("行{} 『{}』 の{}", row, raw_line, message).into());
+                    }
+                });
+                Err(errors)
+            }
+
+            /// validate() を実行し、エラーごとに (フィールド名, 元の値, 理由) で observer を呼ぶ。
+            ///
+            /// ログやメトリクスに流すためのもので、結果は validate() と同じものを返す。
+            /// 通知はフィールドの宣言順に行う。flatten したフィールドの子のエラーは
+            /// "親.子" のフィールド名で、親のフィールドの後に通知する。
+            pub fn validate_with_observer(&self, observer: &mut dyn FnMut(&str, &str, &str)) -> Result<(), validator::ValidationErrors> {
+                let result = validator::Validate::validate(self);
+                if let Err(errors) = &result {
+                    let mut field_errors = raw_struct::flat_field_errors(errors);
+                    field_errors.sort_by_cached_key(|(key, _)| {
+                        let head = key.split('.').next().unwrap_or_default();
+                        let position = [#(#field_names,)* #(#flatten_keys),*].iter().position(|name| *name == head).unwrap_or(usize::MAX);
+                        (position, key.clone())
+                    });
+                    for (key, errs) in field_errors {
+                        let raw = self.__raw_value(&key).unwrap_or("");
+                        for err in errs {
+                            let reason = match &err.message {
+                                Some(message) => message.to_string(),
+                                None => err.code.to_string(),
+                            };
+                            observer(&key, raw, &reason);
+                        }
+                    }
+                }
+                result
+            }
+
+            #(#field_validators)*
+        }
+
+        impl raw_struct::HasRaw for #struct_name {
+            type Raw = #raw_struct_ident;
+        }
+
+        #[doc = #raw_alias_doc]
+        pub type #raw_alias_ident = #raw_struct_ident;
+
+        impl From<&std::collections::HashMap<String, String>> for #raw_struct_ident {
+            fn from(map: &std::collections::HashMap<String, String>) -> Self {
+                #raw_struct_ident::from_map(map)
+            }
+        }
+
+        impl raw_struct::HasFields for #raw_struct_ident {
+            fn fields(&self) -> impl Iterator<Item = (&'static str, Option<&str>)> {
+                #raw_struct_ident::fields(self)
+            }
+        }
+
+        #try_from_impls
+
+        #sequence_validator
+        #unique_validator
+        #outlier_detector
+        #borrowed_impl
+        #free_fn_impl
+
+        #arbitrary_impl
+
+        #anyhow_impl
+        #core_impl
+
+        #json_schema_impl
+        #schema_validation_impl
+
+        #export_module
+    };
+
+    Ok(expanded)
+}
+
+// フィールド1つ分の検証を生成する関数
+//
+// validate() の中で行うチェックと、生データ型に生成するフィールドごとのメソッド
+// (単体の検証・検証の本体・宣言された型への変換) の組を返す
+fn field_check(
+    raw_struct_ident: &syn::Ident,
+    f: &syn::Field,
+    validation: &StringValidation,
+    names: &MethodNames,
+    options: &ContainerOptions,
+) -> (proc_macro2::TokenStream, proc_macro2::TokenStream) {
+    let field_name = &f.ident;
+    let error_key = field_error_key(f, options.serde_names);
+    let field_type = &f.ty;
+    let is_optional = is_option_type(field_type);
+    let base_type = if is_optional {
+        get_inner_type(field_type).unwrap()
+    } else {
+        field_type
+    };
+
+    // エラーのキーはフィールド名のまま、コードで検査の種類を区別する
+    let code_blank = error_code(validation, "blank");
+    let code_invalid_check_digit = error_code(validation, "invalid_check_digit");
+    let code_invalid_iban = error_code(validation, "invalid_iban");
+    let code_iban_checksum = error_code(validation, "iban_checksum");
+    let code_invalid_digits = error_code(validation, "invalid_digits");
+    let code_leading_zeros = error_code(validation, "leading_zeros");
+    let code_invalid_hostname = error_code(validation, "invalid_hostname");
+    let code_invalid_length = error_code(validation, "invalid_length");
+    let code_invalid_mac = error_code(validation, "invalid_mac");
+    let code_invalid_phone = error_code(validation, "invalid_phone");
+    let code_invalid_step = error_code(validation, "invalid_step");
+    let code_invalid_time = error_code(validation, "invalid_time");
+    let code_invalid_uuid = error_code(validation, "invalid_uuid");
+    let code_invalid_timezone = error_code(validation, "invalid_timezone");
+    let code_invalid_glob = error_code(validation, "invalid_glob");
+    let code_invalid_value = error_code(validation, "invalid_value");
+    let code_unknown_variant = error_code(validation, "unknown_variant");
+    let code_invalid_version = error_code(validation, "invalid_version");
+    let code_invalid_version_req = error_code(validation, "invalid_version_req");
+    let code_not_a_bool = error_code(validation, "not_a_bool");
+    let code_not_a_number = error_code(validation, "not_a_number");
+    let code_not_allowed = error_code(validation, "not_allowed");
+    let code_not_an_integer = error_code(validation, "not_an_integer");
+    let code_not_finite = error_code(validation, "not_finite");
+    let code_not_nfc = error_code(validation, "not_nfc");
+    let code_out_of_range = error_code(validation, "out_of_range");
+    let code_not_business_day = error_code(validation, "not_business_day");
+    let code_pattern_mismatch = error_code(validation, "pattern_mismatch");
+    let code_required = error_code(validation, "required");
+    let code_surrounding_whitespace = error_code(validation, "surrounding_whitespace");
+    let code_too_long = error_code(validation, "too_long");
+    let code_too_short = error_code(validation, "too_short");
+    let code_uuid_version = error_code(validation, "uuid_version");
+    let code_varchar_overflow = error_code(validation, "varchar_overflow");
+    let code_invalid_expiry = error_code(validation, "invalid_expiry");
+    let code_invalid_color = error_code(validation, "invalid_color");
+    let code_password_too_short = error_code(validation, "password_too_short");
+    let code_missing_upper = error_code(validation, "missing_upper");
+    let code_missing_lower = error_code(validation, "missing_lower");
+    let code_missing_digit = error_code(validation, "missing_digit");
+    let code_missing_symbol = error_code(validation, "missing_symbol");
+    let code_invalid_postal_code = error_code(validation, "invalid_postal_code");
+    let code_invalid_extension = error_code(validation, "invalid_extension");
+    let code_control_character = error_code(validation, "control_character");
+    let code_invalid_socket = error_code(validation, "invalid_socket");
+    let code_invalid_kana = error_code(validation, "invalid_kana");
+    let code_invalid_prefix = error_code(validation, "invalid_prefix");
+    let code_invalid_suffix = error_code(validation, "invalid_suffix");
+    let code_expired = error_code(validation, "expired");
+    // 整数の解析エラーは数値でない場合と範囲外の場合でコードを分ける
+    let code_parse = match &validation.code {
+        Some(code) => quote! { #code },
+        None => quote! {
+            if e.to_string().contains("invalid digit") { "not_a_number" } else { "out_of_range" }
+        },
+    };
+
+    // all_optional の場合は validate() では必須チェックを行わない
+    // (TryFrom による変換では値がなければ必須項目のエラーになる)
+    let required_check = if !is_optional && !options.all_optional && !options.strict_required {
+        quote! {
+            if self.#field_name.is_none() {
+                let mut err = validator::ValidationError::new(#code_required);
+                err.message = Some(format!("フィールド '{}' は必須項目です", #error_key).into());
+                errors.add(#error_key, err);
+            }
+        }
+    } else {
+        quote! {}
+    };
+
+    let type_str = quote!(#base_type).to_string();
+
+    // Option<T> (T が String 以外) の空文字は未入力 (None) として扱う
+    let field_ref = raw_field_ref(quote! { self }, f, options.strict_required);
+    let present_value = present_value(quote! { self }, f, options.strict_required);
+
+    // タイムゾーン名の検証は chrono-tz クレート (timezone feature) を使う
+    let timezone_validation = if validation.timezone {
+        quote! {
+            if <raw_struct::chrono_tz::Tz as std::str::FromStr>::from_str(value).is_err() {
+                let mut err = validator::ValidationError::new(#code_invalid_timezone);
+                err.message = Some(format!("フィールド '{}' の値 ({}) が IANA のタイムゾーン名 (例: Asia/Tokyo) ではありません",
+                    #error_key,
+                    value
+                ).into());
+                errors.add(#error_key, err);
+            }
+        }
+    } else {
+        quote! {}
+    };
+
+    // glob パターンの検証は glob クレート (glob feature) を使う。パターンに一致するファイルがあるかは見ない
+    let glob_validation = if validation.glob {
+        quote! {
+            if let Err(e) = raw_struct::glob::Pattern::new(value) {
+                let mut err = validator::ValidationError::new(#code_invalid_glob);
+                err.add_param("pos".into(), &e.pos);
+                err.message = Some(format!("フィールド '{}' の値 ({}) が glob パターンとして正しくありません ({} 文字目: {})",
+                    #error_key,
+                    value,
+                    e.pos + 1,
+                    e.msg
+                ).into());
+                errors.add(#error_key, err);
+            }
+        }
+    } else {
+        quote! {}
+    };
+
+    // UUID の検証は uuid クレート (uuid feature) を使う
+    let uuid_validation = match &validation.uuid {
+        Some(uuid) => {
+            let version_check = match uuid.version {
+                Some(version) => quote! {
+                    if uuid.get_version_num() != #version {
+                        let mut err = validator::ValidationError::new(#code_uuid_version);
+                        err.message = Some(format!("フィールド '{}' の値 ({}) は UUID v{} である必要があります（現在: v{}）",
+                            #error_key,
+                            value,
+                            #version,
+                            uuid.get_version_num()
+                        ).into());
+                        errors.add(#error_key, err);
+                    }
+                },
+                None => quote! {}
+            };
+            quote! {
+                match raw_struct::uuid::Uuid::parse_str(value) {
+                    Ok(uuid) => {
+                        #version_check
+                    },
+                    Err(_) => {
+                        let mut err = validator::ValidationError::new(#code_invalid_uuid);
+                        err.message = Some(format!("フィールド '{}' の値 ({}) が有効な UUID ではありません",
+                            #error_key,
+                            value
+                        ).into());
+                        errors.add(#error_key, err);
+                    }
+                }
+            }
+        },
+        None => quote! {}
+    };
+
+    // parse 後の符号なし整数 (parsed) に対するチェック
+    let unsigned_checks = match validation.bits {
+        Some(bits) => {
+            let max = (1u128 << bits) - 1;
+            quote! {
+                if parsed as u128 > #max {
+                    let mut err = validator::ValidationError::new(#code_out_of_range);
+                    err.message = Some(format!("フィールド '{}' の値 ({}) が {} ビットで表現できる範囲（0 ～ {}）を超えています",
+                        #error_key,
+                        value,
+                        #bits,
+                        #max
+                    ).into());
+                    errors.add(#error_key, err);
+                }
+            }
+        },
+        None => quote! {}
+    };
+    // parse 後の数値 (parsed) に対する range(...) / one_of のチェック
+    let is_float = matches!(type_str.as_str(), "f32" | "f64");
+    let mut numeric_checks = validation.range.iter().map(|(bound, lit)| {
+        let limit = match bound_literal("range", lit, is_float) {
+            Ok(limit) => limit,
+            Err(e) => return e.to_compile_error(),
+        };
+        let shown = quote!(#lit).to_string();
+        let (out_of_range, condition) = match bound {
+            RangeBound::Ge => (quote! { parsed < #limit }, "以上"),
+            RangeBound::Le => (quote! { parsed > #limit }, "以下"),
+            RangeBound::Gt => (quote! { parsed <= #limit }, "より大きい値"),
+            RangeBound::Lt => (quote! { parsed >= #limit }, "より小さい値"),
+        };
+        quote! {
+            if #out_of_range {
+                let mut err = validator::ValidationError::new(#code_out_of_range);
+                err.message = Some(format!("フィールド '{}' の値 ({}) は {} {}である必要があります",
+                    #error_key,
+                    value,
+                    #shown,
+                    #condition
+                ).into());
+                errors.add(#error_key, err);
+            }
+        }
+    }).collect::<Vec<_>>();
+    // 定数の値はメッセージにも表示する
+    numeric_checks.extend(validation.range_consts.iter().map(|(bound, path)| {
+        let shown = quote!(#path).to_string().replace(' ', "");
+        let (out_of_range, condition) = match bound {
+            RangeBound::Ge => (quote! { parsed < #path }, "以上"),
+            RangeBound::Le => (quote! { parsed > #path }, "以下"),
+            RangeBound::Gt => (quote! { parsed <= #path }, "より大きい値"),
+            RangeBound::Lt => (quote! { parsed >= #path }, "より小さい値"),
+        };
+        quote! {
+            if #out_of_range {
+                let mut err = validator::ValidationError::new(#code_out_of_range);
+                err.message = Some(format!("フィールド '{}' の値 ({}) は {} ({}) {}である必要があります",
+                    #error_key,
+                    value,
+                    #shown,
+                    #path,
+                    #condition
+                ).into());
+                errors.add(#error_key, err);
+            }
+        }
+    }));
+    if !validation.one_of.is_empty() {
+        let allowed = validation.one_of.iter().map(|lit| match bound_literal("one_of", lit, is_float) {
+            Ok(literal) => literal,
+            Err(e) => e.to_compile_error(),
+        });
+        let shown = validation.one_of.iter().map(|lit| quote!(#lit).to_string()).collect::<Vec<_>>().join(", ");
+        numeric_checks.push(quote! {
+            if ![#(#allowed),*].contains(&parsed) {
+                let mut err = validator::ValidationError::new(#code_not_allowed);
+                err.message = Some(format!("フィールド '{}' の値 ({}) が許可された値 ({}) のいずれでもありません",
+                    #error_key,
+                    value,
+                    #shown
+                ).into());
+                errors.add(#error_key, err);
+            }
+        });
+    }
+    if let Some(step) = &validation.step {
+        numeric_checks.push(match bound_literal("step", step, true) {
+            // 2 進数で表せない刻み (0.05 など) の誤差を吸収するため、
+            // 刻み幅で割った値が整数から epsilon (f64 は 1e-9、f32 は 1e-4) 以内であれば
+            // 刻みに乗っているとみなす
+            Ok(limit) => {
+                let epsilon = if type_str == "f32" { 1e-4 } else { 1e-9 };
+                let shown = quote!(#step).to_string();
+                let decimals = shown.split_once('.').map_or(0, |(_, fraction)| fraction.len());
+                quote! {
+                    let steps = parsed as f64 / #limit;
+                    if (steps - steps.round()).abs() > #epsilon {
+                        let mut err = validator::ValidationError::new(#code_invalid_step);
+                        err.message = Some(format!("フィールド '{}' の値 ({}) が {} 刻みではありません（最も近い値: {:.*}）",
+                            #error_key,
+                            value,
+                            #shown,
+                            #decimals,
+                            steps.round() * #limit
+                        ).into());
+                        errors.add(#error_key, err);
+                    }
+                }
+            },
+            Err(e) => e.to_compile_error(),
+        });
+    }
+    let signed_ok_arm = if numeric_checks.is_empty() {
+        quote! { Ok(_) => {}, }
+    } else {
+        quote! { Ok(parsed) => { #(#numeric_checks)* }, }
+    };
+    let unsigned_ok_arm = if unsigned_checks.is_empty() && numeric_checks.is_empty() {
+        quote! { Ok(_) => {}, }
+    } else {
+        quote! { Ok(parsed) => { #unsigned_checks #(#numeric_checks)* }, }
+    };
+
+    // 表計算ソフトが出力する "3.0" のような値は、小数部がすべて 0 なら整数部だけを解析する
+    let coerce_float = if validation.coerce_float {
+        quote! {
+            let value = match value.split_once('.') {
+                Some((int_part, fraction)) if !int_part.is_empty() && fraction.chars().all(|c| c.is_ascii_digit()) => {
+                    if !fraction.chars().all(|c| c == '0') {
+                        let mut err = validator::ValidationError::new(#code_not_an_integer);
+                        err.message = Some(format!("フィールド '{}' の値 ({}) は小数部が 0 ではないため整数として扱えません",
+                            #error_key,
+                            value
+                        ).into());
+                        errors.add(#error_key, err);
+                        return;
+                    }
+                    int_part
+                },
+                _ => value,
+            };
+        }
+    } else {
+        quote! {}
+    };
+
+    // 整数として解析すると先頭の 0 が失われるため、解析前の文字列で桁数を確認する
+    let digits_check = match validation.digits {
+        Some(digits) => quote! {
+            if value.len() != #digits || !value.bytes().all(|b| b.is_ascii_digit()) {
+                let mut err = validator::ValidationError::new(#code_invalid_digits);
+                err.message = Some(format!("フィールド '{}' の値 ({}) は {} 桁の数字である必要があります",
+                    #error_key,
+                    value,
+                    #digits
+                ).into());
+                errors.add(#error_key, err);
+                return;
+            }
+        },
+        None => quote! {}
+    };
+
+    // 解析すると先頭の 0 が分からなくなるため、符号を除いた解析前の文字列で確認する (0 単体や 0.5 は許可する)
+    let leading_zeros_check = if validation.no_leading_zeros {
+        quote! {
+            let unsigned = value.strip_prefix(['+', '-']).unwrap_or(value).as_bytes();
+            if unsigned.len() > 1 && unsigned[0] == b'0' && unsigned[1].is_ascii_digit() {
+                let mut err = validator::ValidationError::new(#code_leading_zeros);
+                err.message = Some(format!("フィールド '{}' の値 ({}) の先頭に余分な 0 があります",
+                    #error_key,
+                    value
+                ).into());
+                errors.add(#error_key, err);
+                return;
+            }
+        }
+    } else {
+        quote! {}
+    };
+
+    // String と std::net::SocketAddr のどちらのフィールドでも、解析してからポート番号を確認する
+    let socket_check = match &validation.socket {
+        Some(socket) => {
+            let port_check = match socket.port_range {
+                Some((lo, hi)) => quote! {
+                    if !(#lo..=#hi).contains(&addr.port()) {
+                        let mut err = validator::ValidationError::new(#code_out_of_range);
+                        err.message = Some(format!("フィールド '{}' のポート番号 ({}) が許可された範囲 ({} ～ {}) にありません",
+                            #error_key,
+                            addr.port(),
+                            #lo,
+                            #hi
+                        ).into());
+                        errors.add(#error_key, err);
+                        return;
+                    }
+                },
+                None => quote! {}
+            };
+            quote! {
+                match value.parse::<std::net::SocketAddr>() {
+                    Ok(addr) => {
+                        #port_check
+                    },
+                    Err(_) => {
+                        let mut err = validator::ValidationError::new(#code_invalid_socket);
+                        err.message = Some(format!("フィールド '{}' の値 ({}) が有効なソケットアドレスではありません（例: 127.0.0.1:8080）",
+                            #error_key,
+                            value
+                        ).into());
+                        errors.add(#error_key, err);
+                        return;
+                    }
+                }
+            }
+        },
+        None => quote! {}
+    };
+
+    let value_check = match type_str.as_str() {
+        "u8" | "u16" | "u32" | "u64" | "u128" | "i8" | "i16" | "i32" | "i64" | "i128" if validation.radix.is_some() => {
+            let radix = validation.radix.unwrap();
+            let strip_prefix = radix_digits(radix);
+            let ok_arm = if type_str.starts_with('u') {
+                unsigned_ok_arm.clone()
+            } else {
+                signed_ok_arm.clone()
+            };
+            quote! {
+                #strip_prefix
+                match #base_type::from_str_radix(digits, #radix) {
+                    #ok_arm
+                    Err(_) => {
+                        let mut err = validator::ValidationError::new(#code_not_a_number);
+                        err.message = Some(format!("フィールド '{}' の値 ({}) が {} 進数の {}型として無効です",
+                            #error_key,
+                            value,
+                            #radix,
+                            stringify!(#base_type)
+                        ).into());
+                        errors.add(#error_key, err);
+                    }
+                }
+            }
+        },
+        "u8" | "u16" | "u32" | "u64" | "u128" => quote! {
+            #coerce_float
+            match value {
+                v if v.starts_with('-') => {
+                    let mut err = validator::ValidationError::new(#code_out_of_range);
+                    err.message = Some(format!("フィールド '{}' に負の値 ({}) が指定されましたが、{}型は負の値を受け付けません", 
+                        #error_key, 
+                        value,
+                        stringify!(#base_type)
+                    ).into());
+                    errors.add(#error_key, err);
+                },
+                v => match v.parse::<#base_type>() {
+                    #unsigned_ok_arm
+                    Err(e) => {
+                        let mut err = validator::ValidationError::new(#code_parse);
+                        err.message = Some(match e.to_string().contains("invalid digit") {
+                            true => format!("フィールド '{}' の値 ({}) が数値ではありません", 
+                                #error_key,
+                                value
+                            ),
+                            false => format!("フィールド '{}' の値 ({}) が {}型の範囲（0 ～ {}) を超えています", 
+                                #error_key,
+                                value,
+                                stringify!(#base_type),
+                                #base_type::MAX
+                            )
+                        }.into());
+                        errors.add(#error_key, err);
+                    }
+                }
+            }
+        },
+        "i8" | "i16" | "i32" | "i64" | "i128" => quote! {
+            #coerce_float
+            match value.parse::<#base_type>() {
+                #signed_ok_arm
+                Err(e) => {
+                    let mut err = validator::ValidationError::new(#code_parse);
+                    err.message = Some(match e.to_string().contains("invalid digit") {
+                        true => format!("フィールド '{}' の値 ({}) が数値ではありません", 
+                            #error_key,
+                            value
+                        ),
+                        false => format!("フィールド '{}' の値 ({}) が {}型の範囲（{} ～ {}) を超えています", 
+                            #error_key,
+                            value,
+                            stringify!(#base_type),
+                            #base_type::MIN,
+                            #base_type::MAX
+                        )
+                    }.into());
+                    errors.add(#error_key, err);
+                }
+            }
+        },
+        "f32" | "f64" => quote! {
+            match value.parse::<#base_type>() {
+                // "NaN" や "inf" も parse できてしまうため有限値のみ受け付ける
+                Ok(v) if !v.is_finite() => {
+                    let mut err = validator::ValidationError::new(#code_not_finite);
+                    err.message = Some(format!("フィールド '{}' の値 ({}) が有限の数値ではありません",
+                        #error_key,
+                        value
+                    ).into());
+                    errors.add(#error_key, err);
+                },
+                #signed_ok_arm
+                Err(_) => {
+                    let mut err = validator::ValidationError::new(#code_not_a_number);
+                    err.message = Some(format!("フィールド '{}' の値 ({}) が有効な浮動小数点数ではありません", 
+                        #error_key,
+                        value
+                    ).into());
+                    errors.add(#error_key, err);
+                }
+            }
+        },
+        "bool" => quote! {
+            match value.to_lowercase().as_str() {
+                "true" | "false" | "1" | "0" => {},
+                _ => {
+                    let mut err = validator::ValidationError::new(#code_not_a_bool);
+                    err.message = Some(format!("フィールド '{}' の値 ({}) が真偽値ではありません。'true'/'false' または '1'/'0' を使用してください", 
+                        #error_key,
+                        value
+                    ).into());
+                    errors.add(#error_key, err);
+                }
+            }
+        },
+        "String" => {
+            // 書記素クラスタ (見た目の1文字) で数える場合は unicode-segmentation クレート (graphemes feature) を、
+            // 表示幅 (East Asian Width) で数える場合は unicode-width クレート (width feature) を使う
+            let (len, noun, unit) = match validation.length_unit {
+                LengthUnit::Chars => (quote! { value.chars().count() }, "文字数", "文字"),
+                LengthUnit::Bytes => (quote! { value.len() }, "バイト数", "バイト"),
+                LengthUnit::Graphemes => (quote! { raw_struct::unicode_segmentation::UnicodeSegmentation::graphemes(value, true).count() }, "文字数", "文字"),
+                LengthUnit::Width => (quote! { raw_struct::unicode_width::UnicodeWidthStr::width(value) }, "表示幅", "桁"),
+            };
+            let length_validation = match (validation.min_length, validation.max_length) {
+                (Some(min), Some(max)) => {
+                    let message = format!("フィールド '{{}}' の{}が制限（{{}} ～ {{}} {}）を超えています（現在: {{}} {}）", noun, unit, unit);
+                    quote! {
+                        let len = #len;
+                        if !(#min..=#max).contains(&len) {
+                            let mut err = validator::ValidationError::new(#code_invalid_length);
+                            err.message = Some(format!(#message,
+                                #error_key,
+                                #min,
+                                #max,
+                                len
+                            ).into());
+                            errors.add(#error_key, err);
+                        }
+                    }
+                },
+                (Some(min), None) => {
+                    let message = format!("フィールド '{{}}' の{}が最小{}（{{}} {}）未満です（現在: {{}} {}）", noun, noun, unit, unit);
+                    quote! {
+                        let len = #len;
+                        if len < #min {
+                            let mut err = validator::ValidationError::new(#code_too_short);
+                            err.message = Some(format!(#message,
+                                #error_key,
+                                #min,
+                                len
+                            ).into());
+                            errors.add(#error_key, err);
+                        }
+                    }
+                },
+                (None, Some(max)) => {
+                    let message = format!("フィールド '{{}}' の{}が最大{}（{{}} {}）を超えています（現在: {{}} {}）", noun, noun, unit, unit);
+                    quote! {
+                        let len = #len;
+                        if len > #max {
+                            let mut err = validator::ValidationError::new(#code_too_long);
+                            err.message = Some(format!(#message,
+                                #error_key,
+                                #max,
+                                len
+                            ).into());
+                            errors.add(#error_key, err);
+                        }
+                    }
+                },
+                (None, None) => quote! {}
+            };
+
+            // 電話番号の検証は phonenumber クレート (phone feature) を使う
+            let phone_validation = match &validation.phone {
+                Some(phone) => {
+                    let region = phone_region(phone);
+                    quote! {
+                        match raw_struct::phonenumber::parse(#region, value) {
+                            Ok(number) if raw_struct::phonenumber::is_valid(&number) => {},
+                            _ => {
+                                let mut err = validator::ValidationError::new(#code_invalid_phone);
+                                err.message = Some(format!("フィールド '{}' の値 ({}) が有効な電話番号ではありません",
+                                    #error_key,
+                                    value
+                                ).into());
+                                errors.add(#error_key, err);
+                            }
+                        }
+                    }
+                },
+                None => quote! {}
+            };
+
+            // バージョン文字列の検証は semver クレート (semver feature) を使う
+            let semver_validation = if validation.semver {
+                quote! {
+                    if let Err(e) = raw_struct::semver::Version::parse(value) {
+                        let mut err = validator::ValidationError::new(#code_invalid_version);
+                        err.message = Some(format!("フィールド '{}' の値 ({}) が有効なバージョンではありません: {}",
+                            #error_key,
+                            value,
+                            e
+                        ).into());
+                        errors.add(#error_key, err);
+                    }
+                }
+            } else {
+                quote! {}
+            };
+            let semver_req_validation = if validation.semver_req {
+                quote! {
+                    if let Err(e) = raw_struct::semver::VersionReq::parse(value) {
+                        let mut err = validator::ValidationError::new(#code_invalid_version_req);
+                        err.message = Some(format!("フィールド '{}' の値 ({}) が有効なバージョン要求ではありません: {}",
+                            #error_key,
+                            value,
+                            e
+                        ).into());
+                        errors.add(#error_key, err);
+                    }
+                }
+            } else {
+                quote! {}
+            };
+
+            // static な HashSet や &'static HashSet<String> を返す関数呼び出しなど、
+            // contains(&str) を持つ任意の式を受け付ける
+            let in_set_validation = match &validation.in_set {
+                Some(set) => quote! {
+                    if !(#set).contains(value) {
+                        let mut err = validator::ValidationError::new(#code_not_allowed);
+                        err.message = Some(format!("フィールド '{}' の値 ({}) が許可された値の一覧に含まれていません",
+                            #error_key,
+                            value
+                        ).into());
+                        errors.add(#error_key, err);
+                    }
+                },
+                None => quote! {}
+            };
+
+            // 文字数とバイト数の両方を示し、マルチバイト文字による超過だと分かるようにする
+            let db_varchar_validation = match validation.db_varchar {
+                Some(limit) => quote! {
+                    if value.len() > #limit {
+                        let mut err = validator::ValidationError::new(#code_varchar_overflow);
+                        err.message = Some(format!("フィールド '{}' の値が VARCHAR({}) に収まりません（現在: {} 文字 / {} バイト、上限: {} バイト）",
+                            #error_key,
+                            #limit,
+                            value.chars().count(),
+                            value.len(),
+                            #limit
+                        ).into());
+                        errors.add(#error_key, err);
+                    }
+                },
+                None => quote! {}
+            };
+
+            // カード番号などのチェックディジット (数字以外の区切りは無視する)
+            let luhn_validation = if validation.luhn {
+                quote! {
+                    if !raw_struct::luhn_valid(value) {
+                        let mut err = validator::ValidationError::new(#code_invalid_check_digit);
+                        err.message = Some(format!("フィールド '{}' の値 ({}) のチェックディジットが正しくありません",
+                            #error_key,
+                            value
+                        ).into());
+                        errors.add(#error_key, err);
+                    }
+                }
+            } else {
+                quote! {}
+            };
+
+            // 書式・桁数の誤りとチェックサムの不一致は別のエラーコードにする
+            let iban_validation = if validation.iban {
+                quote! {
+                    match raw_struct::iban_check(value) {
+                        Ok(()) => {},
+                        Err(raw_struct::IbanError::Format) => {
+                            let mut err = validator::ValidationError::new(#code_invalid_iban);
+                            err.message = Some(format!("フィールド '{}' の値 ({}) が IBAN の書式ではありません（国コードごとの桁数の英数字）",
+                                #error_key,
+                                value
+                            ).into());
+                            errors.add(#error_key, err);
+                        },
+                        Err(raw_struct::IbanError::Checksum) => {
+                            let mut err = validator::ValidationError::new(#code_iban_checksum);
+                            err.message = Some(format!("フィールド '{}' の値 ({}) の IBAN のチェックサムが正しくありません",
+                                #error_key,
+                                value
+                            ).into());
+                            errors.add(#error_key, err);
+                        },
+                    }
+                }
+            } else {
+                quote! {}
+            };
+
+            let hostname_validation = if validation.hostname {
+                quote! {
+                    if !raw_struct::hostname_valid(value) {
+                        let mut err = validator::ValidationError::new(#code_invalid_hostname);
+                        err.message = Some(format!("フィールド '{}' の値 ({}) が有効なホスト名ではありません（英数字とハイフンのラベル、全体で 253 文字以内）",
+                            #error_key,
+                            value
+                        ).into());
+                        errors.add(#error_key, err);
+                    }
+                }
+            } else {
+                quote! {}
+            };
+
+            let one_of_validation = if validation.one_of.is_empty() {
+                quote! {}
+            } else {
+                let allowed = validation.one_of.iter().map(|lit| match lit {
+                    Lit::Str(_) => quote! { #lit },
+                    _ => syn::Error::new(lit.span(), "文字列のフィールドの one_of には文字列を指定してください").to_compile_error(),
+                });
+                let shown = validation.one_of.iter().map(|lit| match lit {
+                    Lit::Str(lit) => lit.value(),
+                    _ => String::new(),
+                }).collect::<Vec<_>>().join(", ");
+                quote! {
+                    if ![#(#allowed),*].contains(&value) {
+                        let mut err = validator::ValidationError::new(#code_not_allowed);
+                        err.message = Some(format!("フィールド '{}' の値 ({}) が許可された値 ({}) のいずれでもありません",
+                            #error_key,
+                            value,
+                            #shown
+                        ).into());
+                        errors.add(#error_key, err);
+                    }
+                }
+            };
+
+            // 値が None なら検証されないため、Some("") や Some("  ") のような入力だけが対象になる
+            let non_blank_validation = if validation.present_non_blank {
+                quote! {
+                    if value.trim().is_empty() {
+                        let mut err = validator::ValidationError::new(#code_blank);
+                        err.message = Some(format!("フィールド '{}' が指定されていますが、値が空です",
+                            #error_key
+                        ).into());
+                        errors.add(#error_key, err);
+                    }
+                }
+            } else {
+                quote! {}
+            };
+
+            // 上流のデータの問題に気付けるよう、trim とは違い値を直さずにエラーにする
+            let whitespace_validation = if validation.no_surrounding_whitespace {
+                quote! {
+                    if value != value.trim() {
+                        let mut err = validator::ValidationError::new(#code_surrounding_whitespace);
+                        err.message = Some(format!("フィールド '{}' の値 ({:?}) の前後に空白があります",
+                            #error_key,
+                            value
+                        ).into());
+                        errors.add(#error_key, err);
+                    }
+                }
+            } else {
+                quote! {}
+            };
+
+            // ignore_case の場合は指定した文字列を生成時に小文字にしておき、値だけを実行時に小文字にする
+            let affix_value = if validation.ignore_case {
+                quote! { value.to_lowercase() }
+            } else {
+                quote! { value }
+            };
+            let affix_pattern = |affix: &String| if validation.ignore_case { affix.to_lowercase() } else { affix.clone() };
+            let starts_with_validation = match &validation.starts_with {
+                Some(prefix) => {
+                    let pattern = affix_pattern(prefix);
+                    quote! {
+                        if !#affix_value.starts_with(#pattern) {
+                            let mut err = validator::ValidationError::new(#code_invalid_prefix);
+                            err.message = Some(format!("フィールド '{}' の値 ({}) が '{}' で始まっていません",
+                                #error_key,
+                                value,
+                                #prefix
+                            ).into());
+                            errors.add(#error_key, err);
+                        }
+                    }
+                },
+                None => quote! {}
+            };
+            let ends_with_validation = match &validation.ends_with {
+                Some(suffix) => {
+                    let pattern = affix_pattern(suffix);
+                    quote! {
+                        if !#affix_value.ends_with(#pattern) {
+                            let mut err = validator::ValidationError::new(#code_invalid_suffix);
+                            err.message = Some(format!("フィールド '{}' の値 ({}) が '{}' で終わっていません",
+                                #error_key,
+                                value,
+                                #suffix
+                            ).into());
+                            errors.add(#error_key, err);
+                        }
+                    }
+                },
+                None => quote! {}
+            };
+
+            // 端末やログを崩さないよう、メッセージには値ではなく位置と文字のエスケープ表記を出す
+            let no_control_chars_validation = match validation.no_control_chars {
+                Some(allow_tab) => {
+                    let is_control = if allow_tab {
+                        quote! { c.is_control() && *c != '\t' }
+                    } else {
+                        quote! { c.is_control() }
+                    };
+                    quote! {
+                        if let Some((position, c)) = value.chars().enumerate().find(|(_, c)| #is_control) {
+                            let mut err = validator::ValidationError::new(#code_control_character);
+                            err.message = Some(format!("フィールド '{}' の {} 文字目に制御文字 ({}) が含まれています",
+                                #error_key,
+                                position + 1,
+                                c.escape_default()
+                            ).into());
+                            errors.add(#error_key, err);
+                        }
+                    }
+                },
+                None => quote! {}
+            };
+
+            // 拡張子は最後の . 以降 (".bashrc" のような隠しファイル名は拡張子なしとして扱う)
+            let file_ext_validation = if validation.file_ext.is_empty() {
+                quote! {}
+            } else {
+                let allowed = &validation.file_ext;
+                let shown = validation.file_ext.join(", ");
+                quote! {
+                    let extension = std::path::Path::new(value)
+                        .extension()
+                        .and_then(|ext| ext.to_str())
+                        .map(|ext| ext.to_lowercase());
+                    if !extension.is_some_and(|ext| [#(#allowed),*].contains(&ext.as_str())) {
+                        let mut err = validator::ValidationError::new(#code_invalid_extension);
+                        err.message = Some(format!("フィールド '{}' の値 ({}) の拡張子が許可された拡張子 ({}) のいずれでもありません",
+                            #error_key,
+                            value,
+                            #shown
+                        ).into());
+                        errors.add(#error_key, err);
+                    }
+                }
+            };
+
+            let jp_postal_code_validation = if validation.jp_postal_code {
+                quote! {
+                    let bytes = value.as_bytes();
+                    let is_postal_code = bytes.len() == 8
+                        && bytes[3] == b'-'
+                        && bytes.iter().enumerate().all(|(i, b)| i == 3 || b.is_ascii_digit());
+                    if !is_postal_code {
+                        let mut err = validator::ValidationError::new(#code_invalid_postal_code);
+                        err.message = Some(format!("フィールド '{}' の値 ({}) が郵便番号の形式ではありません（例: 123-4567）",
+                            #error_key,
+                            value
+                        ).into());
+                        errors.add(#error_key, err);
+                    }
+                }
+            } else {
+                quote! {}
+            };
+
+            // 長音符 (ー) はひらがな・カタカナのどちらにも使われるため両方で許可する
+            let kana_validation = match validation.kana {
+                Some(kana) => {
+                    let (allowed, name) = match kana {
+                        Kana::Katakana => (quote! { '\u{30A1}'..='\u{30FA}' | '\u{30FC}' | '\u{30FD}' | '\u{30FE}' }, "全角カタカナ"),
+                        Kana::HalfWidthKatakana => (quote! { '\u{FF66}'..='\u{FF9F}' }, "半角カタカナ"),
+                        Kana::Hiragana => (quote! { '\u{3041}'..='\u{3096}' | '\u{309D}' | '\u{309E}' | '\u{30FC}' }, "ひらがな"),
+                    };
+                    quote! {
+                        if !value.chars().all(|c| matches!(c, #allowed)) {
+                            let mut err = validator::ValidationError::new(#code_invalid_kana);
+                            err.message = Some(format!("フィールド '{}' の値 ({}) に{}以外の文字が含まれています",
+                                #error_key,
+                                value,
+                                #name
+                            ).into());
+                            errors.add(#error_key, err);
+                        }
+                    }
+                },
+                None => quote! {}
+            };
+
+            let hex_color_validation = match &validation.hex_color {
+                Some(hex_color) => {
+                    let lengths = &hex_color.lengths;
+                    let strip_hash = if hex_color.hash_optional {
+                        quote! { Some(value.strip_prefix('#').unwrap_or(value)) }
+                    } else {
+                        quote! { value.strip_prefix('#') }
+                    };
+                    let example = hex_color.lengths.iter().map(|length| match length {
+                        3 => "#RGB",
+                        4 => "#RGBA",
+                        6 => "#RRGGBB",
+                        _ => "#RRGGBBAA",
+                    }).collect::<Vec<_>>().join(", ");
+                    quote! {
+                        let is_color = #strip_hash.is_some_and(|digits| {
+                            [#(#lengths),*].contains(&digits.len()) && digits.bytes().all(|b| b.is_ascii_hexdigit())
+                        });
+                        if !is_color {
+                            let mut err = validator::ValidationError::new(#code_invalid_color);
+                            err.message = Some(format!("フィールド '{}' の値 ({}) が有効なカラーコードではありません（{}）",
+                                #error_key,
+                                value,
+                                #example
+                            ).into());
+                            errors.add(#error_key, err);
+                        }
+                    }
+                },
+                None => quote! {}
+            };
+
+            // パスワードは値をメッセージに含めず、満たしていない条件ごとにエラーを追加する
+            let password_validation = match &validation.password {
+                Some(password) => {
+                    let min_len = password.min_len;
+                    let requirements = [
+                        (password.require_upper, &code_missing_upper, quote! { char::is_uppercase }, "英大文字"),
+                        (password.require_lower, &code_missing_lower, quote! { char::is_lowercase }, "英小文字"),
+                        (password.require_digit, &code_missing_digit, quote! { |c: char| c.is_ascii_digit() }, "数字"),
+                        (password.require_symbol, &code_missing_symbol, quote! { |c: char| !c.is_alphanumeric() && !c.is_whitespace() }, "記号"),
+                    ];
+                    let class_checks = requirements.iter().filter(|(required, _, _, _)| *required).map(|(_, code, predicate, label)| quote! {
+                        if !value.chars().any(#predicate) {
+                            let mut err = validator::ValidationError::new(#code);
+                            err.message = Some(format!("フィールド '{}' には{}を1文字以上含める必要があります",
+                                #error_key,
+                                #label
+                            ).into());
+                            errors.add(#error_key, err);
+                        }
+                    });
+                    quote! {
+                        if value.chars().count() < #min_len {
+                            let mut err = validator::ValidationError::new(#code_password_too_short);
+                            err.message = Some(format!("フィールド '{}' は {} 文字以上である必要があります",
+                                #error_key,
+                                #min_len
+                            ).into());
+                            errors.add(#error_key, err);
+                        }
+                        #(#class_checks)*
+                    }
+                },
+                None => quote! {}
+            };
+
+            // 有効期限の月の末日までは有効とし、現在時刻との比較には chrono (chrono feature) を使う
+            let card_expiry_validation = match &validation.card_expiry {
+                Some(card_expiry) => {
+                    let expired_check = if card_expiry.allow_past {
+                        quote! {}
+                    } else {
+                        quote! {
+                            let today = raw_struct::chrono::Utc::now().date_naive();
+                            let now = raw_struct::chrono::Datelike::year(&today) * 12 + raw_struct::chrono::Datelike::month(&today) as i32;
+                            if year * 12 + month < now {
+                                let mut err = validator::ValidationError::new(#code_expired);
+                                err.message = Some(format!("フィールド '{}' の有効期限 ({}) が切れています",
+                                    #error_key,
+                                    value
+                                ).into());
+                                errors.add(#error_key, err);
+                            }
+                        }
+                    };
+                    quote! {
+                        let expiry = value.split_once('/').and_then(|(month, year)| {
+                            if month.len() != 2 || year.len() != 2 || !month.bytes().chain(year.bytes()).all(|b| b.is_ascii_digit()) {
+                                return None;
+                            }
+                            let month = month.parse::<i32>().ok().filter(|m| (1..=12).contains(m))?;
+                            let year = year.parse::<i32>().ok()?;
+                            Some((month, 2000 + year))
+                        });
+                        match expiry {
+                            Some((month, year)) => {
+                                #expired_check
+                            },
+                            None => {
+                                let mut err = validator::ValidationError::new(#code_invalid_expiry);
+                                err.message = Some(format!("フィールド '{}' の値 ({}) が有効期限の形式 (MM/YY) ではありません",
+                                    #error_key,
+                                    value
+                                ).into());
+                                errors.add(#error_key, err);
+                            }
+                        }
+                    }
+                },
+                None => quote! {}
+            };
+
+            // コロン区切り・ハイフン区切り・区切りなしの 48 ビット表記を受け付ける
+            let mac_validation = if validation.mac.is_some() {
+                quote! {
+                    let bytes = value.as_bytes();
+                    let is_mac = match bytes.len() {
+                        12 => bytes.iter().all(u8::is_ascii_hexdigit),
+                        17 => {
+                            let separator = bytes[2];
+                            (separator == b':' || separator == b'-')
+                                && bytes.iter().enumerate().all(|(i, b)| {
+                                    if i % 3 == 2 { *b == separator } else { b.is_ascii_hexdigit() }
+                                })
+                        },
+                        _ => false,
+                    };
+                    if !is_mac {
+                        let mut err = validator::ValidationError::new(#code_invalid_mac);
+                        err.message = Some(format!("フィールド '{}' の値 ({}) が有効な MAC アドレスではありません（例: 00:1A:2B:3C:4D:5E）",
+                            #error_key,
+                            value
+                        ).into());
+                        errors.add(#error_key, err);
+                    }
+                }
+            } else {
+                quote! {}
+            };
+
+            // NFC 正規化の判定は unicode-normalization クレート (unicode feature) を使う
+            let nfc_validation = if validation.nfc {
+                quote! {
+                    if !raw_struct::unicode_normalization::is_nfc(value) {
+                        let mut err = validator::ValidationError::new(#code_not_nfc);
+                        err.message = Some(format!("フィールド '{}' の値 ({}) が NFC 正規化されていません",
+                            #error_key,
+                            value
+                        ).into());
+                        errors.add(#error_key, err);
+                    }
+                }
+            } else {
+                quote! {}
+            };
+
+            // 各正規表現は初回の検証時に一度だけコンパイルする
+            let regex_any_validation = if validation.regex_any.is_empty() {
+                quote! {}
+            } else {
+                let patterns = &validation.regex_any;
+                let count = patterns.len();
+                let joined = patterns.join(", ");
+                quote! {
+                    static PATTERNS: std::sync::OnceLock<[raw_struct::regex::Regex; #count]> = std::sync::OnceLock::new();
+                    let patterns = PATTERNS.get_or_init(|| [#(raw_struct::regex::Regex::new(#patterns).unwrap()),*]);
+                    if !patterns.iter().any(|pattern| pattern.is_match(value)) {
+                        let mut err = validator::ValidationError::new(#code_pattern_mismatch);
+                        err.message = Some(format!("フィールド '{}' の値 ({}) がいずれの形式 ({}) にも一致しません",
+                            #error_key,
+                            value,
+                            #joined
+                        ).into());
+                        errors.add(#error_key, err);
+                    }
+                }
+            };
+
+            quote! {
+                #non_blank_validation
+                #whitespace_validation
+                #no_control_chars_validation
+                #length_validation
+                #one_of_validation
+                #starts_with_validation
+                #ends_with_validation
+                #luhn_validation
+                #iban_validation
+                #hostname_validation
+                #db_varchar_validation
+                #regex_any_validation
+                #mac_validation
+                #card_expiry_validation
+                #hex_color_validation
+                #password_validation
+                #file_ext_validation
+                #jp_postal_code_validation
+                #kana_validation
+                #nfc_validation
+                #phone_validation
+                #semver_validation
+                #semver_req_validation
+                #in_set_validation
+                #uuid_validation
+                #timezone_validation
+                #glob_validation
+            }
+        },
+        // 時刻の解析は chrono クレート (chrono feature) を使う
+        _ if is_naive_time(base_type) => {
+            let time_format = validation.time_format.as_deref().unwrap_or(DEFAULT_TIME_FORMAT);
+            quote! {
+                if raw_struct::chrono::NaiveTime::parse_from_str(value, #time_format).is_err() {
+                    let mut err = validator::ValidationError::new(#code_invalid_time);
+                    err.message = Some(format!("フィールド '{}' の値 ({}) が時刻の形式 ({}) に一致しません",
+                        #error_key,
+                        value,
+                        #time_format
+                    ).into());
+                    errors.add(#error_key, err);
+                }
+            }
+        },
+        // バリアント名は EnumNames トレイトの一覧と比べる
+        _ if validation.enum_name.is_some() => {
+            let enum_path = validation.enum_name.as_ref().unwrap();
+            let from_name = enum_from_name(enum_path, validation.case_insensitive);
+            quote! {
+                if #from_name.is_none() {
+                    let allowed = <#enum_path as raw_struct::EnumNames>::NAMES.join(", ");
+                    let mut err = validator::ValidationError::new(#code_unknown_variant);
+                    err.message = Some(format!("フィールド '{}' の値 ({}) が {} のバリアント名 ({}) のいずれでもありません",
+                        #error_key,
+                        value,
+                        stringify!(#enum_path),
+                        allowed
+                    ).into());
+                    err.add_param("allowed".into(), &allowed);
+                    errors.add(#error_key, err);
+                }
+            }
+        },
+        // 日付の範囲と曜日は chrono クレート (chrono feature) で解析してから調べる
+        _ if validation.date_range.is_some() || validation.business_day => {
+            let (min, max) = match &validation.date_range {
+                Some((min, max)) => (min.as_ref(), max.as_ref()),
+                None => (None, None),
+            };
+            let min_check = min.map(|bound| {
+                let bound = date_bound_expr(bound);
+                quote! {
+                    let min = #bound;
+                    if date < min {
+                        let mut err = validator::ValidationError::new(#code_out_of_range);
+                        err.message = Some(format!("フィールド '{}' の日付 ({}) が下限 ({}) より前です",
+                            #error_key,
+                            date,
+                            min
+                        ).into());
+                        errors.add(#error_key, err);
+                    }
+                }
+            });
+            let max_check = max.map(|bound| {
+                let bound = date_bound_expr(bound);
+                quote! {
+                    let max = #bound;
+                    if date > max {
+                        let mut err = validator::ValidationError::new(#code_out_of_range);
+                        err.message = Some(format!("フィールド '{}' の日付 ({}) が上限 ({}) より後です",
+                            #error_key,
+                            date,
+                            max
+                        ).into());
+                        errors.add(#error_key, err);
+                    }
+                }
+            });
+            // 休日は実行時に決まるため、ここでは土日だけを調べる (休日は validate_with() で調べる)
+            let business_day_check = validation.business_day.then(|| quote! {
+                if matches!(raw_struct::chrono::Datelike::weekday(&date), raw_struct::chrono::Weekday::Sat | raw_struct::chrono::Weekday::Sun) {
+                    let mut err = validator::ValidationError::new(#code_not_business_day);
+                    err.message = Some(format!("フィールド '{}' の日付 ({}) が土日です。営業日を指定してください",
+                        #error_key,
+                        date
+                    ).into());
+                    errors.add(#error_key, err);
+                }
+            });
+            quote! {
+                match value.parse::<raw_struct::chrono::NaiveDate>() {
+                    Ok(date) => {
+                        #min_check
+                        #max_check
+                        #business_day_check
+                    },
+                    Err(_) => {
+                        let mut err = validator::ValidationError::new(#code_invalid_value);
+                        err.message = Some(format!("フィールド '{}' の値 ({}) が有効な日付 (YYYY-MM-DD) ではありません",
+                            #error_key,
+                            value
+                        ).into());
+                        errors.add(#error_key, err);
+                    }
+                }
+            }
+        },
+        // uuid 属性付きのフィールド (uuid::Uuid 型) は UUID として検証する
+        _ if validation.uuid.is_some() => quote! {
+            #uuid_validation
+        },
+        // timezone 属性付きのフィールド (chrono_tz::Tz 型) はタイムゾーン名として検証する
+        _ if validation.timezone => quote! {
+            #timezone_validation
+        },
+        // glob 属性付きのフィールド (glob::Pattern 型) は glob パターンとして検証する
+        _ if validation.glob => quote! {
+            #glob_validation
+        },
+        _ => quote! {
+            match value.parse::<#base_type>() {
+                Ok(_) => {},
+                Err(_) => {
+                    let mut err = validator::ValidationError::new(#code_invalid_value);
+                    err.message = Some(format!("フィールド '{}' の値 ({}) が {}型として無効です", 
+                        #error_key,
+                        value,
+                        stringify!(#base_type)
+                    ).into());
+                    errors.add(#error_key, err);
+                }
+            }
+        }
+    };
+
+    let field_ident = field_name.as_ref().unwrap();
+    let getter_fn = &names.getter;
+    let check_fn = &names.check;
+    let validate_fn = &names.validate;
+    let parse_fn = &names.parse;
+    let doc = format!(" フィールド `{}` の値を単体で検証し、最初のエラーを返す。", field_ident);
+    let empty_check = if blank_is_missing(f) {
+        quote! {
+            if value.is_empty() {
+                return Ok(());
+            }
+        }
+    } else {
+        quote! {}
+    };
+
+    // 検証済みの値を宣言された型に変換する式
+    let parsed_value = match type_str.as_str() {
+        "u8" | "u16" | "u32" | "u64" | "u128" | "i8" | "i16" | "i32" | "i64" | "i128" if validation.radix.is_some() => {
+            let radix = validation.radix.unwrap();
+            let strip_prefix = radix_digits(radix);
+            quote! {
+                {
+                    #strip_prefix
+                    #base_type::from_str_radix(digits, #radix)
+                        .map_err(|_| validator::ValidationError::new(#code_invalid_value))?
+                }
+            }
+        },
+        "u8" | "u16" | "u32" | "u64" | "u128" | "i8" | "i16" | "i32" | "i64" | "i128" if validation.coerce_float => quote! {
+            value.split_once('.').map_or(value, |(int_part, _)| int_part).parse::<#base_type>()
+                .map_err(|_| validator::ValidationError::new(#code_invalid_value))?
+        },
+        "bool" => quote! {
+            matches!(value.to_lowercase().as_str(), "true" | "1")
+        },
+        "String" if validation.mac.as_ref().is_some_and(|mac| mac.normalize) => quote! {
+            value
+                .chars()
+                .filter(char::is_ascii_hexdigit)
+                .collect::<Vec<_>>()
+                .chunks(2)
+                .map(|pair| pair.iter().collect::<String>().to_lowercase())
+                .collect::<Vec<_>>()
+                .join(":")
+        },
+        "String" if validation.phone.as_ref().is_some_and(|phone| phone.normalize) => {
+            let e164 = phone_e164(validation.phone.as_ref().unwrap(), quote! { value });
+            quote! {
+                #e164.ok_or_else(|| validator::ValidationError::new(#code_invalid_phone))?
+            }
+        },
+        "String" if validation.normalize_nfc => quote! {
+            raw_struct::unicode_normalization::UnicodeNormalization::nfc(value).collect::<String>()
+        },
+        "String" => quote! {
+            value.to_string()
+        },
+        _ if validation.enum_name.is_some() => {
+            let from_name = enum_from_name(validation.enum_name.as_ref().unwrap(), validation.case_insensitive);
+            quote! {
+                #from_name.ok_or_else(|| validator::ValidationError::new(#code_unknown_variant))?
+            }
+        },
+        _ if is_naive_time(base_type) => {
+            let time_format = validation.time_format.as_deref().unwrap_or(DEFAULT_TIME_FORMAT);
+            quote! {
+                raw_struct::chrono::NaiveTime::parse_from_str(value, #time_format)
+                    .map_err(|_| validator::ValidationError::new(#code_invalid_value))?
+            }
+        },
+        _ => quote! {
+            value.parse::<#base_type>()
+                .map_err(|_| validator::ValidationError::new(#code_invalid_value))?
+        },
+    };
+    let getter_doc = format!(" フィールド `{}` の値を検証し、宣言された型に変換して返す。", field_ident);
+    let getter = if is_optional {
+        quote! {
+            #[doc = #getter_doc]
+            pub fn #getter_fn(&self) -> Result<Option<#base_type>, validator::ValidationError> {
+                match #present_value {
+                    Some(value) => {
+                        let value = value.as_str();
+                        Self::#validate_fn(value)?;
+                        Self::#parse_fn(value).map(Some)
+                    },
+                    None => Ok(None),
+                }
+            }
+        }
+    } else {
+        quote! {
+            #[doc = #getter_doc]
+            pub fn #getter_fn(&self) -> Result<#base_type, validator::ValidationError> {
+                match #field_ref {
+                    Some(value) => {
+                        let value = value.as_str();
+                        Self::#validate_fn(value)?;
+                        Self::#parse_fn(value)
+                    },
+                    None => {
+                        let mut err = validator::ValidationError::new(#code_required);
+                        err.message = Some(format!("フィールド '{}' は必須項目です", #error_key).into());
+                        Err(err)
+                    }
+                }
+            }
+        }
+    };
+
+    // borrowed の場合は借用版の生データ型の validate() でも同じチェックを使う
+    let check = quote! {
+        #required_check
+        if let Some(value) = #present_value {
+            #raw_struct_ident::#check_fn(value, &mut errors);
+        }
+    };
+    let validator_fns = quote! {
+        #[doc = #doc]
+        pub fn #validate_fn(value: &str) -> Result<(), validator::ValidationError> {
+            #empty_check
+            let mut errors = validator::ValidationErrors::new();
+            Self::#check_fn(value, &mut errors);
+            match errors.into_errors().remove(#error_key) {
+                Some(validator::ValidationErrorsKind::Field(mut errs)) if !errs.is_empty() => Err(errs.remove(0)),
+                _ => Ok(()),
+            }
+        }
+
+        #[allow(unused_variables)]
+        fn #check_fn(value: &str, errors: &mut validator::ValidationErrors) {
+            #digits_check
+            #leading_zeros_check
+            #socket_check
+            #value_check
+        }
+
+        #[doc(hidden)]
+        pub fn #parse_fn(value: &str) -> Result<#base_type, validator::ValidationError> {
+            let parsed = #parsed_value;
+            Ok(parsed)
+        }
+
+        #getter
+    };
+    (check, validator_fns)
+}
+
+// フィールド1つ分の、他のフィールドを参照するチェック (requires / format_by / same_as) を生成する関数
+fn cross_field_check(
+    fields: &syn::punctuated::Punctuated<syn::Field, syn::token::Comma>,
+    f: &syn::Field,
+    validation: &StringValidation,
+    options: &ContainerOptions,
+) -> syn::Result<proc_macro2::TokenStream> {
+    let field_ref = raw_field_ref(quote! { self }, f, options.strict_required);
+    let error_key = field_error_key(f, options.serde_names);
+    let code_required = error_code(validation, "required");
+    let code_pattern_mismatch = error_code(validation, "pattern_mismatch");
+    let code_mismatch = error_code(validation, "mismatch");
+
+    let requires_checks = validation.requires.iter().map(|required| {
+        let required_field = find_field(fields, &required.value()).ok_or_else(|| {
+            syn::Error::new(required.span(), format!("requires で指定されたフィールド '{}' が存在しません", required.value()))
+        })?;
+        let required_ref = raw_field_ref(quote! { self }, required_field, options.strict_required);
+        let required_key = field_error_key(required_field, options.serde_names);
+        Ok(quote! {
+            if #field_ref.is_some() && #required_ref.is_none() {
+                let mut err = validator::ValidationError::new(#code_required);
+                err.message = Some(format!("フィールド '{}' が指定されている場合、フィールド '{}' は必須項目です",
+                    #error_key,
+                    #required_key
+                ).into());
+                errors.add(#required_key, err);
+            }
+        })
+    }).collect::<syn::Result<Vec<_>>>()?;
+
+    let format_by_check = match &validation.format_by {
+        Some(format_by) => {
+            let by_field = find_field(fields, &format_by.field.value()).ok_or_else(|| {
+                syn::Error::new(format_by.field.span(), format!("format_by で指定されたフィールド '{}' が存在しません", format_by.field.value()))
+            })?;
+            let by_ref = raw_field_ref(quote! { self }, by_field, options.strict_required);
+            let by_key = field_error_key(by_field, options.serde_names);
+            let case_values = format_by.cases.iter().map(|(case, _)| case);
+            let case_indexes = 0..format_by.cases.len();
+            let patterns = format_by.cases.iter().map(|(_, pattern)| pattern);
+            quote! {
+                if let (Some(value), Some(key)) = (#field_ref, #by_ref) {
+                    static PATTERNS: std::sync::OnceLock<Vec<raw_struct::regex::Regex>> = std::sync::OnceLock::new();
+                    let patterns = PATTERNS.get_or_init(|| vec![#(raw_struct::regex::Regex::new(#patterns).unwrap()),*]);
+                    let index = match key.as_str() {
+                        #(#case_values => Some(#case_indexes),)*
+                        _ => None,
+                    };
+                    if let Some(index) = index {
+                        if !patterns[index].is_match(value) {
+                            let mut err = validator::ValidationError::new(#code_pattern_mismatch);
+                            err.message = Some(format!("フィールド '{}' の値 ({}) が '{}' = {} の場合の形式 ({}) に一致しません",
+                                #error_key,
+                                value,
+                                #by_key,
+                                key,
+                                patterns[index].as_str()
+                            ).into());
+                            errors.add(#error_key, err);
+                        }
+                    }
+                }
+            }
+        },
+        None => quote! {}
+    };
+
+    // 確認用フィールド (メールアドレスの再入力など) の一致チェック。エラーはこのフィールドに付ける
+    let same_as_check = match &validation.same_as {
+        Some(same_as) => {
+            let other_field = find_field(fields, &same_as.value()).ok_or_else(|| {
+                syn::Error::new(same_as.span(), format!("same_as で指定されたフィールド '{}' が存在しません", same_as.value()))
+            })?;
+            let other_ref = raw_field_ref(quote! { self }, other_field, options.strict_required);
+            let other_key = field_error_key(other_field, options.serde_names);
+            let trim = if validation.ignore_whitespace {
+                quote! { .trim() }
+            } else {
+                quote! {}
+            };
+            let case = if validation.ignore_case {
+                quote! { .to_lowercase() }
+            } else {
+                quote! {}
+            };
+            quote! {
+                if let (Some(value), Some(other)) = (#field_ref, #other_ref) {
+                    if value #trim #case != other #trim #case {
+                        let mut err = validator::ValidationError::new(#code_mismatch);
+                        err.message = Some(format!("フィールド '{}' の値がフィールド '{}' の値と一致しません",
+                            #error_key,
+                            #other_key
+                        ).into());
+                        errors.add(#error_key, err);
+                    }
+                }
+            }
+        },
+        None => quote! {}
+    };
+
+    Ok(quote! {
+        #(#requires_checks)*
+        #format_by_check
+        #same_as_check
+    })
+}
+
+// normalized() で作り直す各フィールドの式と、sanitize_all() で整形する各フィールドの代入を生成する関数
+fn normalize_fields(
+    fields: &syn::punctuated::Punctuated<syn::Field, syn::token::Comma>,
+    validations: &[StringValidation],
+    options: &ContainerOptions,
+) -> (Vec<proc_macro2::TokenStream>, Vec<proc_macro2::TokenStream>) {
+    // 文字列の整形 (trim → null_values → normalize → phone(normalize) → default → case の順) だけを行う
+    //
+    // 整形の指定がないフィールドは None とし、値をそのまま使う
+    let field_transforms = validations.iter().map(|validation| {
+        let trim = if validation.trim {
+            quote! { let value = value.map(|v| v.trim().to_string()); }
+        } else {
+            quote! {}
+        };
+        let null_values = if validation.null_values.is_empty() {
+            quote! {}
+        } else {
+            let null_values = &validation.null_values;
+            quote! { let value = value.filter(|v| ![#(#null_values),*].contains(&v.as_str())); }
+        };
+        let nfc = if validation.normalize_nfc {
+            quote! { let value = value.map(|v| raw_struct::unicode_normalization::UnicodeNormalization::nfc(v.as_str()).collect::<String>()); }
+        } else {
+            quote! {}
+        };
+        // 電話番号として解釈できない値は validate() でエラーにするため、そのまま残す
+        let phone = match &validation.phone {
+            Some(phone) if phone.normalize => {
+                let e164 = phone_e164(phone, quote! { v.as_str() });
+                quote! { let value = value.map(|v| #e164.unwrap_or(v)); }
+            },
+            _ => quote! {},
+        };
+        let default = match &validation.default {
+            Some(default) => quote! { let value = value.or_else(|| Some(#default.to_string())); },
+            None => quote! {}
+        };
+        let case = match validation.case {
+            Some(Case::Lower) => quote! { let value = value.map(|v| v.to_lowercase()); },
+            Some(Case::Upper) => quote! { let value = value.map(|v| v.to_uppercase()); },
+            None => quote! {}
+        };
+        if trim.is_empty() && null_values.is_empty() && nfc.is_empty() && phone.is_empty() && default.is_empty() && case.is_empty() {
+            return None;
+        }
+        Some(quote! {
+            #trim
+            #null_values
+            #nfc
+            #phone
+            #default
+            #case
+        })
+    }).collect::<Vec<_>>();
+    // strict_required で String になったフィールドは Some に包んで整形し、
+    // null_values などで未入力になった場合は空文字に戻す
+    let wrap_strict = |f: &syn::Field, value: proc_macro2::TokenStream| {
+        if is_strict(f, options.strict_required) {
+            (quote! { Some(#value) }, quote! { value.unwrap_or_default() })
+        } else {
+            (value, quote! { value })
+        }
+    };
+    let normalized_fields = fields.iter().zip(&field_transforms).map(|(f, transform)| {
+        let field_name = &f.ident;
+        match transform {
+            Some(transform) => {
+                let (value, result) = wrap_strict(f, quote! { self.#field_name });
+                quote! {
+                    #field_name: {
+                        let value = #value;
+                        #transform
+                        #result
+                    }
+                }
+            },
+            None => quote! { #field_name: self.#field_name },
+        }
+    });
+    let sanitize_fields = fields.iter().zip(&field_transforms).filter_map(|(f, transform)| {
+        let field_name = &f.ident;
+        let (value, result) = wrap_strict(f, quote! { std::mem::take(&mut record.#field_name) });
+        transform.as_ref().map(|transform| quote! {
+            record.#field_name = {
+                let value = #value;
+                #transform
+                #result
+            };
+        })
+    });
+
+    (normalized_fields.collect(), sanitize_fields.collect())
+}
+
+// validate_with() で validate() の後に行う、実行時の設定 (config) に従ったチェックを生成する関数
+//
+// lookup_values() で参照データの値を取り出す各フィールドの処理も合わせて返す
+fn runtime_checks(
+    fields: &syn::punctuated::Punctuated<syn::Field, syn::token::Comma>,
+    validations: &[StringValidation],
+    method_names: &[MethodNames],
+    options: &ContainerOptions,
+) -> (proc_macro2::TokenStream, Vec<proc_macro2::TokenStream>) {
+    // validate_with() で実行時に必須にされたフィールドのチェック
+    // (strict_required のフィールドは常に値があるため対象外。validate() と同じく String 以外の空文字は未入力とみなす)
+    let runtime_required_checks = fields.iter().zip(validations).filter(|(f, _)| !is_strict(f, options.strict_required)).map(|(f, validation)| {
+        let field_name = &f.ident;
+        let error_key = field_error_key(f, options.serde_names);
+        let code_required = error_code(validation, "required");
+        let base_type = get_inner_type(&f.ty).unwrap_or(&f.ty);
+        let is_string = quote!(#base_type).to_string() == "String";
+        let missing = if is_string {
+            quote! { self.#field_name.is_none() }
+        } else {
+            quote! { self.#field_name.as_ref().is_none_or(|v| v.is_empty()) }
+        };
+        quote! {
+            if config.is_required(#error_key) && #missing && !errors.errors().contains_key(#error_key) {
+                let mut err = validator::ValidationError::new(#code_required);
+                err.message = Some(format!("フィールド '{}' は必須項目です", #error_key).into());
+                errors.add(#error_key, err);
+            }
+        }
+    }).collect::<Vec<_>>();
+
+    // validate_with() で config に登録されたクロージャによるチェック (組み込みのチェックの後に行う)
+    let runtime_closure_checks = fields.iter().zip(validations).map(|(f, validation)| {
+        let error_key = field_error_key(f, options.serde_names);
+        let code_custom = error_code(validation, "custom");
+        let present_value = present_value(quote! { self }, f, options.strict_required);
+        quote! {
+            if let Some(value) = #present_value {
+                if let Err(message) = config.check(#error_key, value) {
+                    let mut err = validator::ValidationError::new(#code_custom);
+                    err.message = Some(message.into());
+                    errors.add(#error_key, err);
+                }
+            }
+        }
+    });
+
+    // business_day のフィールドが config に登録された休日でないかのチェック (日付として解析できた場合のみ)
+    let runtime_holiday_checks = fields.iter().zip(validations).zip(method_names).filter(|((_, validation), _)| validation.business_day).map(|((f, validation), names)| {
+        let error_key = field_error_key(f, options.serde_names);
+        let code_not_business_day = error_code(validation, "not_business_day");
+        let getter = &names.getter;
+        let date = if is_option_type(&f.ty) {
+            quote! { self.#getter() }
+        } else {
+            quote! { self.#getter().map(Some) }
+        };
+        quote! {
+            if let Ok(Some(date)) = #date {
+                if config.is_holiday(&date.format("%Y-%m-%d").to_string()) {
+                    let mut err = validator::ValidationError::new(#code_not_business_day);
+                    err.message = Some(format!("フィールド '{}' の日付 ({}) が休日です。営業日を指定してください",
+                        #error_key,
+                        date
+                    ).into());
+                    errors.add(#error_key, err);
+                }
+            }
+        }
+    }).collect::<Vec<_>>();
+
+    // config に登録された参照データのチェックと、値に対応する参照データの値の取り出し
+    let (runtime_lookup_checks, lookup_captures): (Vec<_>, Vec<_>) = fields.iter().zip(validations).map(|(f, validation)| {
+        let error_key = field_error_key(f, options.serde_names);
+        let code_not_in_lookup = error_code(validation, "not_in_lookup");
+        let present_value = present_value(quote! { self }, f, options.strict_required);
+        let check = quote! {
+            if let (Some(table), Some(value)) = (config.lookup_table(#error_key), #present_value) {
+                if !table.contains_key(value.as_str()) {
+                    let mut err = validator::ValidationError::new(#code_not_in_lookup);
+                    err.add_param("value".into(), value);
+                    err.message = Some(format!("フィールド '{}' の値 ({}) は参照データに存在しません", #error_key, value).into());
+                    errors.add(#error_key, err);
+                }
+            }
+        };
+        let capture = quote! {
+            if let (Some(table), Some(value)) = (config.lookup_table(#error_key), #present_value) {
+                if let Some(found) = table.get(value.as_str()) {
+                    values.insert(#error_key, found.clone());
+                }
+            }
+        };
+        (check, capture)
+    }).unzip();
+
+    let checks = quote! {
+        #(#runtime_required_checks)*
+        #(#runtime_closure_checks)*
+        #(#runtime_lookup_checks)*
+        #(#runtime_holiday_checks)*
+    };
+    (checks, lookup_captures)
+}
+
+// 生データ型 (と csv の行) から宣言された型の構造体に変換する TryFrom の実装を生成する関数
+fn try_from_impls(
+    struct_name: &syn::Ident,
+    raw_struct_ident: &syn::Ident,
+    fields: &syn::punctuated::Punctuated<syn::Field, syn::token::Comma>,
+    flatten_fields: &[FlattenField],
+    validations: &[StringValidation],
+    method_names: &[MethodNames],
+    options: &ContainerOptions,
+) -> proc_macro2::TokenStream {
+    // 検証してから各フィールドを宣言された型に変換する
+    //
+    // 変換後は生データが残らないため、エラーには元の文字列を "value" パラメータとして付ける
+    // (メッセージとは別に、長い値も省略せずに保持する)
+    //
+    // 変換先が String で整形の指定がないフィールドは、複製せずに生データから値を移動する。
+    // 移動すると生データを借用できなくなるため、他のフィールドをすべて変換してから移動する
+    let conversion_vars = (0..fields.len()).map(|i| format_ident!("value_{}", i)).collect::<Vec<_>>();
+    let is_movable = |f: &syn::Field, validation: &StringValidation| {
+        let base_type = get_inner_type(&f.ty).unwrap_or(&f.ty);
+        quote!(#base_type).to_string() == "String"
+            && !validation.normalize_nfc
+            && !validation.mac.as_ref().is_some_and(|mac| mac.normalize)
+            && !validation.phone.as_ref().is_some_and(|phone| phone.normalize)
+    };
+    let conversions = fields.iter().zip(validations).zip(method_names).zip(&conversion_vars).filter(|(((f, validation), _), _)| {
+        !is_movable(f, validation)
+    }).map(|(((f, _), names), var)| {
+        let getter = &names.getter;
+        let error_key = field_error_key(f, options.serde_names);
+        let field_ref = raw_field_ref(quote! { raw }, f, options.strict_required);
+        quote! {
+            let #var = raw.#getter().map_err(|mut err| {
+                if let Some(value) = #field_ref {
+                    err.add_param("value".into(), value);
+                }
+                let mut errors = validator::ValidationErrors::new();
+                errors.add(#error_key, err);
+                errors
+            })?;
+        }
+    }).collect::<Vec<_>>();
+    let converted_fields = fields.iter().zip(validations).zip(&conversion_vars).map(|((f, validation), var)| {
+        let field_name = &f.ident;
+        if !is_movable(f, validation) {
+            return quote! { #field_name: #var };
+        }
+        // validate() を通っているため、値がないのは all_optional の必須フィールドの場合だけ
+        if is_option_type(&f.ty) || is_strict(f, options.strict_required) {
+            quote! { #field_name: raw.#field_name }
+        } else {
+            let error_key = field_error_key(f, options.serde_names);
+            let code_required = error_code(validation, "required");
+            quote! {
+                #field_name: match raw.#field_name {
+                    Some(value) => value,
+                    None => {
+                        let mut err = validator::ValidationError::new(#code_required);
+                        err.message = Some(format!("フィールド '{}' は必須項目です", #error_key).into());
+                        let mut errors = validator::ValidationErrors::new();
+                        errors.add(#error_key, err);
+                        return Err(errors);
+                    }
+                }
+            }
+        }
+    });
+
+    let field_names = fields.iter().map(|f| field_error_name(f, options.serde_names));
+    let raw_field_refs = fields.iter().map(|f| raw_field_ref(quote! { raw }, f, options.strict_required));
+    let flatten_idents = flatten_fields.iter().map(|flatten| &flatten.field.ident).collect::<Vec<_>>();
+    let flatten_types = flatten_fields.iter().map(|flatten| &flatten.field.ty);
+    let flatten_keys = flatten_fields.iter().map(|flatten| field_error_key(&flatten.field, options.serde_names));
+
+    let mut try_from_doc = " validate() で検証してから宣言された型に変換する。エラーには元の値を \"value\" のパラメータとして付ける。".to_string();
+    if options.all_optional {
+        try_from_doc.push_str("\n\n all_optional により validate() が通っても、Option でないフィールドが未入力の場合は\
+            宣言された型の値を作れないため、そのフィールドの \"required\" のエラーを返す。");
+    }
+
+    quote! {
+    #[doc = #try_from_doc]
+    impl TryFrom<#raw_struct_ident> for #struct_name {
+        type Error = validator::ValidationErrors;
+
+        fn try_from(raw: #raw_struct_ident) -> Result<Self, Self::Error> {
+            if let Err(mut errors) = validator::Validate::validate(&raw) {
+                for (key, kind) in errors.errors_mut() {
+                    let value = match *key {
+                        #(#field_names => #raw_field_refs,)*
+                        _ => None,
+                    };
+                    if let (validator::ValidationErrorsKind::Field(errs), Some(value)) = (kind, value) {
+                        for err in errs {
+                            err.add_param("value".into(), value);
+                        }
+                    }
+                }
+                return Err(errors);
+            }
+            #(#conversions)*
+            Ok(#struct_name {
+                #(#converted_fields,)*
+                #(#flatten_idents: <#flatten_types>::try_from(raw.#flatten_idents).map_err(|child_errors| {
+                    let mut errors = validator::ValidationErrors::new();
+                    errors.errors_mut().insert(#flatten_keys, validator::ValidationErrorsKind::Struct(Box::new(child_errors)));
+                    errors
+                })?,)*
+            })
+        }
+    }
+
+    // (値の行, ヘッダ行) から生データ型を経由して検証・変換する
+    // (csv のデシリアライズは余った列を無視するため、列数の不一致は先に調べる)
+    impl TryFrom<(&raw_struct::csv::StringRecord, &raw_struct::csv::StringRecord)> for #struct_name {
+        type Error = raw_struct::Error;
+
+        fn try_from((record, headers): (&raw_struct::csv::StringRecord, &raw_struct::csv::StringRecord)) -> Result<Self, Self::Error> {
+            if record.len() != headers.len() {
+                return Err(raw_struct::Error::UnequalLengths { headers: headers.len(), record: record.len() });
+            }
+            let raw: #raw_struct_ident = record.deserialize(Some(headers))?;
+            Ok(#struct_name::try_from(raw)?)
+        }
+    }
+    }
+}
+
+// options.lossy の場合に try_into_lossy() を生成する関数
+fn lossy_fn(
+    struct_name: &syn::Ident,
+    fields: &syn::punctuated::Punctuated<syn::Field, syn::token::Comma>,
+    validations: &[StringValidation],
+    method_names: &[MethodNames],
+    options: &ContainerOptions,
+) -> proc_macro2::TokenStream {
+    if !options.lossy {
+        return quote! {};
+    }
+
+    // 変換できなかったフィールドは default の値 (なければ型の Default) で補い、エラーは報告として集める
+    // (default の値は検証せずに解析だけするため、解析できない場合も panic せずに型の Default で補う)
+    let lossy_conversions = fields.iter().zip(validations).zip(method_names).map(|((f, validation), names)| {
+        let field_name = &f.ident;
+        let getter = &names.getter;
+        let error_key = field_error_key(f, options.serde_names);
+        let field_ref = raw_field_ref(quote! { self }, f, options.strict_required);
+        let parse = &names.parse;
+        let fallback = match &validation.default {
+            // Option の空文字の既定値は未入力 (None) になる
+            Some(default) if is_option_type(&f.ty) && default.value().is_empty() => quote! { None },
+            Some(default) if is_option_type(&f.ty) => quote! { Self::#parse(#default).ok() },
+            Some(default) => quote! { Self::#parse(#default).unwrap_or_default() },
+            None => quote! { Default::default() },
+        };
+        quote! {
+            #field_name: match self.#getter() {
+                Ok(value) => value,
+                Err(mut err) => {
+                    if let Some(value) = #field_ref {
+                        err.add_param("value".into(), value);
+                    }
+                    err.add_param("field".into(), &#error_key);
+                    report.push(err);
+                    #fallback
+                }
+            }
+        }
+    });
+    quote! {
+        /// 変換できないフィールドを既定値で補いながら、宣言された型の構造体に変換する。
+        ///
+        /// 補う値は `validate(default = "...")` があればその値 (検証せずに解析だけする)、なければ型の Default。
+        /// 補ったフィールドのエラーには "field" と "value" (元の値) のパラメータを付けて返す。
+        /// 構造体全体の検証 (フィールド間のチェックなど) は行わない。
+        pub fn try_into_lossy(self) -> (#struct_name, Vec<validator::ValidationError>) {
+            let mut report = Vec::new();
+            let record = #struct_name {
+                #(#lossy_conversions,)*
+            };
+            (record, report)
+        }
+    }
+}
+
+// into_lenient_optional() を生成する関数
+fn lenient_fn(
+    struct_name: &syn::Ident,
+    fields: &syn::punctuated::Punctuated<syn::Field, syn::token::Comma>,
+    flatten_fields: &[FlattenField],
+    method_names: &[MethodNames],
+    options: &ContainerOptions,
+) -> proc_macro2::TokenStream {
+    // Option のフィールドは解析できなければ None にして警告として記録し、必須のフィールドはエラーにする
+    // (フィールド名が errors などのローカル変数と衝突しないよう、変換した値は番号付きの変数に入れる)
+    let lenient_vars = (0..fields.len()).map(|i| format_ident!("value_{}", i)).collect::<Vec<_>>();
+    let lenient_conversions = fields.iter().zip(method_names).zip(&lenient_vars).map(|((f, names), var)| {
+        let getter = &names.getter;
+        let error_key = field_error_key(f, options.serde_names);
+        let field_ref = raw_field_ref(quote! { self }, f, options.strict_required);
+        if is_option_type(&f.ty) {
+            quote! {
+                let #var = self.#getter().unwrap_or_else(|err| {
+                    warnings.push(raw_struct::Warning {
+                        field: #error_key,
+                        value: #field_ref.cloned().unwrap_or_default(),
+                        error: err,
+                    });
+                    None
+                });
+            }
+        } else {
+            quote! {
+                let #var = self.#getter().map_err(|mut err| {
+                    if let Some(value) = #field_ref {
+                        err.add_param("value".into(), value);
+                    }
+                    errors.add(#error_key, err);
+                });
+            }
+        }
+    });
+    let lenient_fields = fields.iter().zip(&lenient_vars).map(|(f, var)| {
+        let field_name = &f.ident;
+        quote! { #field_name: #var }
+    });
+    // 必須のフィールドの変換結果 (Result<_, ()>)。エラーは errors に記録済みのため、Err があればそのまま返す
+    let required_vars = fields
+        .iter()
+        .zip(&lenient_vars)
+        .filter(|(f, _)| !is_option_type(&f.ty))
+        .map(|(_, var)| var)
+        .collect::<Vec<_>>();
+
+    // flatten したフィールドは子の into_lenient_optional() で変換し、警告は子のものをそのまま引き継ぐ
+    // (self からフィールドを移動するため、他のフィールドの変換の後に行う)
+    let flatten_lenient_vars = (0..flatten_fields.len()).map(|i| format_ident!("child_{}", i)).collect::<Vec<_>>();
+    let flatten_lenient_conversions = flatten_fields.iter().zip(&flatten_lenient_vars).map(|(flatten, var)| {
+        let ident = &flatten.field.ident;
+        let error_key = field_error_key(&flatten.field, options.serde_names);
+        quote! {
+            let #var = match self.#ident.into_lenient_optional() {
+                Ok((value, child_warnings)) => {
+                    warnings.extend(child_warnings);
+                    Some(value)
+                },
+                Err(child_errors) => {
+                    errors.errors_mut().insert(#error_key, validator::ValidationErrorsKind::Struct(Box::new(child_errors)));
+                    None
+                },
+            };
+        }
+    });
+    let flatten_idents = flatten_fields.iter().map(|flatten| &flatten.field.ident);
+    let unwrap_results = if required_vars.is_empty() && flatten_lenient_vars.is_empty() {
+        quote! {}
+    } else {
+        quote! {
+            let (#(Ok(#required_vars),)* #(Some(#flatten_lenient_vars),)*) = (#(#required_vars,)* #(#flatten_lenient_vars,)*) else {
+                return Err(errors);
+            };
+        }
+    };
+
+    quote! {
+        /// Option のフィールドの解析できない値を None に置き換えながら、宣言された型の構造体に変換する。
+        ///
+        /// 置き換えたフィールドは警告として返し、必須のフィールドが変換できない場合はエラーにする。
+        /// 構造体全体の検証 (フィールド間のチェックなど) は行わない。
+        pub fn into_lenient_optional(self) -> Result<(#struct_name, Vec<raw_struct::Warning>), validator::ValidationErrors> {
+            let mut warnings = Vec::new();
+            #[allow(unused_mut, unused_variables)]
+            let mut errors = validator::ValidationErrors::new();
+            #(#lenient_conversions)*
+            #(#flatten_lenient_conversions)*
+            #unwrap_results
+            Ok((#struct_name { #(#lenient_fields,)* #(#flatten_idents: #flatten_lenient_vars,)* }, warnings))
+        }
+    }
+}
+
+// options.borrowed の場合に、入力のバッファを借用する生データ型 (RawXRef<'a>) を生成する関数
+//
+// フィールドごとのチェックは生データ型の関数を &str のまま呼び、文字列を確保しない。
+// フィールド間のチェックなど構造体全体のチェックがある場合だけ、所有する生データ型に変換して行う
+fn borrowed_impl(
+    struct_name: &syn::Ident,
+    raw_struct_ident: &syn::Ident,
+    fields: &syn::punctuated::Punctuated<syn::Field, syn::token::Comma>,
+    options: &ContainerOptions,
+    checks: &ValidateChecks,
+    struct_attrs: proc_macro2::TokenStream,
+) -> proc_macro2::TokenStream {
+    if !options.borrowed {
+        return quote! {};
+    }
+    let ValidateChecks { field_checks: validation_checks, cross_field_checks, sum_checks, checksum_checks, post_validate, error_caps } = checks;
+    let ref_ident = format_ident!("{}Ref", raw_struct_ident);
+    let ref_fields = fields.iter().map(|f| {
+        let field_name = &f.ident;
+        let vis = &f.vis;
+        let rename = serde_rename(f).map(|name| quote! { #[serde(rename = #name)] });
+        let ref_type = if is_strict(f, options.strict_required) {
+            quote! { &'a str }
+        } else {
+            quote! { Option<&'a str> }
+        };
+        quote! {
+            #rename
+            #[serde(borrow)]
+            #vis #field_name: #ref_type
+        }
+    });
+    let to_owned_fields = fields.iter().map(|f| {
+        let field_name = &f.ident;
+        if is_strict(f, options.strict_required) {
+            quote! { #field_name: raw.#field_name.to_string() }
+        } else {
+            quote! { #field_name: raw.#field_name.map(str::to_string) }
+        }
+    });
+    let has_struct_checks = cross_field_checks.iter().any(|checks| !checks.is_empty())
+        || !sum_checks.is_empty()
+        || !checksum_checks.is_empty()
+        || !post_validate.is_empty();
+    let errors_mut = if error_caps.is_empty() { quote! {} } else { quote! { mut } };
+    let (struct_check_fn, struct_check) = if has_struct_checks {
+        (
+            quote! {
+                impl #raw_struct_ident {
+                    #[doc(hidden)]
+                    pub fn __validate_struct(&self, mut errors: validator::ValidationErrors) -> validator::ValidationErrors {
+                        #(#cross_field_checks)*
+                        #(#sum_checks)*
+                        #(#checksum_checks)*
+                        #post_validate
+                        errors
+                    }
+                }
+            },
+            quote! { let #errors_mut errors = #raw_struct_ident::from(self).__validate_struct(errors); },
+        )
+    } else {
+        (quote! {}, quote! {})
+    };
+    let ref_doc = format!(" `{}` の入力を借用する版。CSV の行などから文字列を確保せずに読み込んで検証できる。", raw_struct_ident);
+    quote! {
+        #[doc = #ref_doc]
+        #[derive(Debug, Clone, Copy, Default, serde::Deserialize)]
+        #struct_attrs
+        pub struct #ref_ident<'a> {
+            #(#ref_fields,)*
+        }
+
+        impl From<&#ref_ident<'_>> for #raw_struct_ident {
+            fn from(raw: &#ref_ident<'_>) -> Self {
+                #raw_struct_ident {
+                    #(#to_owned_fields,)*
+                }
+            }
+        }
+
+        #struct_check_fn
+
+        impl validator::Validate for #ref_ident<'_> {
+            fn validate(&self) -> Result<(), validator::ValidationErrors> {
+                let mut errors = validator::ValidationErrors::new();
+
+                #(#validation_checks)*
+                #struct_check
+                #(#error_caps)*
+
+                if errors.is_empty() {
+                    Ok(())
+                } else {
+                    Err(errors)
+                }
+            }
+        }
+
+        // 型変換では値を所有する必要があるため、生データ型に変換してから行う
+        impl TryFrom<#ref_ident<'_>> for #struct_name {
+            type Error = validator::ValidationErrors;
+
+            fn try_from(raw: #ref_ident<'_>) -> Result<Self, Self::Error> {
+                #struct_name::try_from(#raw_struct_ident::from(&raw))
+            }
+        }
+    }
+}
+
+// 列番号順に並んだフィールドの列挙型 (RecordField など) を生成する関数
+fn field_enum(
+    struct_name: &syn::Ident,
+    raw_struct_ident: &syn::Ident,
+    fields: &syn::punctuated::Punctuated<syn::Field, syn::token::Comma>,
+    headers: &[String],
+) -> proc_macro2::TokenStream {
+    let field_enum_ident = format_ident!("{}Field", struct_name);
+    let variants = fields
+        .iter()
+        .map(|f| format_ident!("{}", to_pascal_case(&f.ident.as_ref().unwrap().unraw().to_string())))
+        .collect::<Vec<_>>();
+    let field_enum_doc = format!(" `{}` のフィールドを宣言順に並べた列挙型。`as usize` で列番号になる。", raw_struct_ident);
+
+    quote! {
+        #[doc = #field_enum_doc]
+        #[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+        pub enum #field_enum_ident {
+            #(#variants,)*
+        }
+
+        impl #field_enum_ident {
+            /// CSV のヘッダ名 (serde の rename があればその名前) を返す。
+            pub fn name(&self) -> &'static str {
+                match self {
+                    #(Self::#variants => #headers,)*
+                }
+            }
+
+            /// ヘッダ名から対応するフィールドを探す。
+            pub fn from_header(header: &str) -> Option<Self> {
+                match header {
+                    #(#headers => Some(Self::#variants),)*
+                    _ => None,
+                }
+            }
+        }
+    }
+}
+
+// 生データ型の serde の derive と、flatten がある場合に手で書く serde の実装を生成する関数
+//
+// 子の列は親の生データ型のフィールドにならないため、flatten がある場合は
+// 列名と値の組 (子の列は prefix 付き) として serde の実装を手で書く
+fn flatten_serde_impl(raw_struct_ident: &syn::Ident, flatten_fields: &[FlattenField]) -> (proc_macro2::TokenStream, proc_macro2::TokenStream) {
+    if flatten_fields.is_empty() {
+        (quote! { serde::Deserialize, serde::Serialize }, quote! {})
+    } else {
+        let raw_struct_name = raw_struct_ident.to_string();
+        (quote! {}, quote! {
+            impl serde::Serialize for #raw_struct_ident {
+                fn serialize<S: serde::Serializer>(&self, serializer: S) -> Result<S::Ok, S::Error> {
+                    use serde::ser::SerializeStruct;
+                    // serialize_struct の列名は &'static str のため、prefix を付けた列名を型ごとの static に持って借用する
+                    // (csv は serialize_map に対応しないため、String の列名の map としては書き出せない)
+                    static HEADERS: std::sync::OnceLock<Vec<String>> = std::sync::OnceLock::new();
+                    let mut columns = Vec::new();
+                    self.__flat_columns("", &mut columns);
+                    let headers = HEADERS.get_or_init(|| columns.iter().map(|(header, _)| header.clone()).collect());
+                    let mut state = serializer.serialize_struct(#raw_struct_name, columns.len())?;
+                    for (header, (_, value)) in headers.iter().zip(&columns) {
+                        state.serialize_field(header.as_str(), value)?;
+                    }
+                    state.end()
+                }
+            }
+
+            impl<'de> serde::Deserialize<'de> for #raw_struct_ident {
+                fn deserialize<D: serde::Deserializer<'de>>(deserializer: D) -> Result<Self, D::Error> {
+                    let columns = <std::collections::HashMap<String, Option<String>> as serde::Deserialize>::deserialize(deserializer)?;
+                    let mut raw = Self::default();
+                    for (name, value) in columns {
+                        raw.set_field(&name, value);
+                    }
+                    Ok(raw)
+                }
+            }
+        })
+    }
+}
+
+// 数値型のフィールドの値 (var) を f64 に変換する式を生成する関数。数値型でなければ None を返す
+fn numeric_as_f64(field: &syn::Field, var: &syn::Ident) -> Option<proc_macro2::TokenStream> {
+    let base_type = get_inner_type(&field.ty).unwrap_or(&field.ty);
+    match quote!(#base_type).to_string().as_str() {
+        "f64" => Some(quote! { #var }),
+        "u8" | "u16" | "u32" | "u64" | "u128" | "usize" | "i8" | "i16" | "i32" | "i64" | "i128" | "isize" | "f32" => {
+            Some(quote! { #var as f64 })
+        },
+        _ => None,
+    }
+}
+
+// ? でそのまま伝播できる validate_anyhow() を生成する関数
+#[cfg(feature = "anyhow")]
+fn anyhow_impl(raw_struct_ident: &syn::Ident) -> proc_macro2::TokenStream {
+    quote! {
+        impl #raw_struct_ident {
+            /// validate() を実行し、失敗した場合は全エラーを1行ずつ並べた
+            /// `anyhow::Error` を返す。
+            pub fn validate_anyhow(&self) -> anyhow::Result<()> {
+                validator::Validate::validate(self).map_err(|errors| {
+                    let mut lines = raw_struct::flat_field_errors(&errors)
+                        .into_iter()
+                        .flat_map(|(field, errs)| {
+                            errs.iter().map(move |err| match &err.message {
+                                Some(message) => format!("{}: {}", field, message),
+                                None => format!("{}: {}", field, err.code),
+                            })
+                        })
+                        .collect::<Vec<_>>();
+                    // HashMap の順序に左右されないよう並べ替える
+                    lines.sort();
+                    anyhow::anyhow!("{} の検証に失敗しました:\n{}", stringify!(#raw_struct_ident), lines.join("\n"))
+                })
+            }
+        }
+    }
+}
+
+// 基数に対応する接頭辞 (0x / 0o / 0b) を取り除いた digits を束縛するコードを生成する関数
+fn radix_digits(radix: u32) -> proc_macro2::TokenStream {
+    let prefix = match radix {
+        16 => "0x",
+        8 => "0o",
+        2 => "0b",
+        _ => return quote! { let digits = value; },
+    };
+    let upper = prefix.to_uppercase();
+    quote! {
+        let digits = value
+            .strip_prefix(#prefix)
+            .or_else(|| value.strip_prefix(#upper))
+            .unwrap_or(value);
+    }
+}
+
+// フィールドに付いた #[serde(rename = "...")] の名前を取得する補助関数
+fn serde_rename(field: &syn::Field) -> Option<syn::LitStr> {
+    field
+        .attrs
+        .iter()
+        .filter(|attr| attr.path.is_ident("serde"))
+        // serde の属性は serde 自身が検証するため、解析できないものは無視する
+        .filter_map(|attr| attr.parse_args_with(attr::parse_args).ok())
+        .flatten()
+        .find_map(|arg| match arg {
+            AttrArg::Value(key, value) if key == "rename" => value.parse_str(&key).ok(),
+            _ => None,
+        })
+}
+
+// ValidationErrors のキーとメッセージに使うフィールド名を求める関数
+fn field_error_name(field: &syn::Field, serde_names: bool) -> String {
+    match serde_rename(field) {
+        Some(name) if serde_names => name.value(),
+        _ => field.ident.as_ref().unwrap().unraw().to_string(),
+    }
+}
+
+// エラーコードを文字列リテラルとして生成する関数 (code = "..." の指定があればそれを優先する)
+fn error_code(validation: &StringValidation, default: &str) -> proc_macro2::TokenStream {
+    match &validation.code {
+        Some(code) => quote! { #code },
+        None => quote! { #default },
+    }
+}
+
+// strict_required で生データ型のフィールドが String になるか
+fn is_strict(field: &syn::Field, strict_required: bool) -> bool {
+    strict_required && !is_option_type(&field.ty)
+}
+
+// 生データ型のフィールドを Option<&String> として参照する式を生成する関数
+// (String になったフィールドは常に Some として扱う)
+fn raw_field_ref(receiver: proc_macro2::TokenStream, field: &syn::Field, strict_required: bool) -> proc_macro2::TokenStream {
+    let field_name = &field.ident;
+    if is_strict(field, strict_required) {
+        quote! { Some(&#receiver.#field_name) }
+    } else {
+        quote! { #receiver.#field_name.as_ref() }
+    }
+}
+
+// Option<T> (T が String 以外) のフィールドで、空文字を未入力 (None) とみなすかを判定する補助関数
+fn blank_is_missing(field: &syn::Field) -> bool {
+    let base_type = get_inner_type(&field.ty).unwrap_or(&field.ty);
+    is_option_type(&field.ty) && quote!(#base_type).to_string() != "String"
+}
+
+// 値が入力されている場合だけ Some(&String) になる式を生成する関数 (blank_is_missing の空文字は None にする)
+fn present_value(receiver: proc_macro2::TokenStream, field: &syn::Field, strict_required: bool) -> proc_macro2::TokenStream {
+    let field_ref = raw_field_ref(receiver, field, strict_required);
+    if blank_is_missing(field) {
+        quote! { #field_ref.filter(|v| !v.is_empty()) }
+    } else {
+        field_ref
+    }
+}
+
+// フィールド名を文字列リテラルとして生成する関数
+fn field_error_key(field: &syn::Field, serde_names: bool) -> proc_macro2::TokenStream {
+    let name = field_error_name(field, serde_names);
+    quote! { #name }
+}
+
+// snake_case のフィールド名を PascalCase に変換する補助関数
+fn to_pascal_case(name: &str) -> String {
+    name.split('_')
+        .filter(|part| !part.is_empty())
+        .map(|part| {
+            let mut chars = part.chars();
+            match chars.next() {
+                Some(first) => first.to_uppercase().chain(chars).collect::<String>(),
+                None => String::new(),
+            }
+        })
+        .collect()
+}
+
+fn to_snake_case(name: &str) -> String {
+    let chars = name.chars().collect::<Vec<_>>();
+    let mut snake = String::new();
+    for (i, c) in chars.iter().enumerate() {
+        if c.is_uppercase() {
+            // 単語の区切り (fooBar, FOOBar の B) でのみ _ を入れる
+            let prev = i.checked_sub(1).map(|j| chars[j]);
+            let next = chars.get(i + 1);
+            let boundary = match prev {
+                Some(prev) if prev.is_lowercase() || prev.is_ascii_digit() => true,
+                Some(prev) if prev.is_uppercase() => next.is_some_and(|next| next.is_lowercase()),
+                _ => false,
+            };
+            if boundary {
+                snake.push('_');
+            }
+            snake.extend(c.to_lowercase());
+        } else {
+            snake.push(*c);
+        }
+    }
+    snake
+}
+
+// "1900-01-01" 形式の日付または today / now を解析する関数 (存在しない日付は None)
+fn parse_date_bound(bound: &str) -> Option<DateBound> {
+    if bound == "today" || bound == "now" {
+        return Some(DateBound::Today);
+    }
+    let mut parts = bound.splitn(3, '-');
+    let year = parts.next()?.parse::<i32>().ok()?;
+    let month = parts.next()?.parse::<u32>().ok()?;
+    let day = parts.next()?.parse::<u32>().ok()?;
+    let leap = (year % 4 == 0 && year % 100 != 0) || year % 400 == 0;
+    let days = match month {
+        1 | 3 | 5 | 7 | 8 | 10 | 12 => 31,
+        4 | 6 | 9 | 11 => 30,
+        2 if leap => 29,
+        2 => 28,
+        _ => return None,
+    };
+    (1..=days).contains(&day).then_some(DateBound::Date(year, month, day))
+}
+
+// "1024..=65535" / "8000..9000" をポート番号の範囲 (両端を含む) に変換する関数
+fn parse_port_range(range: &str) -> Option<(u16, u16)> {
+    let (lo, hi) = match range.split_once("..=") {
+        Some((lo, hi)) => (lo.trim().parse::<u16>().ok()?, hi.trim().parse::<u16>().ok()?),
+        None => {
+            let (lo, hi) = range.split_once("..")?;
+            (lo.trim().parse::<u16>().ok()?, hi.trim().parse::<u16>().ok()?.checked_sub(1)?)
+        },
+    };
+    (lo <= hi).then_some((lo, hi))
+}
+
+// 文字列から識別子を作る (キーワードは r#... にする)
+fn method_ident(name: &str, span: proc_macro2::Span) -> syn::Ident {
+    syn::parse_str::<syn::Ident>(name)
+        .map(|ident| syn::Ident::new(&ident.to_string(), span))
+        .unwrap_or_else(|_| syn::Ident::new_raw(name, span))
+}
+
+// 各フィールドのメソッド名を決め、重複があればコンパイルエラーにする関数
+fn method_names(
+    fields: &syn::punctuated::Punctuated<syn::Field, syn::token::Comma>,
+    snake_case: bool,
+) -> syn::Result<Vec<MethodNames>> {
+    let mut names: Vec<MethodNames> = Vec::new();
+    for f in fields {
+        let field_ident = f.ident.as_ref().unwrap();
+        let span = field_ident.span();
+        let mut base = field_ident.unraw().to_string();
+        if snake_case {
+            base = to_snake_case(&base);
+        }
+        if RESERVED_METHODS.contains(&base.as_str()) || NON_RAW_KEYWORDS.contains(&base.as_str()) {
+            base = format!("{}_value", base);
+        }
+        let mut validate = format!("validate_{}", base);
+        if RESERVED_METHODS.contains(&validate.as_str()) {
+            validate = format!("validate_{}_value", base);
+        }
+        let method = MethodNames {
+            getter: method_ident(&base, span),
+            validate: syn::Ident::new(&validate, span),
+            check: syn::Ident::new(&format!("__check_{}", base), span),
+            parse: syn::Ident::new(&format!("__parse_{}", base), span),
+        };
+
+        let generated = [&method.getter, &method.validate];
+        let conflict = names.iter().zip(fields).find(|(other, _)| {
+            [&other.getter, &other.validate].iter().any(|name| generated.contains(name))
+        });
+        if let Some((_, other)) = conflict {
+            return Err(syn::Error::new_spanned(
+                field_ident,
+                format!("フィールド '{}' から生成されるメソッド名がフィールド '{}' のものと重複しています",
+                    field_ident.unraw(),
+                    other.ident.as_ref().unwrap().unraw()
+                ),
+            ));
+        }
+        names.push(method);
+    }
+    Ok(names)
+}
+
+// range(...) / one_of / step の値をフィールドの型に合わせたリテラルにする補助関数
+//
+// 型推論に任せられるよう接尾辞なしのリテラルを返す。key はエラーメッセージに示す属性名
+fn bound_literal(key: &str, lit: &Lit, is_float: bool) -> syn::Result<proc_macro2::TokenStream> {
+    match lit {
+        Lit::Int(int) if is_float => Ok(float_literal(int.base10_parse()?)),
+        Lit::Float(float) if is_float => Ok(float_literal(float.base10_parse()?)),
+        Lit::Int(int) => Ok(int_literal(int.base10_parse()?)),
+        _ if is_float => Err(syn::Error::new(lit.span(), format!("浮動小数点数のフィールドの {} には数値を指定してください", key))),
+        _ => Err(syn::Error::new(lit.span(), format!("整数のフィールドの {} には整数を指定してください", key))),
+    }
+}
+
+// 整数・浮動小数点数のリテラルを f64 として読む補助関数 (epsilon や sigma などの属性の値に使う)
+fn lit_as_f64(lit: &Lit) -> syn::Result<f64> {
+    match lit {
+        Lit::Int(int) => int.base10_parse(),
+        Lit::Float(float) => float.base10_parse(),
+        _ => Err(syn::Error::new(lit.span(), "数値を指定してください")),
+    }
+}
+
+fn int_literal(value: i128) -> proc_macro2::TokenStream {
+    let literal = proc_macro2::Literal::u128_unsuffixed(value.unsigned_abs());
+    if value < 0 {
+        quote! { (-#literal) }
+    } else {
+        quote! { #literal }
+    }
+}
+
+fn float_literal(value: f64) -> proc_macro2::TokenStream {
+    let literal = proc_macro2::Literal::f64_unsuffixed(value.abs());
+    if value < 0.0 {
+        quote! { (-#literal) }
+    } else {
+        quote! { #literal }
+    }
+}
+
+// bits と range(...) から arbitrary で生成する整数の範囲 (下限, 上限) を求める関数
+#[cfg(feature = "arbitrary")]
+fn int_bounds(
+    validation: &StringValidation,
+    base_type: &Type,
+    type_str: &str,
+) -> (proc_macro2::TokenStream, proc_macro2::TokenStream) {
+    // 型の範囲を超えるリテラルはコンパイルエラーになるため、型の範囲に収める
+    let (type_min, type_max) = match type_str {
+        "u8" => (0, u8::MAX as i128),
+        "u16" => (0, u16::MAX as i128),
+        "u32" => (0, u32::MAX as i128),
+        "u64" => (0, u64::MAX as i128),
+        "u128" => (0, i128::MAX),
+        "i8" => (i8::MIN as i128, i8::MAX as i128),
+        "i16" => (i16::MIN as i128, i16::MAX as i128),
+        "i32" => (i32::MIN as i128, i32::MAX as i128),
+        "i64" => (i64::MIN as i128, i64::MAX as i128),
+        _ => (i128::MIN, i128::MAX),
+    };
+    let mut lo: Option<i128> = None;
+    let mut hi: Option<i128> = None;
+    if let Some(bits) = validation.bits.filter(|_| type_str.starts_with('u')) {
+        hi = i128::try_from((1u128 << bits) - 1).ok();
+    }
+    for (bound, lit) in &validation.range {
+        let Lit::Int(int) = lit else { continue };
+        let Ok(limit) = int.base10_parse::<i128>() else { continue };
+        match bound {
+            RangeBound::Ge => lo = Some(lo.map_or(limit, |lo| lo.max(limit))),
+            RangeBound::Gt => lo = Some(lo.map_or(limit + 1, |lo| lo.max(limit + 1))),
+            RangeBound::Le => hi = Some(hi.map_or(limit, |hi| hi.min(limit))),
+            RangeBound::Lt => hi = Some(hi.map_or(limit - 1, |hi| hi.min(limit - 1))),
+        }
+    }
+    let mut lo = match lo {
+        Some(lo) => int_literal(lo.clamp(type_min, type_max)),
+        None => quote! { #base_type::MIN },
+    };
+    let mut hi = match hi {
+        Some(hi) => int_literal(hi.clamp(type_min, type_max)),
+        None => quote! { #base_type::MAX },
+    };
+    // 定数で指定された境界は値が生成時にわからないため、実行時に狭める
+    for (bound, path) in &validation.range_consts {
+        match bound {
+            RangeBound::Ge => lo = quote! { #base_type::max(#lo, #path) },
+            RangeBound::Gt => lo = quote! { #base_type::max(#lo, #path + 1) },
+            RangeBound::Le => hi = quote! { #base_type::min(#hi, #path) },
+            RangeBound::Lt => hi = quote! { #base_type::min(#hi, #path - 1) },
+        }
+    }
+    (lo, hi)
+}
+
+// time_format を省略した場合の時刻の書式
+const DEFAULT_TIME_FORMAT: &str = "%H:%M:%S";
+
+// enum_name のフィールドの値からバリアントを Option で求める式を生成する関数
+fn enum_from_name(enum_path: &syn::Path, case_insensitive: bool) -> proc_macro2::TokenStream {
+    if case_insensitive {
+        quote! { <#enum_path as raw_struct::EnumNames>::from_name_ignore_case(value) }
+    } else {
+        quote! { <#enum_path as raw_struct::EnumNames>::from_name(value) }
+    }
+}
+
+// 日付の上限・下限を chrono::NaiveDate として求める式を生成する関数
+fn date_bound_expr(bound: &DateBound) -> proc_macro2::TokenStream {
+    match bound {
+        DateBound::Date(year, month, day) => quote! { raw_struct::chrono::NaiveDate::from_ymd_opt(#year, #month, #day).unwrap() },
+        DateBound::Today => quote! { raw_struct::chrono::Utc::now().date_naive() },
+    }
+}
+
+// 必須項目の未入力と、宣言された型として解析できない場合のエラーコードを求める関数
+//
+// validate_categorized() でこれらのコードを structural、それ以外を semantic に振り分ける。
+// code = "..." の指定があると検査の種類をコードで区別できないため、何も返さない
+fn structural_codes(field: &syn::Field, validation: &StringValidation) -> Vec<&'static str> {
+    if validation.code.is_some() {
+        return Vec::new();
+    }
+    let base_type = get_inner_type(&field.ty).unwrap_or(&field.ty);
+    let type_str = quote!(#base_type).to_string();
+    let is_integer = matches!(type_str.as_str(), "u8" | "u16" | "u32" | "u64" | "u128" | "i8" | "i16" | "i32" | "i64" | "i128");
+    let type_codes: &[&'static str] = match type_str.as_str() {
+        _ if is_integer => &["not_a_number", "not_an_integer"],
+        "f32" | "f64" => &["not_a_number", "not_finite"],
+        "bool" => &["not_a_bool"],
+        "String" => &[],
+        _ if validation.enum_name.is_some() => &["unknown_variant"],
+        _ if is_naive_time(base_type) => &["invalid_time"],
+        _ if validation.uuid.is_some() => &["invalid_uuid"],
+        _ if validation.timezone => &["invalid_timezone"],
+        _ if validation.glob => &["invalid_glob"],
+        _ => &["invalid_value"],
+    };
+    let mut codes = std::iter::once("required").chain(type_codes.iter().copied()).collect::<Vec<_>>();
+    // 整数の型の範囲を超える値も out_of_range になるため、範囲の制約がなければ型の誤りとして扱う
+    if is_integer && validation.range.is_empty() && validation.range_consts.is_empty() && validation.bits.is_none() {
+        codes.push("out_of_range");
+    }
+    codes
+}
+
+// rules_markdown() の「制約」の列に載せる説明を属性から組み立てる補助関数
+//
+// 正規化 (trim / default など) や行をまたがないエラーの扱い (code / max_errors) は値の制約ではないため載せない
+fn rule_descriptions(validation: &StringValidation) -> Vec<String> {
+    let mut rules = Vec::new();
+    let (noun, unit) = match validation.length_unit {
+        LengthUnit::Chars | LengthUnit::Graphemes => ("文字数", "文字"),
+        LengthUnit::Bytes => ("バイト数", "バイト"),
+        LengthUnit::Width => ("表示幅", "桁"),
+    };
+    match (validation.min_length, validation.max_length) {
+        (Some(min), Some(max)) => rules.push(format!("{} {} ～ {} {}", noun, min, max, unit)),
+        (Some(min), None) => rules.push(format!("{} {} {}以上", noun, min, unit)),
+        (None, Some(max)) => rules.push(format!("{} {} {}以下", noun, max, unit)),
+        (None, None) => {},
+    }
+    if let Some(bytes) = validation.db_varchar {
+        rules.push(format!("UTF-8 で {} バイト以下", bytes));
+    }
+    let bound_condition = |bound: &RangeBound| match bound {
+        RangeBound::Ge => "以上",
+        RangeBound::Le => "以下",
+        RangeBound::Gt => "より大きい値",
+        RangeBound::Lt => "より小さい値",
+    };
+    for (bound, lit) in &validation.range {
+        rules.push(format!("{} {}", quote!(#lit), bound_condition(bound)));
+    }
+    for (bound, path) in &validation.range_consts {
+        rules.push(format!("{} {}", quote!(#path).to_string().replace(' ', ""), bound_condition(bound)));
+    }
+    if let Some(bits) = validation.bits {
+        rules.push(format!("{} ビットに収まる値", bits));
+    }
+    if let Some(step) = &validation.step {
+        rules.push(format!("{} 刻み", quote!(#step)));
+    }
+    if !validation.one_of.is_empty() {
+        let allowed = validation.one_of.iter().map(|lit| quote!(#lit).to_string()).collect::<Vec<_>>();
+        rules.push(format!("次のいずれか: {}", allowed.join(", ")));
+    }
+    if let Some(path) = &validation.enum_name {
+        let ignore_case = if validation.case_insensitive { " (大文字・小文字を区別しない)" } else { "" };
+        rules.push(format!("{} のバリアント名{}", quote!(#path).to_string().replace(' ', ""), ignore_case));
+    }
+    if let Some(expr) = &validation.in_set {
+        rules.push(format!("{} に含まれる値", quote!(#expr).to_string().replace(' ', "")));
+    }
+    match validation.regex_any.as_slice() {
+        [] => {},
+        [pattern] => rules.push(format!("正規表現 `{}` に一致", pattern)),
+        patterns => {
+            let shown = patterns.iter().map(|pattern| format!("`{}`", pattern)).collect::<Vec<_>>();
+            rules.push(format!("正規表現 {} のいずれかに一致", shown.join(" / ")));
+        },
+    }
+    if let Some(format_by) = &validation.format_by {
+        rules.push(format!("{} の値に応じた形式", format_by.field.value()));
+    }
+    if let Some(digits) = validation.digits {
+        rules.push(format!("{} 桁の数字", digits));
+    }
+    if let Some(prefix) = &validation.starts_with {
+        rules.push(format!("「{}」で始まる", prefix));
+    }
+    if let Some(suffix) = &validation.ends_with {
+        rules.push(format!("「{}」で終わる", suffix));
+    }
+    if !validation.file_ext.is_empty() {
+        rules.push(format!("拡張子: {}", validation.file_ext.join(", ")));
+    }
+    if let Some(kana) = validation.kana {
+        rules.push(match kana {
+            Kana::Katakana => "全角カタカナ",
+            Kana::HalfWidthKatakana => "半角カタカナ",
+            Kana::Hiragana => "ひらがな",
+        }.to_string());
+    }
+    if let Some(password) = &validation.password {
+        let mut required = Vec::new();
+        if password.require_upper { required.push("英大文字"); }
+        if password.require_lower { required.push("英小文字"); }
+        if password.require_digit { required.push("数字"); }
+        if password.require_symbol { required.push("記号"); }
+        if required.is_empty() {
+            rules.push(format!("パスワード ({} 文字以上)", password.min_len));
+        } else {
+            rules.push(format!("パスワード ({} 文字以上、{}を含む)", password.min_len, required.join("・")));
+        }
+    }
+    if let Some(uuid) = &validation.uuid {
+        match uuid.version {
+            Some(version) => rules.push(format!("UUID (バージョン {})", version)),
+            None => rules.push("UUID".to_string()),
+        }
+    }
+    if let Some((min, max)) = &validation.date_range {
+        let shown = |bound: &DateBound| match bound {
+            DateBound::Date(year, month, day) => format!("{:04}-{:02}-{:02}", year, month, day),
+            DateBound::Today => "今日".to_string(),
+        };
+        match (min, max) {
+            (Some(min), Some(max)) => rules.push(format!("{} ～ {} の日付", shown(min), shown(max))),
+            (Some(min), None) => rules.push(format!("{} 以降の日付", shown(min))),
+            (None, Some(max)) => rules.push(format!("{} 以前の日付", shown(max))),
+            (None, None) => {},
+        }
+    }
+    if let Some(same_as) = &validation.same_as {
+        rules.push(format!("{} と同じ値", same_as.value()));
+    }
+    if !validation.requires.is_empty() {
+        let names = validation.requires.iter().map(|name| name.value()).collect::<Vec<_>>();
+        rules.push(format!("値がある場合は {} も必須", names.join(", ")));
+    }
+    let flags = [
+        (validation.phone.as_ref().is_some_and(|phone| !phone.normalize), "電話番号"),
+        (validation.phone.as_ref().is_some_and(|phone| phone.normalize), "電話番号 (E.164 形式に揃える)"),
+        (validation.semver, "semver のバージョン"),
+        (validation.semver_req, "semver のバージョン要求"),
+        (validation.timezone, "IANA のタイムゾーン名"),
+        (validation.glob, "glob パターン"),
+        (validation.business_day, "営業日 (土日と休日を除く)"),
+        (validation.mac.is_some(), "MAC アドレス"),
+        (validation.socket.is_some(), "ソケットアドレス (ホスト:ポート)"),
+        (validation.jp_postal_code, "郵便番号 (123-4567)"),
+        (validation.hex_color.is_some(), "カラーコード (#RRGGBB)"),
+        (validation.card_expiry.is_some(), "カードの有効期限 (MM/YY)"),
+        (validation.luhn, "Luhn のチェックディジット"),
+        (validation.iban, "IBAN"),
+        (validation.hostname, "ホスト名 (RFC 1123)"),
+        (validation.no_leading_zeros, "先頭に余分な 0 を付けない"),
+        (validation.no_surrounding_whitespace, "前後に空白を含まない"),
+        (validation.present_non_blank, "空白だけの値は不可"),
+        (validation.no_control_chars.is_some(), "制御文字を含まない"),
+        (validation.nfc, "NFC 正規化済み"),
+        (validation.monotonic, "前の行の値より大きい"),
+        (validation.unique, "列の中で重複しない"),
+    ];
+    rules.extend(flags.iter().filter(|(enabled, _)| *enabled).map(|(_, rule)| rule.to_string()));
+    rules
+}
+
+// phone(region = "JP") の地域を phonenumber::parse() に渡す式を生成する補助関数
+fn phone_region(phone: &PhoneValidation) -> proc_macro2::TokenStream {
+    match &phone.region {
+        Some(region) => quote! { Some(raw_struct::phonenumber::country::Id::#region) },
+        None => quote! { None },
+    }
+}
+
+// 電話番号 (value: &str) を E.164 形式の Option<String> に変換する式を生成する補助関数
+// (有効な電話番号として解釈できなければ None)
+fn phone_e164(phone: &PhoneValidation, value: proc_macro2::TokenStream) -> proc_macro2::TokenStream {
+    let region = phone_region(phone);
+    quote! {
+        raw_struct::phonenumber::parse(#region, #value)
+            .ok()
+            .filter(raw_struct::phonenumber::is_valid)
+            .map(|number| number.format().mode(raw_struct::phonenumber::Mode::E164).to_string())
+    }
+}
+
+// 宣言された型から FIELD_TYPES に載せる型の種類を求める補助関数
+fn field_type_tag(ty: &Type) -> &'static str {
+    let base_type = get_inner_type(ty).unwrap_or(ty);
+    match quote!(#base_type).to_string().as_str() {
+        "u8" | "u16" | "u32" | "u64" | "u128" | "i8" | "i16" | "i32" | "i64" | "i128" => "integer",
+        "f32" | "f64" => "number",
+        "bool" => "boolean",
+        _ => {
+            let name = match base_type {
+                Type::Path(type_path) => type_path.path.segments.last().map(|segment| segment.ident.to_string()),
+                _ => None,
+            };
+            match name.as_deref() {
+                Some("NaiveDate") => "date",
+                Some("NaiveTime") => "time",
+                Some("NaiveDateTime" | "DateTime") => "datetime",
+                _ => "string",
+            }
+        },
+    }
+}
+
+// 型が chrono::NaiveDate (または NaiveDate) かどうかをチェックする補助関数
+fn is_naive_date(ty: &Type) -> bool {
+    if let Type::Path(type_path) = ty {
+        if let Some(segment) = type_path.path.segments.last() {
+            return segment.ident == "NaiveDate"
+        }
+    }
+    false
+}
+
+// 型が chrono::NaiveTime (または NaiveTime) かどうかをチェックする補助関数
+fn is_naive_time(ty: &Type) -> bool {
+    if let Type::Path(type_path) = ty {
+        if let Some(segment) = type_path.path.segments.last() {
+            return segment.ident == "NaiveTime"
+        }
+    }
+    false
+}
 
-    TokenStream::from(expanded)
+// 名前からフィールドを探す補助関数
+fn find_field<'a>(
+    fields: &'a syn::punctuated::Punctuated<syn::Field, syn::token::Comma>,
+    name: &str,
+) -> Option<&'a syn::Field> {
+    fields
+        .iter()
+        .find(|f| f.ident.as_ref().is_some_and(|ident| ident == name))
 }
 
 // 型がOption<T>かどうかをチェックする補助関数
 fn is_option_type(ty: &Type) -> bool {
     if let Type::Path(type_path) = ty {
-        if let Some(segment) = type_path.path.segments.first() {
+        if let Some(segment) = type_path.path.segments.last() {
             return segment.ident == "Option"
         }
     }
@@ -285,7 +5357,7 @@ fn is_option_type(ty: &Type) -> bool {
 // Option<T>の内部の型を取得する補助関数
 fn get_inner_type(ty: &Type) -> Option<&Type> {
     if let Type::Path(type_path) = ty {
-        if let Some(segment) = type_path.path.segments.first() {
+        if let Some(segment) = type_path.path.segments.last() {
             if segment.ident == "Option" {
                 if let PathArguments::AngleBracketed(args) = &segment.arguments {
                     if let Some(GenericArgument::Type(inner_type)) = args.args.first() {
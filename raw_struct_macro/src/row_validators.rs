@@ -0,0 +1,277 @@
+// 複数の行にまたがる検査 (単調増加・重複・外れ値) を行う型を生成する関数
+use quote::{format_ident, quote};
+use super::{field_error_key, float_literal, get_inner_type, is_option_type, numeric_as_f64, raw_field_ref, ContainerOptions, MethodNames, StringValidation};
+
+// monotonic 指定のフィールドを行ごとに検査する {Struct}SequenceValidator を生成する関数
+pub(crate) fn sequence_validator(
+    struct_name: &syn::Ident,
+    raw_struct_ident: &syn::Ident,
+    fields: &syn::punctuated::Punctuated<syn::Field, syn::token::Comma>,
+    validations: &[StringValidation],
+    method_names: &[MethodNames],
+    serde_names: bool,
+) -> proc_macro2::TokenStream {
+    let monotonic_fields = fields
+        .iter()
+        .zip(validations)
+        .zip(method_names)
+        .filter(|((_, validation), _)| validation.monotonic)
+        .map(|((f, _), names)| (f, &names.getter))
+        .collect::<Vec<_>>();
+    if monotonic_fields.is_empty() {
+        return quote! {};
+    }
+
+    let validator_ident = format_ident!("{}SequenceValidator", struct_name);
+    // 状態のフィールド名やローカル変数名にはスネークケースにしたメソッド名を使う
+    let last_fields = monotonic_fields.iter().map(|(f, getter)| {
+        let last = format_ident!("last_{}", getter);
+        let field_type = &f.ty;
+        let base_type = get_inner_type(field_type).unwrap_or(field_type);
+        quote! { #last: Option<#base_type> }
+    });
+    let checks = monotonic_fields.iter().map(|(f, getter)| {
+        let last = format_ident!("last_{}", getter);
+        let error_key = field_error_key(f, serde_names);
+        let current = if is_option_type(&f.ty) {
+            quote! { raw.#getter()? }
+        } else {
+            quote! { Some(raw.#getter()?) }
+        };
+        quote! {
+            let #getter = #current;
+            if let (Some(value), Some(last)) = (&#getter, &self.#last) {
+                if value <= last {
+                    let mut err = validator::ValidationError::new("not_increasing");
+                    err.message = Some(format!("フィールド '{}' の値 ({}) が前の行の値 ({}) より大きくありません",
+                        #error_key,
+                        value,
+                        last
+                    ).into());
+                    return Err(err);
+                }
+            }
+        }
+    });
+    // すべてのフィールドが通った場合のみ値を記憶する
+    let updates = monotonic_fields.iter().map(|(_, getter)| {
+        let last = format_ident!("last_{}", getter);
+        quote! {
+            if let Some(value) = #getter {
+                self.#last = Some(value);
+            }
+        }
+    });
+    let doc = format!(" `{}` の monotonic 指定のフィールドが行ごとに単調増加しているかを検査する。", raw_struct_ident);
+
+    quote! {
+        #[doc = #doc]
+        #[derive(Debug, Default)]
+        pub struct #validator_ident {
+            #(#last_fields,)*
+        }
+
+        impl #validator_ident {
+            /// まだ1行も検査していない状態で作成する。
+            pub fn new() -> Self {
+                Self::default()
+            }
+
+            /// 次の行を検査し、前の行の値以下であればエラーを返す。
+            ///
+            /// エラーになった値は記憶しないため、以降の行は直前の正しい値と比較される。
+            pub fn check(&mut self, raw: &#raw_struct_ident) -> Result<(), validator::ValidationError> {
+                #(#checks)*
+                #(#updates)*
+                Ok(())
+            }
+        }
+    }
+}
+
+// unique 指定のフィールドの重複を検査する {Struct}UniqueValidator を生成する関数
+pub(crate) fn unique_validator(
+    struct_name: &syn::Ident,
+    raw_struct_ident: &syn::Ident,
+    fields: &syn::punctuated::Punctuated<syn::Field, syn::token::Comma>,
+    validations: &[StringValidation],
+    method_names: &[MethodNames],
+    options: &ContainerOptions,
+) -> proc_macro2::TokenStream {
+    let unique_fields = fields
+        .iter()
+        .zip(validations)
+        .zip(method_names)
+        .filter(|((_, validation), _)| validation.unique)
+        .map(|((f, _), names)| (f, &names.getter))
+        .collect::<Vec<_>>();
+    if unique_fields.is_empty() {
+        return quote! {};
+    }
+
+    let validator_ident = format_ident!("{}UniqueValidator", struct_name);
+    let seen_fields = unique_fields.iter().map(|(_, getter)| {
+        let seen = format_ident!("seen_{}", getter);
+        quote! { #seen: std::collections::HashSet<String> }
+    });
+    // 型変換前の文字列で比較するため、未入力の値は重複として扱わない
+    let checks = unique_fields.iter().map(|(f, getter)| {
+        let field_ref = raw_field_ref(quote! { raw }, f, options.strict_required);
+        let seen = format_ident!("seen_{}", getter);
+        let error_key = field_error_key(f, options.serde_names);
+        quote! {
+            if let Some(value) = #field_ref.filter(|v| !v.is_empty()) {
+                if !self.#seen.insert(value.clone()) {
+                    let mut err = validator::ValidationError::new("duplicate");
+                    err.message = Some(format!("フィールド '{}' の値 ({}) は前の行と重複しています",
+                        #error_key,
+                        value
+                    ).into());
+                    errors.add(#error_key, err);
+                    if !self.duplicates.iter().any(|(key, v)| *key == #error_key && v == value) {
+                        self.duplicates.push((#error_key, value.clone()));
+                    }
+                }
+            }
+        }
+    });
+    let doc = format!(" `{}` の unique 指定のフィールドがデータ全体で重複していないかを検査する。", raw_struct_ident);
+
+    quote! {
+        #[doc = #doc]
+        #[derive(Debug, Default)]
+        pub struct #validator_ident {
+            #(#seen_fields,)*
+            // 重複が見つかった (フィールド名, 値) を最初に見つかった順に記憶する
+            duplicates: Vec<(&'static str, String)>,
+        }
+
+        impl #validator_ident {
+            /// まだ1行も検査していない状態で作成する。
+            pub fn new() -> Self {
+                Self::default()
+            }
+
+            /// 次の行を検査し、それまでの行と値が重複していればエラーを返す。
+            pub fn check(&mut self, raw: &#raw_struct_ident) -> Result<(), validator::ValidationErrors> {
+                let mut errors = validator::ValidationErrors::new();
+                #(#checks)*
+                if errors.is_empty() {
+                    Ok(())
+                } else {
+                    Err(errors)
+                }
+            }
+
+            /// すべての行を検査し終えたあと、重複していた値をまとめて返す。
+            pub fn finish(self) -> Result<(), validator::ValidationErrors> {
+                let mut errors = validator::ValidationErrors::new();
+                for (key, value) in self.duplicates {
+                    let mut err = validator::ValidationError::new("duplicate");
+                    err.message = Some(format!("フィールド '{}' の値 ({}) が複数の行に存在します", key, value).into());
+                    errors.add(key, err);
+                }
+                if errors.is_empty() {
+                    Ok(())
+                } else {
+                    Err(errors)
+                }
+            }
+        }
+    }
+}
+
+// outlier 指定のフィールドの外れ値を検出する {Struct}OutlierDetector を生成する関数
+//
+// 平均と標準偏差はすべての行を読み終えるまで決まらないため、値を溜めておいて最後に判定する
+pub(crate) fn outlier_detector(
+    struct_name: &syn::Ident,
+    raw_struct_ident: &syn::Ident,
+    fields: &syn::punctuated::Punctuated<syn::Field, syn::token::Comma>,
+    validations: &[StringValidation],
+    method_names: &[MethodNames],
+) -> syn::Result<proc_macro2::TokenStream> {
+    let outlier_fields = fields
+        .iter()
+        .zip(validations)
+        .zip(method_names)
+        .filter_map(|((f, validation), names)| validation.outlier.map(|sigma| (f, sigma, &names.getter)))
+        .collect::<Vec<_>>();
+    if outlier_fields.is_empty() {
+        return Ok(quote! {});
+    }
+
+    let detector_ident = format_ident!("{}OutlierDetector", struct_name);
+    let values_fields = outlier_fields.iter().map(|(_, _, getter)| {
+        let values = format_ident!("values_{}", getter);
+        quote! { #values: Vec<(usize, f64)> }
+    });
+    // 変換できない値や未入力の値は集計に含めない (それらは validate() でエラーになる)
+    let accumulates = outlier_fields.iter().map(|(f, _, getter)| {
+        let values = format_ident!("values_{}", getter);
+        let value_var = format_ident!("value");
+        let as_f64 = numeric_as_f64(f, &value_var).ok_or_else(|| {
+            syn::Error::new_spanned(&f.ty, "outlier は数値型のフィールドにのみ指定できます")
+        })?;
+        let pattern = if is_option_type(&f.ty) {
+            quote! { Ok(Some(value)) }
+        } else {
+            quote! { Ok(value) }
+        };
+        Ok(quote! {
+            if let #pattern = raw.#getter() {
+                self.#values.push((self.rows, #as_f64));
+            }
+        })
+    }).collect::<syn::Result<Vec<_>>>()?;
+    let detections = outlier_fields.iter().map(|(_, sigma, getter)| {
+        let values = format_ident!("values_{}", getter);
+        let sigma = float_literal(*sigma);
+        quote! {
+            if !self.#values.is_empty() {
+                let count = self.#values.len() as f64;
+                let mean = self.#values.iter().map(|(_, value)| value).sum::<f64>() / count;
+                let stddev = (self.#values.iter().map(|(_, value)| (value - mean).powi(2)).sum::<f64>() / count).sqrt();
+                rows.extend(
+                    self.#values
+                        .iter()
+                        .filter(|(_, value)| (value - mean).abs() > #sigma * stddev)
+                        .map(|(row, _)| *row),
+                );
+            }
+        }
+    });
+    let doc = format!(" `{}` の outlier 指定のフィールドについて、データ全体の平均から外れた値を持つ行を検出する。", raw_struct_ident);
+
+    Ok(quote! {
+        #[doc = #doc]
+        #[derive(Debug, Default)]
+        pub struct #detector_ident {
+            // accumulate() を呼んだ行の数 (次の行の行番号)
+            rows: usize,
+            #(#values_fields,)*
+        }
+
+        impl #detector_ident {
+            /// まだ1行も集計していない状態で作成する。
+            pub fn new() -> Self {
+                Self::default()
+            }
+
+            /// 次の行の値を集計に加える。行番号は呼び出した順の 0 始まりの番号になる。
+            pub fn accumulate(&mut self, raw: &#raw_struct_ident) {
+                #(#accumulates)*
+                self.rows += 1;
+            }
+
+            /// いずれかのフィールドが平均から標準偏差の sigma 倍より離れている行の番号を昇順で返す。
+            pub fn outliers(&self) -> Vec<usize> {
+                let mut rows = Vec::new();
+                #(#detections)*
+                rows.sort_unstable();
+                rows.dedup();
+                rows
+            }
+        }
+    })
+}
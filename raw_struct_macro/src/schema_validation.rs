@@ -0,0 +1,124 @@
+// jsonschema feature で生成する validate_against_schema() の実装
+use quote::quote;
+use super::{field_error_key, get_inner_type, present_value, radix_digits, ContainerOptions, FlattenField, StringValidation};
+
+// 外部の JSON Schema で生データを検証する validate_against_schema() を生成する関数
+pub(crate) fn schema_validation_impl(
+    raw_struct_ident: &syn::Ident,
+    fields: &syn::punctuated::Punctuated<syn::Field, syn::token::Comma>,
+    flatten_fields: &[FlattenField],
+    validations: &[StringValidation],
+    headers: &[String],
+    options: &ContainerOptions,
+) -> proc_macro2::TokenStream {
+    let error_keys = fields.iter().map(|f| field_error_key(f, options.serde_names)).collect::<Vec<_>>();
+    let flatten_idents = flatten_fields.iter().map(|flatten| &flatten.field.ident);
+    let flatten_prefixes = flatten_fields.iter().map(|flatten| &flatten.prefix);
+    // json_schema() の "integer" / "number" / "boolean" に合わせて、解析できる値は JSON の数値・真偽値にする
+    // (解析できない値は文字列のまま渡し、スキーマの型の誤りとして報告させる)
+    let schema_values = fields.iter().zip(validations).zip(headers).map(|((f, validation), header)| {
+        let base_type = get_inner_type(&f.ty).unwrap_or(&f.ty);
+        let parsed = match quote!(#base_type).to_string().as_str() {
+            "u8" | "u16" | "u32" | "u64" | "u128" | "i8" | "i16" | "i32" | "i64" | "i128" => {
+                let (digits, signed, unsigned) = match validation.radix {
+                    Some(radix) => (
+                        radix_digits(radix),
+                        quote! { i64::from_str_radix(digits, #radix) },
+                        quote! { u64::from_str_radix(digits, #radix) },
+                    ),
+                    None => {
+                        let digits = if validation.coerce_float {
+                            quote! { let digits = value.split_once('.').map_or(value.as_str(), |(int_part, _)| int_part); }
+                        } else {
+                            quote! { let digits = value.as_str(); }
+                        };
+                        (digits, quote! { digits.parse::<i64>() }, quote! { digits.parse::<u64>() })
+                    },
+                };
+                quote! {{
+                    #digits
+                    #signed
+                        .map(serde_json::Value::from)
+                        .or_else(|_| #unsigned.map(serde_json::Value::from))
+                        .ok()
+                }}
+            },
+            "f32" | "f64" => quote! {
+                value.parse::<f64>().ok().and_then(serde_json::Number::from_f64).map(serde_json::Value::Number)
+            },
+            "bool" => quote! {
+                match value.to_lowercase().as_str() {
+                    "true" | "1" => Some(serde_json::Value::Bool(true)),
+                    "false" | "0" => Some(serde_json::Value::Bool(false)),
+                    _ => None,
+                }
+            },
+            _ => quote! { None },
+        };
+        let present_value = present_value(quote! { self }, f, options.strict_required);
+        quote! {
+            if let Some(value) = #present_value {
+                let parsed: Option<serde_json::Value> = #parsed;
+                instance.insert(format!("{}{}", prefix, #header), parsed.unwrap_or_else(|| serde_json::Value::from(value.as_str())));
+            }
+        }
+    }).collect::<Vec<_>>();
+
+    quote! {
+        impl #raw_struct_ident {
+            /// 列名をキー、列の値を値とする JSON オブジェクトを外部の JSON Schema で検証する。
+            ///
+            /// 整数・浮動小数点数・真偽値のフィールドは、解析できれば JSON の数値・真偽値として渡すため、
+            /// json_schema() で生成したスキーマをそのまま使える。それ以外の値と解析できない値は文字列のまま渡す。
+            /// 未入力の列 (Option の数値などの空文字を含む) はキーごと省略する。
+            /// 属性によるチェックは行わないため、validate() と併用する。
+            /// 列に対応しないエラー (flatten したフィールドの列を含む) やスキーマ自体が不正な場合は
+            /// "__schema__" をキーとして返す。
+            pub fn validate_against_schema(&self, schema: &serde_json::Value) -> Result<(), validator::ValidationErrors> {
+                let mut instance = serde_json::Map::new();
+                self.__schema_instance("", &mut instance);
+                let instance = serde_json::Value::Object(instance);
+
+                let mut errors = validator::ValidationErrors::new();
+                let validator = match jsonschema::validator_for(schema) {
+                    Ok(validator) => validator,
+                    Err(e) => {
+                        let mut err = validator::ValidationError::new("__schema__");
+                        err.message = Some(format!("JSON Schema が正しくありません: {}", e).into());
+                        errors.add("__schema__", err);
+                        return Err(errors);
+                    }
+                };
+                for error in validator.iter_errors(&instance) {
+                    // 必須の列がない場合は位置がルートになるため、不足している列名を使う
+                    let column = match (error.instance_path().as_str().split('/').nth(1), error.kind()) {
+                        (Some(column), _) => column.to_string(),
+                        (None, jsonschema::error::ValidationErrorKind::Required { property }) => {
+                            property.as_str().unwrap_or_default().to_string()
+                        },
+                        (None, _) => String::new(),
+                    };
+                    let key = match column.as_str() {
+                        #(#headers => #error_keys,)*
+                        _ => "__schema__",
+                    };
+                    let mut err = validator::ValidationError::new(key);
+                    err.message = Some(format!("フィールド '{}' が JSON Schema を満たしません: {}", key, error).into());
+                    errors.add(key, err);
+                }
+                if errors.is_empty() {
+                    Ok(())
+                } else {
+                    Err(errors)
+                }
+            }
+
+            // validate_against_schema() で検証する JSON オブジェクトに、prefix を付けた列名で値を追加する
+            #[doc(hidden)]
+            pub fn __schema_instance(&self, prefix: &str, instance: &mut serde_json::Map<String, serde_json::Value>) {
+                #(#schema_values)*
+                #(self.#flatten_idents.__schema_instance(&format!("{}{}", prefix, #flatten_prefixes), instance);)*
+            }
+        }
+    }
+}
@@ -0,0 +1,177 @@
+// 構造体全体の値から決まるチェック (raw_struct(sum / percent_sum / within_tolerance / checksum)) を生成する関数
+use quote::{format_ident, quote};
+use super::{error_code, field_error_key, field_error_name, float_literal, is_option_type, numeric_as_f64, raw_field_ref, ContainerOptions, MethodNames, StringValidation};
+
+// raw_struct(sum(...))・raw_struct(percent_sum(...))・raw_struct(within_tolerance(...)) のチェックを生成する関数
+//
+// 各フィールドを宣言された型に変換してから f64 で合計を比べる。
+// 変換できないフィールドや未入力のフィールドがある場合は、そのフィールドのエラーに任せてチェックしない
+pub(crate) fn sum_checks(
+    fields: &syn::punctuated::Punctuated<syn::Field, syn::token::Comma>,
+    validations: &[StringValidation],
+    method_names: &[MethodNames],
+    options: &ContainerOptions,
+) -> syn::Result<Vec<proc_macro2::TokenStream>> {
+    // 名前から (フィールドの位置, 値を Option で取り出す式, f64 に変換する式) を求める
+    let operand = |attr_name: &str, name: &syn::LitStr, var: &syn::Ident| {
+        let index = fields
+            .iter()
+            .position(|f| f.ident.as_ref().is_some_and(|ident| ident == &name.value()))
+            .ok_or_else(|| syn::Error::new(name.span(), format!("{} で指定されたフィールド '{}' が存在しません", attr_name, name.value())))?;
+        let f = &fields[index];
+        let as_f64 = numeric_as_f64(f, var).ok_or_else(|| {
+            syn::Error::new(name.span(), format!("{} で指定されたフィールド '{}' は数値型である必要があります", attr_name, name.value()))
+        })?;
+        let getter = &method_names[index].getter;
+        let value = if is_option_type(&f.ty) {
+            quote! { self.#getter() }
+        } else {
+            quote! { self.#getter().map(Some) }
+        };
+        Ok((index, value, as_f64))
+    };
+
+    let mut checks = options.sums.iter().map(|sum| {
+        let addend_vars = (0..sum.addends.len()).map(|i| format_ident!("addend_{}", i)).collect::<Vec<_>>();
+        let total_var = format_ident!("total");
+        let addends = sum.addends.iter().zip(&addend_vars).map(|(name, var)| operand("sum", name, var)).collect::<syn::Result<Vec<_>>>()?;
+        let (total_index, total_value, total_f64) = operand("sum", &sum.equals, &total_var)?;
+        let addend_values = addends.iter().map(|(_, value, _)| value);
+        let addend_f64s = addends.iter().map(|(_, _, as_f64)| as_f64);
+        let total_field = &fields[total_index];
+        let error_key = field_error_key(total_field, options.serde_names);
+        let code_sum_mismatch = error_code(&validations[total_index], "sum_mismatch");
+        let addend_names = addends
+            .iter()
+            .map(|(index, _, _)| field_error_name(&fields[*index], options.serde_names))
+            .collect::<Vec<_>>()
+            .join(" + ");
+        let epsilon = float_literal(sum.epsilon);
+        Ok(quote! {
+            if let (#(Ok(Some(#addend_vars)),)* Ok(Some(#total_var))) = (#(#addend_values,)* #total_value) {
+                let sum = 0.0 #(+ #addend_f64s)*;
+                if (sum - #total_f64).abs() > #epsilon {
+                    let mut err = validator::ValidationError::new(#code_sum_mismatch);
+                    err.message = Some(format!("フィールド '{}' の値 ({}) が {} の合計 ({}) と一致しません",
+                        #error_key,
+                        #total_var,
+                        #addend_names,
+                        sum
+                    ).into());
+                    errors.add(#error_key, err);
+                }
+            }
+        })
+    }).collect::<syn::Result<Vec<_>>>()?;
+
+    // percent_sum は特定のフィールドではなくグループ全体のエラーとして "stocks+bonds+cash" のようなキーに付ける
+    let percent_sum_checks = options.percent_sums.iter().map(|percent_sum| {
+        let vars = (0..percent_sum.fields.len()).map(|i| format_ident!("percent_{}", i)).collect::<Vec<_>>();
+        let operands = percent_sum.fields.iter().zip(&vars).map(|(name, var)| operand("percent_sum", name, var)).collect::<syn::Result<Vec<_>>>()?;
+        let values = operands.iter().map(|(_, value, _)| value);
+        let as_f64s = operands.iter().map(|(_, _, as_f64)| as_f64);
+        let group_key = operands
+            .iter()
+            .map(|(index, _, _)| field_error_name(&fields[*index], options.serde_names))
+            .collect::<Vec<_>>()
+            .join("+");
+        let group_names = group_key.replace('+', " + ");
+        let total = float_literal(percent_sum.total);
+        let epsilon = float_literal(percent_sum.epsilon);
+        Ok(quote! {
+            if let (#(Ok(Some(#vars)),)*) = (#(#values,)*) {
+                let sum = 0.0 #(+ #as_f64s)*;
+                if (sum - #total).abs() > #epsilon {
+                    let mut err = validator::ValidationError::new("percent_sum_mismatch");
+                    err.message = Some(format!("{} の合計 ({}) が {} になっていません", #group_names, sum, #total).into());
+                    err.add_param("fields".into(), &#group_key);
+                    err.add_param("total".into(), &#total);
+                    err.add_param("sum".into(), &sum);
+                    errors.add(#group_key, err);
+                }
+            }
+        })
+    }).collect::<syn::Result<Vec<_>>>()?;
+
+    // 基準値からのずれを基準値の絶対値に対する割合で比べる (基準値が 0 なら一致する場合のみ通す)
+    let tolerance_checks = options.tolerances.iter().map(|tolerance| {
+        let actual_var = format_ident!("actual");
+        let expected_var = format_ident!("expected");
+        let (actual_index, actual_value, actual_f64) = operand("within_tolerance", &tolerance.actual, &actual_var)?;
+        let (expected_index, expected_value, expected_f64) = operand("within_tolerance", &tolerance.expected, &expected_var)?;
+        let actual_field = &fields[actual_index];
+        let error_key = field_error_key(actual_field, options.serde_names);
+        let expected_name = field_error_name(&fields[expected_index], options.serde_names);
+        let code_out_of_tolerance = error_code(&validations[actual_index], "out_of_tolerance");
+        let pct = float_literal(tolerance.pct);
+        Ok(quote! {
+            if let (Ok(Some(#actual_var)), Ok(Some(#expected_var))) = (#actual_value, #expected_value) {
+                let base = #expected_f64;
+                let allowed = base.abs() * #pct / 100.0;
+                if (#actual_f64 - base).abs() > allowed {
+                    let mut err = validator::ValidationError::new(#code_out_of_tolerance);
+                    err.message = Some(format!("フィールド '{}' の値 ({}) が {} の値 ({}) の ±{}% の範囲（{} ～ {}）にありません",
+                        #error_key,
+                        #actual_var,
+                        #expected_name,
+                        #expected_var,
+                        #pct,
+                        base - allowed,
+                        base + allowed
+                    ).into());
+                    errors.add(#error_key, err);
+                }
+            }
+        })
+    }).collect::<syn::Result<Vec<_>>>()?;
+
+    checks.extend(percent_sum_checks);
+    checks.extend(tolerance_checks);
+    Ok(checks)
+}
+
+// raw_struct(checksum(...)) のチェックを生成する関数 (CRC-32 の計算は crc32fast クレート (checksum feature) を使う)
+//
+// 解析前の文字列をそのまま連結して計算し、未入力のフィールドは空文字として扱う。
+// チェックサムのフィールドは 16 進数 (0x は省略可、大文字・小文字は区別しない) として比べる
+pub(crate) fn checksum_checks(
+    fields: &syn::punctuated::Punctuated<syn::Field, syn::token::Comma>,
+    validations: &[StringValidation],
+    options: &ContainerOptions,
+) -> syn::Result<Vec<proc_macro2::TokenStream>> {
+    let lookup = |name: &syn::LitStr| {
+        fields
+            .iter()
+            .position(|f| f.ident.as_ref().is_some_and(|ident| ident == &name.value()))
+            .ok_or_else(|| syn::Error::new(name.span(), format!("checksum で指定されたフィールド '{}' が存在しません", name.value())))
+    };
+
+    options.checksums.iter().map(|checksum| {
+        let over_refs = checksum.over.iter().map(|name| {
+            let index = lookup(name)?;
+            Ok(raw_field_ref(quote! { self }, &fields[index], options.strict_required))
+        }).collect::<syn::Result<Vec<_>>>()?;
+        let index = lookup(&checksum.field)?;
+        let target = &fields[index];
+        let target_ref = raw_field_ref(quote! { self }, target, options.strict_required);
+        let error_key = field_error_key(target, options.serde_names);
+        let code_checksum_mismatch = error_code(&validations[index], "checksum_mismatch");
+        Ok(quote! {
+            if let Some(value) = #target_ref.filter(|v| !v.is_empty()) {
+                let mut hasher = raw_struct::crc32fast::Hasher::new();
+                #(hasher.update(#over_refs.map(String::as_str).unwrap_or("").as_bytes());)*
+                let expected = hasher.finalize();
+                let hex = value.strip_prefix("0x").or_else(|| value.strip_prefix("0X")).unwrap_or(value);
+                if u32::from_str_radix(hex, 16).ok() != Some(expected) {
+                    let mut err = validator::ValidationError::new(#code_checksum_mismatch);
+                    err.message = Some(format!("フィールド '{}' の値 ({}) がチェックサム ({:08x}) と一致しません",
+                        #error_key,
+                        value,
+                        expected
+                    ).into());
+                    errors.add(#error_key, err);
+                }
+            }
+        })
+    }).collect()
+}
@@ -0,0 +1,89 @@
+// arbitrary_valid() は宣言された型と制約を満たす生データを生成する
+// (cargo test --features arbitrary,phone,semver,uuid で実行する)
+#![cfg(all(feature = "arbitrary", feature = "phone", feature = "semver", feature = "uuid"))]
+use raw_struct::RawStruct;
+use validator::Validate;
+
+#[derive(RawStruct, Debug)]
+pub struct Account {
+    #[validate(min_length = 2, max_length = 8)]
+    pub name: String,
+    #[validate(range(gt = 0.0, lt = 1.0))]
+    pub ratio: f64,
+    #[validate(range(gt = 0, lt = 10), step = 0.5)]
+    pub score: Option<f32>,
+    #[validate(digits = 4)]
+    pub branch: u16,
+    #[validate(uuid(version = 4))]
+    pub id: String,
+    #[validate(phone(region = "JP"))]
+    pub tel: Option<String>,
+    #[validate(semver)]
+    pub version: Option<String>,
+    #[validate(iban)]
+    pub iban: Option<String>,
+    #[validate(luhn, digits = 16)]
+    pub card: Option<String>,
+    #[validate(hex_color)]
+    pub color: Option<String>,
+    #[validate(mac)]
+    pub mac: Option<String>,
+    #[validate(hostname)]
+    pub host: Option<String>,
+    #[validate(jp_postal_code)]
+    pub postal_code: Option<String>,
+    #[validate(password(min_len = 8, require_upper, require_digit, require_symbol))]
+    pub password: Option<String>,
+    #[validate(same_as = "password")]
+    pub password_confirm: Option<String>,
+    #[validate(starts_with = "SKU-", max_length = 10)]
+    pub sku: Option<String>,
+    #[validate(file_ext = ["csv"])]
+    pub file: Option<String>,
+    #[validate(katakana)]
+    pub kana: Option<String>,
+    #[validate(socket(port_range = "1024..=65535"))]
+    pub addr: Option<String>,
+}
+
+// 固定の種から作ったバイト列で何度も生成し、すべて validate() を通ることを確かめる
+fn generate(seed: u64) -> arbitrary::Result<RawAccount> {
+    let mut state = seed;
+    let bytes = (0..4096)
+        .map(|_| {
+            state = state.wrapping_mul(6364136223846793005).wrapping_add(1442695040888963407);
+            (state >> 56) as u8
+        })
+        .collect::<Vec<_>>();
+    RawAccount::arbitrary_valid(&mut arbitrary::Unstructured::new(&bytes))
+}
+
+#[test]
+fn generated_values_pass_validation() {
+    for seed in 0..200 {
+        let raw = generate(seed).unwrap_or_else(|e| panic!("seed {} で生成に失敗しました: {}", seed, e));
+        assert!(raw.validate().is_ok(), "{:?}", raw);
+    }
+}
+
+#[test]
+fn exclusive_float_bounds_are_not_generated() {
+    for seed in 0..200 {
+        let raw = generate(seed).unwrap();
+        let ratio = raw.ratio.unwrap().parse::<f64>().unwrap();
+        assert!(ratio > 0.0 && ratio < 1.0, "{}", ratio);
+        if let Some(score) = raw.score {
+            let score = score.parse::<f32>().unwrap();
+            assert!(score > 0.0 && score < 10.0, "{}", score);
+        }
+    }
+}
+
+#[test]
+fn formatted_fields_are_generated_in_their_format() {
+    let raw = generate(1).unwrap();
+    let id = raw.id.unwrap();
+    assert_eq!(id.len(), 36);
+    assert_eq!(&id[14..15], "4");
+    assert_eq!(raw.branch.unwrap().len(), 4);
+}
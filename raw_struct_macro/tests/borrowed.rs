@@ -0,0 +1,58 @@
+// raw_struct(borrowed) は入力を借用する RawXxxRef<'a> を生成し、所有する生データ型と同じ検証を行う
+use raw_struct::csv::StringRecord;
+use raw_struct::RawStruct;
+use validator::Validate;
+
+#[derive(RawStruct, Debug)]
+#[raw_struct(borrowed)]
+pub struct Order {
+    pub id: u32,
+    #[validate(min_length = 1, max_length = 8)]
+    pub sku: String,
+    #[validate(requires = "sku")]
+    pub note: Option<String>,
+}
+
+fn headers() -> StringRecord {
+    StringRecord::from(vec!["id", "sku", "note"])
+}
+
+#[test]
+fn deserializes_by_borrowing_the_record() {
+    let record = StringRecord::from(vec!["7", "AB-1", "fragile"]);
+    let headers = headers();
+    let raw: RawOrderRef = record.deserialize(Some(&headers)).unwrap();
+    assert_eq!(raw.id, Some("7"));
+    assert_eq!(raw.sku, Some("AB-1"));
+    assert_eq!(raw.note, Some("fragile"));
+    assert!(raw.validate().is_ok());
+}
+
+#[test]
+fn field_checks_run_on_borrowed_values() {
+    let raw = RawOrderRef { id: Some("7"), sku: Some("TOO-LONG-SKU"), note: None };
+    let errors = raw.validate().unwrap_err();
+    assert_eq!(errors.field_errors()["sku"][0].code, "invalid_length");
+
+    let raw = RawOrderRef { id: Some("x"), sku: Some("AB-1"), note: None };
+    assert_eq!(raw.validate().unwrap_err().field_errors()["id"][0].code, "not_a_number");
+}
+
+#[test]
+fn cross_field_checks_run_on_borrowed_values() {
+    let raw = RawOrderRef { id: Some("7"), sku: None, note: Some("fragile") };
+    let errors = raw.validate().unwrap_err();
+    // 借用版でも所有する生データ型と同じエラーになる
+    let owned_errors = RawOrder::from(&raw).validate().unwrap_err();
+    assert!(!errors.is_empty());
+    assert_eq!(errors, owned_errors);
+}
+
+#[test]
+fn converts_to_owned_struct() {
+    let raw = RawOrderRef { id: Some("7"), sku: Some("AB-1"), note: None };
+    let order = Order::try_from(raw).unwrap();
+    assert_eq!(order.id, 7);
+    assert_eq!(order.sku, "AB-1");
+    assert_eq!(order.note, None);
+}
@@ -0,0 +1,57 @@
+// validate(business_day) は土日の日付をエラーにし、validate_with() では config に登録された休日もエラーにする
+// (cargo test --features chrono で実行する)
+#![cfg(feature = "chrono")]
+use raw_struct::chrono::NaiveDate;
+use raw_struct::{RawStruct, ValidationConfig};
+use validator::Validate;
+
+#[derive(RawStruct, Debug)]
+pub struct Settlement {
+    #[validate(business_day)]
+    pub date: NaiveDate,
+    #[validate(business_day)]
+    pub value_date: Option<NaiveDate>,
+}
+
+fn raw(date: &str, value_date: Option<&str>) -> RawSettlement {
+    RawSettlement {
+        date: Some(date.to_string()),
+        value_date: value_date.map(str::to_string),
+    }
+}
+
+#[test]
+fn weekdays_pass() {
+    // 2024-01-08 は月曜日、2024-01-12 は金曜日
+    assert!(raw("2024-01-08", Some("2024-01-12")).validate().is_ok());
+    assert!(raw("2024-01-10", None).validate().is_ok());
+}
+
+#[test]
+fn weekend_is_error() {
+    // 2024-01-13 は土曜日、2024-01-14 は日曜日
+    let errors = raw("2024-01-13", Some("2024-01-14")).validate().unwrap_err();
+    let field_errors = errors.field_errors();
+    assert_eq!(field_errors["date"][0].code, "not_business_day");
+    assert_eq!(field_errors["value_date"][0].code, "not_business_day");
+}
+
+#[test]
+fn holidays_are_checked_only_with_config() {
+    // 2024-01-08 (成人の日) は月曜日なので、config がなければ営業日として扱う
+    let raw = raw("2024-01-08", Some("2024-01-09"));
+    assert!(raw.validate().is_ok());
+
+    let config = ValidationConfig::new().holidays(["2024-01-01", "2024-01-08"]);
+    let errors = raw.validate_with(&config).unwrap_err();
+    let field_errors = errors.field_errors();
+    assert_eq!(field_errors["date"][0].code, "not_business_day");
+    assert!(!field_errors.contains_key("value_date"));
+}
+
+#[test]
+fn unparsable_date_is_not_business_day_error() {
+    let config = ValidationConfig::new().holidays(["2024-01-08"]);
+    let errors = raw("2024-13-01", None).validate_with(&config).unwrap_err();
+    assert_ne!(errors.field_errors()["date"][0].code, "not_business_day");
+}
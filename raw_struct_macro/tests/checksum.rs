@@ -0,0 +1,50 @@
+// raw_struct(checksum(...)) は over のフィールドを連結した CRC-32 と field の値を比べる
+// (cargo test --features checksum で実行する)
+#![cfg(feature = "checksum")]
+use raw_struct::RawStruct;
+use validator::Validate;
+
+#[derive(RawStruct, Debug)]
+#[raw_struct(checksum(over = ["code", "quantity"], field = "crc", algo = "crc32"))]
+pub struct Line {
+    pub code: String,
+    pub quantity: Option<u32>,
+    pub crc: Option<String>,
+}
+
+fn raw(code: &str, quantity: Option<&str>, crc: Option<&str>) -> RawLine {
+    RawLine {
+        code: Some(code.to_string()),
+        quantity: quantity.map(str::to_string),
+        crc: crc.map(str::to_string),
+    }
+}
+
+#[test]
+fn matching_checksum_passes() {
+    // CRC-32("A-100150") = 0x1112acd4
+    assert!(raw("A-1001", Some("50"), Some("1112acd4")).validate().is_ok());
+    // 0x の接頭辞と大文字の 16 進数も受け付ける
+    assert!(raw("A-1001", Some("50"), Some("0x1112ACD4")).validate().is_ok());
+}
+
+#[test]
+fn missing_field_is_hashed_as_empty() {
+    // CRC-32("A-1001") = 0xaef3df42
+    assert!(raw("A-1001", None, Some("aef3df42")).validate().is_ok());
+}
+
+#[test]
+fn mismatched_checksum_is_error() {
+    let errors = raw("A-1001", Some("51"), Some("1112acd4")).validate().unwrap_err();
+    assert_eq!(errors.field_errors()["crc"][0].code, "checksum_mismatch");
+
+    let errors = raw("A-1001", Some("50"), Some("not hex")).validate().unwrap_err();
+    assert_eq!(errors.field_errors()["crc"][0].code, "checksum_mismatch");
+}
+
+#[test]
+fn blank_checksum_is_not_checked() {
+    assert!(raw("A-1001", Some("50"), None).validate().is_ok());
+    assert!(raw("A-1001", Some("50"), Some("")).validate().is_ok());
+}
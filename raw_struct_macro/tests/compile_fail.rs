@@ -0,0 +1,22 @@
+// 属性の誤りがコンパイルエラーになることを trybuild で確かめる
+#[test]
+fn ui() {
+    let t = trybuild::TestCases::new();
+    t.compile_fail("tests/ui/*.rs");
+}
+
+// feature を有効にしていない場合のエラーは、その feature がないときだけ確かめる
+#[cfg(not(feature = "uuid"))]
+#[test]
+fn missing_feature() {
+    let t = trybuild::TestCases::new();
+    t.compile_fail("tests/ui_features/*.rs");
+}
+
+// arbitrary_valid() で値を組み立てられない制約のエラーは、arbitrary feature があるときだけ確かめる
+#[cfg(feature = "arbitrary")]
+#[test]
+fn arbitrary_unsupported() {
+    let t = trybuild::TestCases::new();
+    t.compile_fail("tests/ui_arbitrary/*.rs");
+}
@@ -0,0 +1,61 @@
+// export_in で作ったモジュールから、生成したすべての項目とトレイトを取り込める
+mod record {
+    use raw_struct::RawStruct;
+
+    #[derive(RawStruct, Debug)]
+    #[raw_struct(export_in = "raw", borrowed, free_fn)]
+    pub struct Order {
+        #[validate(unique)]
+        pub id: u32,
+        #[validate(monotonic)]
+        pub seq: u32,
+        #[validate(outlier(sigma = 3.0))]
+        pub amount: f64,
+    }
+}
+
+use record::raw::*;
+
+fn raw(id: &str, seq: &str) -> RawOrder {
+    let mut raw = RawOrder::default();
+    raw.set_field("id", Some(id.to_string()));
+    raw.set_field("seq", Some(seq.to_string()));
+    raw.set_field("amount", Some("1.5".to_string()));
+    raw
+}
+
+#[test]
+fn generated_items_are_exported() {
+    let first: RawOfOrder = raw("1", "1");
+    assert!(first.validate().is_ok());
+    assert!(validate_raw_order(&first).is_ok());
+    assert_eq!(first.fields().count(), 3);
+    assert_eq!(OrderField::Seq as usize, 1);
+
+    let mut unique = OrderUniqueValidator::new();
+    assert!(unique.check(&first).is_ok());
+    assert!(unique.check(&raw("1", "2")).is_err());
+
+    let mut sequence = OrderSequenceValidator::new();
+    assert!(sequence.check(&first).is_ok());
+    assert!(sequence.check(&raw("2", "1")).is_err());
+
+    let _ = OrderOutlierDetector::new();
+    let borrowed = RawOrderRef { id: Some("1"), seq: Some("1"), amount: Some("1.5") };
+    assert!(borrowed.validate().is_ok());
+}
+
+fn assert_has_raw<T: HasRaw<Raw = RawOrder>>() {}
+
+#[test]
+fn traits_are_exported() {
+    assert_has_raw::<record::Order>();
+    let order = record::Order::try_from(raw("7", "3")).unwrap();
+    assert_eq!((order.id, order.seq, order.amount), (7, 3, 1.5));
+}
+
+#[cfg(feature = "json_schema")]
+#[test]
+fn json_schema_is_exported() {
+    assert_eq!(json_schema(), RawOrder::json_schema());
+}
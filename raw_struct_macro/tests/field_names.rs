@@ -0,0 +1,45 @@
+// キーワードや生成されるメソッドと同じ名前のフィールドでもメソッドを生成できること
+use raw_struct::RawStruct;
+use validator::Validate;
+
+#[derive(RawStruct, Debug)]
+pub struct Awkward {
+    pub r#type: String,
+    #[validate(min_length = 2)]
+    pub validate: String,
+    pub fields: Option<u32>,
+}
+
+fn raw() -> RawAwkward {
+    RawAwkward {
+        r#type: Some("kind".to_string()),
+        validate: Some("ok".to_string()),
+        fields: Some("3".to_string()),
+    }
+}
+
+#[test]
+fn keyword_field_keeps_its_name() {
+    assert_eq!(raw().r#type().unwrap(), "kind");
+    assert!(RawAwkward::validate_type("kind").is_ok());
+}
+
+#[test]
+fn reserved_names_get_value_suffix() {
+    let raw = raw();
+    assert_eq!(raw.validate_value().unwrap(), "ok");
+    assert_eq!(raw.fields_value().unwrap(), Some(3));
+    assert!(RawAwkward::validate_validate_value("x").is_err());
+    assert!(RawAwkward::validate_fields_value("3").is_ok());
+    // 生成されたメソッドは元のメソッドを隠さない
+    assert!(raw.validate().is_ok());
+    assert_eq!(raw.fields().map(|(name, _)| name).collect::<Vec<_>>(), ["type", "validate", "fields"]);
+}
+
+#[test]
+fn conversion_uses_original_field_names() {
+    let record = Awkward::try_from(raw()).unwrap();
+    assert_eq!(record.r#type, "kind");
+    assert_eq!(record.validate, "ok");
+    assert_eq!(record.fields, Some(3));
+}
@@ -0,0 +1,87 @@
+// flatten した子のフィールドのエラーが "親.子" のキーで報告される
+use raw_struct::RawStruct;
+
+#[derive(RawStruct, Debug)]
+pub struct Address {
+    #[validate(min_length = 2)]
+    pub city: String,
+}
+
+#[derive(RawStruct, Debug)]
+pub struct Customer {
+    pub name: String,
+    #[raw_struct(flatten(prefix = "home_"))]
+    pub home: Address,
+}
+
+const CSV: &str = "name,home_city\nAlice,X\n";
+
+fn raw() -> RawCustomer {
+    RawCustomer {
+        name: Some("Alice".to_string()),
+        home: RawAddress { city: Some("X".to_string()) },
+    }
+}
+
+#[test]
+fn load_all_reports_nested_error() {
+    let rdr = raw_struct::csv::Reader::from_reader(CSV.as_bytes());
+    let errors = raw_struct::load_all::<Customer, _>(rdr).unwrap_err();
+    let fields = errors.iter().map(|(_, field, _)| field).collect::<Vec<_>>();
+    assert_eq!(fields, ["home.city"]);
+}
+
+#[test]
+fn collect_errors_reports_nested_error() {
+    let mut rdr = raw_struct::csv::Reader::from_reader(CSV.as_bytes());
+    let errors = raw_struct::collect_errors::<RawCustomer, _>(&mut rdr);
+    let fields = errors.iter().map(|(_, field, _)| field).collect::<Vec<_>>();
+    assert_eq!(fields, ["home.city"]);
+}
+
+#[test]
+fn split_keeps_nested_error() {
+    let errors = validator::Validate::validate(&raw()).unwrap_err();
+    let categorized = raw_struct::CategorizedErrors::split(errors, |_, _| false);
+    let semantic = raw_struct::flat_field_errors(&categorized.semantic);
+    assert_eq!(semantic.len(), 1);
+    assert_eq!(semantic[0].0, "home.city");
+    assert_eq!(semantic[0].1[0].code, "too_short");
+    assert!(categorized.structural.is_empty());
+
+    let categorized = raw().validate_categorized().unwrap_err();
+    assert_eq!(raw_struct::flat_field_errors(&categorized.semantic)[0].0, "home.city");
+}
+
+#[test]
+fn validate_with_source_annotates_nested_error() {
+    let errors = raw().validate_with_source("Alice,X", 2).unwrap_err();
+    let flat = raw_struct::flat_field_errors(&errors);
+    assert_eq!(flat.len(), 1);
+    assert_eq!(flat[0].0, "home.city");
+    let err = &flat[0].1[0];
+    assert_eq!(err.params["row"], 2);
+    assert_eq!(err.params["source"], "Alice,X");
+}
+
+#[test]
+fn observer_sees_nested_error() {
+    let mut seen = Vec::new();
+    let result = raw().validate_with_observer(&mut |field, raw, _| seen.push((field.to_string(), raw.to_string())));
+    assert!(result.is_err());
+    assert_eq!(seen, [("home.city".to_string(), "X".to_string())]);
+}
+
+#[test]
+fn csv_round_trip_uses_prefixed_headers() {
+    let mut wtr = raw_struct::csv::Writer::from_writer(Vec::new());
+    // 同じ型を2回書き出しても、列名は同じものを使い回す
+    wtr.serialize(raw()).unwrap();
+    wtr.serialize(raw()).unwrap();
+    let written = String::from_utf8(wtr.into_inner().unwrap()).unwrap();
+    assert_eq!(written, "name,home_city\nAlice,X\nAlice,X\n");
+
+    let mut rdr = raw_struct::csv::Reader::from_reader(written.as_bytes());
+    let read = rdr.deserialize::<RawCustomer>().next().unwrap().unwrap();
+    assert_eq!(read.home.city.as_deref(), Some("X"));
+}
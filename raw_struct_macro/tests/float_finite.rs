@@ -0,0 +1,42 @@
+// 浮動小数点数のフィールドで NaN / 無限大を not_finite にするチェック
+use raw_struct::RawStruct;
+use validator::Validate;
+
+#[derive(RawStruct, Debug)]
+pub struct Measurement {
+    pub single: f32,
+    pub double: f64,
+    pub optional_single: Option<f32>,
+    pub optional_double: Option<f64>,
+}
+
+fn raw(value: &str) -> RawMeasurement {
+    RawMeasurement {
+        single: Some(value.to_string()),
+        double: Some(value.to_string()),
+        optional_single: Some(value.to_string()),
+        optional_double: Some(value.to_string()),
+    }
+}
+
+#[test]
+fn non_finite_values_are_rejected() {
+    for value in ["NaN", "inf", "-inf"] {
+        let errors = raw(value).validate().unwrap_err();
+        let field_errors = errors.field_errors();
+        for field in ["single", "double", "optional_single", "optional_double"] {
+            assert_eq!(field_errors[field][0].code, "not_finite", "{} の {}", field, value);
+        }
+        assert!(Measurement::try_from(raw(value)).is_err(), "{}", value);
+    }
+}
+
+#[test]
+fn finite_value_passes() {
+    assert!(raw("1.5").validate().is_ok());
+    let record = Measurement::try_from(raw("1.5")).unwrap();
+    assert_eq!(record.single, 1.5);
+    assert_eq!(record.double, 1.5);
+    assert_eq!(record.optional_single, Some(1.5));
+    assert_eq!(record.optional_double, Some(1.5));
+}
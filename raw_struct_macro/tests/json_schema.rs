@@ -0,0 +1,52 @@
+// json_schema() で生成したスキーマで validate_against_schema() が通る
+// (cargo test --features json_schema,jsonschema で実行する)
+#![cfg(all(feature = "json_schema", feature = "jsonschema"))]
+use raw_struct::RawStruct;
+
+#[derive(RawStruct, Debug)]
+pub struct Dimensions {
+    pub width: f64,
+}
+
+#[derive(RawStruct, Debug)]
+pub struct Product {
+    #[validate(range(min = 1))]
+    pub id: u32,
+    pub name: String,
+    pub in_stock: bool,
+    pub discount: Option<i32>,
+    #[raw_struct(flatten(prefix = "box_"))]
+    pub dimensions: Dimensions,
+}
+
+fn raw(id: &str, in_stock: &str, discount: &str, width: &str) -> RawProduct {
+    RawProduct {
+        id: Some(id.to_string()),
+        name: Some("Widget".to_string()),
+        in_stock: Some(in_stock.to_string()),
+        discount: Some(discount.to_string()),
+        dimensions: RawDimensions { width: Some(width.to_string()) },
+    }
+}
+
+#[test]
+fn valid_row_matches_own_schema() {
+    let schema = RawProduct::json_schema();
+    assert!(raw("7", "true", "-5", "1.5").validate_against_schema(&schema).is_ok());
+    // 空文字の Option の数値は未入力として省略する
+    assert!(raw("7", "0", "", "2").validate_against_schema(&schema).is_ok());
+}
+
+#[test]
+fn unparseable_values_are_reported() {
+    let schema = RawProduct::json_schema();
+    let errors = raw("abc", "yes", "1", "1.5").validate_against_schema(&schema).unwrap_err();
+    let fields = errors.field_errors();
+    assert!(fields.contains_key("id"));
+    assert!(fields.contains_key("in_stock"));
+    assert!(!fields.contains_key("discount"));
+
+    // 数値として解析した値にもスキーマの制約がかかる
+    let errors = raw("0", "true", "1", "1.5").validate_against_schema(&schema).unwrap_err();
+    assert!(errors.field_errors().contains_key("id"));
+}
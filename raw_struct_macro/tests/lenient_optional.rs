@@ -0,0 +1,62 @@
+// into_lenient_optional() は Option のフィールドの解析できない値を None にして警告し、必須のフィールドはエラーにする
+use raw_struct::RawStruct;
+
+#[derive(RawStruct, Debug)]
+pub struct Dimensions {
+    pub width: u32,
+    pub depth: Option<u32>,
+}
+
+#[derive(RawStruct, Debug)]
+pub struct Item {
+    pub id: u32,
+    pub price: Option<f64>,
+    pub note: Option<String>,
+    #[raw_struct(flatten)]
+    pub size: Dimensions,
+}
+
+fn raw(id: Option<&str>, price: Option<&str>, width: &str, depth: &str) -> RawItem {
+    RawItem {
+        id: id.map(str::to_string),
+        price: price.map(str::to_string),
+        note: Some("memo".to_string()),
+        size: RawDimensions {
+            width: Some(width.to_string()),
+            depth: Some(depth.to_string()),
+        },
+    }
+}
+
+#[test]
+fn valid_values_convert_without_warnings() {
+    let (item, warnings) = raw(Some("1"), Some("9.5"), "10", "20").into_lenient_optional().unwrap();
+    assert!(warnings.is_empty());
+    assert_eq!((item.id, item.price, item.note.as_deref()), (1, Some(9.5), Some("memo")));
+    assert_eq!((item.size.width, item.size.depth), (10, Some(20)));
+}
+
+#[test]
+fn invalid_optional_values_become_none_with_warnings() {
+    let (item, warnings) = raw(Some("1"), Some("cheap"), "10", "deep").into_lenient_optional().unwrap();
+    assert_eq!(item.price, None);
+    assert_eq!(item.size.depth, None);
+    let fields = warnings.iter().map(|warning| (warning.field, warning.value.as_str())).collect::<Vec<_>>();
+    assert_eq!(fields, [("price", "cheap"), ("depth", "deep")]);
+}
+
+#[test]
+fn invalid_required_values_are_errors() {
+    let errors = raw(Some("x"), Some("1.0"), "10", "20").into_lenient_optional().unwrap_err();
+    assert_eq!(errors.field_errors()["id"][0].code, "not_a_number");
+
+    let errors = raw(None, Some("1.0"), "10", "20").into_lenient_optional().unwrap_err();
+    assert_eq!(errors.field_errors()["id"][0].code, "required");
+}
+
+#[test]
+fn invalid_child_required_value_is_nested_error() {
+    let errors = raw(Some("1"), None, "wide", "20").into_lenient_optional().unwrap_err();
+    assert!(errors.errors().contains_key("size"));
+    assert!(!errors.errors().contains_key("id"));
+}
@@ -0,0 +1,44 @@
+// try_into_lossy() は default の値を検証せずに解析だけして補う
+use raw_struct::RawStruct;
+
+pub const MAX_QUANTITY: u32 = 10;
+
+#[derive(RawStruct, Debug)]
+#[raw_struct(lossy)]
+pub struct Stock {
+    // 定数の範囲は生成時に値がわからないため、範囲外の既定値もコンパイルエラーにならない
+    #[validate(range(max_const = "crate::MAX_QUANTITY"), default = "20")]
+    pub quantity: u32,
+    #[validate(default = "")]
+    pub note: Option<u32>,
+    #[validate(default = "7")]
+    pub shelf: Option<u8>,
+}
+
+#[test]
+fn default_outside_const_range_does_not_panic() {
+    let raw = RawStock {
+        quantity: Some("x".to_string()),
+        note: Some("y".to_string()),
+        shelf: Some("z".to_string()),
+    };
+    let (stock, report) = raw.try_into_lossy();
+    assert_eq!(stock.quantity, 20);
+    assert_eq!(stock.note, None);
+    assert_eq!(stock.shelf, Some(7));
+    assert_eq!(report.len(), 3);
+    assert_eq!(report[0].params["field"], "quantity");
+    assert_eq!(report[0].params["value"], "x");
+}
+
+#[test]
+fn valid_values_are_kept() {
+    let raw = RawStock {
+        quantity: Some("3".to_string()),
+        note: None,
+        shelf: Some("1".to_string()),
+    };
+    let (stock, report) = raw.try_into_lossy();
+    assert_eq!((stock.quantity, stock.note, stock.shelf), (3, None, Some(1)));
+    assert!(report.is_empty());
+}
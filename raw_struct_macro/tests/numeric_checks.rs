@@ -0,0 +1,116 @@
+// 必須の数値フィールドの range / one_of / bits / step / coerce_float は解析した値に対して検査する
+use raw_struct::RawStruct;
+use validator::Validate;
+
+#[derive(RawStruct, Debug)]
+pub struct Reading {
+    #[validate(range(min = 1, max = 10))]
+    pub inclusive: u32,
+    #[validate(range(gt = 0, lt = 10))]
+    pub exclusive: i32,
+    #[validate(range(min = -10, lt = -2))]
+    pub negative: i64,
+    #[validate(one_of = [1, 2, 5])]
+    pub allowed: u8,
+    #[validate(bits = 4)]
+    pub nibble: u16,
+    #[validate(step = 0.25)]
+    pub quarter: f64,
+    #[validate(coerce_float)]
+    pub count: u32,
+}
+
+fn valid() -> RawReading {
+    RawReading {
+        inclusive: Some("5".to_string()),
+        exclusive: Some("5".to_string()),
+        negative: Some("-5".to_string()),
+        allowed: Some("2".to_string()),
+        nibble: Some("3".to_string()),
+        quarter: Some("1.25".to_string()),
+        count: Some("3".to_string()),
+    }
+}
+
+// field を value にした生データの、そのフィールドのエラーコード (エラーがなければ None) を返す
+fn code(field: &str, value: &str) -> Option<String> {
+    let mut raw = valid();
+    let slot = match field {
+        "inclusive" => &mut raw.inclusive,
+        "exclusive" => &mut raw.exclusive,
+        "negative" => &mut raw.negative,
+        "allowed" => &mut raw.allowed,
+        "nibble" => &mut raw.nibble,
+        "quarter" => &mut raw.quarter,
+        "count" => &mut raw.count,
+        _ => unreachable!(),
+    };
+    *slot = Some(value.to_string());
+    let errors = raw.validate().err()?;
+    Some(errors.field_errors().get(field)?[0].code.to_string())
+}
+
+#[test]
+fn valid_record_passes() {
+    assert!(valid().validate().is_ok());
+}
+
+#[test]
+fn inclusive_bounds_accept_the_edges() {
+    assert_eq!(code("inclusive", "1"), None);
+    assert_eq!(code("inclusive", "10"), None);
+    assert_eq!(code("inclusive", "0").as_deref(), Some("out_of_range"));
+    assert_eq!(code("inclusive", "11").as_deref(), Some("out_of_range"));
+}
+
+#[test]
+fn exclusive_bounds_reject_the_edges() {
+    assert_eq!(code("exclusive", "1"), None);
+    assert_eq!(code("exclusive", "9"), None);
+    assert_eq!(code("exclusive", "0").as_deref(), Some("out_of_range"));
+    assert_eq!(code("exclusive", "10").as_deref(), Some("out_of_range"));
+}
+
+#[test]
+fn negative_bounds_are_compared_as_negative_numbers() {
+    assert_eq!(code("negative", "-10"), None);
+    assert_eq!(code("negative", "-3"), None);
+    assert_eq!(code("negative", "-11").as_deref(), Some("out_of_range"));
+    assert_eq!(code("negative", "-2").as_deref(), Some("out_of_range"));
+    assert_eq!(code("negative", "10").as_deref(), Some("out_of_range"));
+}
+
+#[test]
+fn integer_one_of_accepts_only_listed_values() {
+    for value in ["1", "2", "5"] {
+        assert_eq!(code("allowed", value), None, "{}", value);
+    }
+    assert_eq!(code("allowed", "3").as_deref(), Some("not_allowed"));
+    assert_eq!(code("allowed", "0").as_deref(), Some("not_allowed"));
+}
+
+#[test]
+fn bits_accepts_up_to_the_largest_n_bit_value() {
+    assert_eq!(code("nibble", "0"), None);
+    assert_eq!(code("nibble", "15"), None);
+    assert_eq!(code("nibble", "16").as_deref(), Some("out_of_range"));
+}
+
+#[test]
+fn step_accepts_only_multiples() {
+    assert_eq!(code("quarter", "0"), None);
+    assert_eq!(code("quarter", "-0.75"), None);
+    assert_eq!(code("quarter", "1.3").as_deref(), Some("invalid_step"));
+}
+
+#[test]
+fn coerce_float_accepts_whole_number_decimals() {
+    assert_eq!(code("count", "3.0"), None);
+    assert_eq!(code("count", "3.00"), None);
+    assert_eq!(code("count", "3.5").as_deref(), Some("not_an_integer"));
+    assert_eq!(code("count", "x").as_deref(), Some("not_a_number"));
+
+    let mut raw = valid();
+    raw.count = Some("3.0".to_string());
+    assert_eq!(Reading::try_from(raw).unwrap().count, 3);
+}
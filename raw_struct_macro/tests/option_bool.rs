@@ -0,0 +1,35 @@
+// Option<bool> のフィールドの未入力・真偽値・不正な値の扱い
+use raw_struct::RawStruct;
+use validator::Validate;
+
+#[derive(RawStruct, Debug)]
+pub struct Flags {
+    pub active: Option<bool>,
+}
+
+fn raw(value: Option<&str>) -> RawFlags {
+    RawFlags { active: value.map(str::to_string) }
+}
+
+#[test]
+fn empty_is_none() {
+    for value in [None, Some("")] {
+        assert!(raw(value).validate().is_ok());
+        assert_eq!(Flags::try_from(raw(value)).unwrap().active, None);
+    }
+}
+
+#[test]
+fn bool_literals_are_some() {
+    for (value, expected) in [("true", true), ("false", false), ("1", true), ("0", false)] {
+        assert!(raw(Some(value)).validate().is_ok(), "{}", value);
+        assert_eq!(Flags::try_from(raw(Some(value))).unwrap().active, Some(expected), "{}", value);
+    }
+}
+
+#[test]
+fn other_values_are_errors() {
+    let errors = raw(Some("yes")).validate().unwrap_err();
+    assert_eq!(errors.field_errors()["active"][0].code, "not_a_bool");
+    assert!(Flags::try_from(raw(Some("yes"))).is_err());
+}
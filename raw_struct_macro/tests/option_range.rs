@@ -0,0 +1,48 @@
+// Option の数値フィールドの range は値がある場合だけ検査する
+use raw_struct::RawStruct;
+use validator::Validate;
+
+#[derive(RawStruct, Debug)]
+pub struct Reading {
+    #[validate(range(min = 1, max = 10))]
+    pub level: Option<i32>,
+    #[validate(range(min = 0.5, max = 2.5))]
+    pub ratio: Option<f64>,
+}
+
+fn raw(value: Option<&str>) -> RawReading {
+    RawReading {
+        level: value.map(str::to_string),
+        ratio: None,
+    }
+}
+
+#[test]
+fn missing_value_skips_range() {
+    for value in [None, Some("")] {
+        let raw = raw(value);
+        assert!(raw.validate().is_ok(), "{:?}", value);
+        assert_eq!(raw.level().unwrap(), None);
+        assert_eq!(Reading::try_from(raw).unwrap().level, None);
+    }
+    let blank_float = RawReading { level: None, ratio: Some(String::new()) };
+    assert!(blank_float.validate().is_ok());
+    assert_eq!(Reading::try_from(blank_float).unwrap().ratio, None);
+}
+
+#[test]
+fn value_in_range_is_some() {
+    let raw = raw(Some("7"));
+    assert!(raw.validate().is_ok());
+    assert_eq!(raw.level().unwrap(), Some(7));
+    assert_eq!(Reading::try_from(raw).unwrap().level, Some(7));
+}
+
+#[test]
+fn value_out_of_range_is_error() {
+    let raw = raw(Some("11"));
+    let errors = raw.validate().unwrap_err();
+    assert_eq!(errors.field_errors()["level"][0].code, "out_of_range");
+    assert_eq!(raw.level().unwrap_err().code, "out_of_range");
+    assert!(Reading::try_from(raw).is_err());
+}
@@ -0,0 +1,61 @@
+// OutlierDetector は outlier 指定のフィールドについて、平均から標準偏差の sigma 倍より離れた行を検出する
+use raw_struct::RawStruct;
+
+#[derive(RawStruct, Debug)]
+pub struct Measurement {
+    #[validate(outlier(sigma = 2.0))]
+    pub weight: f64,
+    #[validate(outlier(sigma = 1.0))]
+    pub count: Option<u32>,
+}
+
+fn raw(weight: &str, count: Option<&str>) -> RawMeasurement {
+    RawMeasurement {
+        weight: Some(weight.to_string()),
+        count: count.map(str::to_string),
+    }
+}
+
+#[test]
+fn no_rows_have_no_outliers() {
+    assert!(MeasurementOutlierDetector::new().outliers().is_empty());
+}
+
+#[test]
+fn uniform_values_have_no_outliers() {
+    let mut detector = MeasurementOutlierDetector::new();
+    for _ in 0..5 {
+        detector.accumulate(&raw("10.0", None));
+    }
+    assert!(detector.outliers().is_empty());
+}
+
+#[test]
+fn far_value_is_reported_by_row_number() {
+    let mut detector = MeasurementOutlierDetector::new();
+    for weight in ["10", "10", "10", "10", "10", "10", "10", "10", "10", "100"] {
+        detector.accumulate(&raw(weight, None));
+    }
+    assert_eq!(detector.outliers(), vec![9]);
+}
+
+#[test]
+fn each_field_uses_its_own_sigma() {
+    let mut detector = MeasurementOutlierDetector::new();
+    // count の平均は 2、標準偏差は約 0.82 なので、1 倍の sigma では 1 と 3 の行が外れ値になる
+    for count in ["1", "2", "3"] {
+        detector.accumulate(&raw("10", Some(count)));
+    }
+    assert_eq!(detector.outliers(), vec![0, 2]);
+}
+
+#[test]
+fn unparsable_and_missing_values_are_skipped_but_counted_as_rows() {
+    let mut detector = MeasurementOutlierDetector::new();
+    detector.accumulate(&raw("x", Some("")));
+    for _ in 0..9 {
+        detector.accumulate(&raw("10", None));
+    }
+    detector.accumulate(&raw("100", None));
+    assert_eq!(detector.outliers(), vec![10]);
+}
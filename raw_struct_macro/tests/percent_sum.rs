@@ -0,0 +1,47 @@
+// raw_struct(percent_sum(...)) は fields の合計が total と epsilon 以内で一致するかを検査し、グループのキーにエラーを付ける
+use raw_struct::RawStruct;
+use validator::Validate;
+
+#[derive(RawStruct, Debug)]
+#[raw_struct(percent_sum(fields = ["stocks", "bonds", "cash"], total = 100.0, epsilon = 0.01))]
+pub struct Allocation {
+    pub stocks: f64,
+    pub bonds: f64,
+    pub cash: Option<f64>,
+}
+
+fn raw(stocks: &str, bonds: &str, cash: Option<&str>) -> RawAllocation {
+    RawAllocation {
+        stocks: Some(stocks.to_string()),
+        bonds: Some(bonds.to_string()),
+        cash: cash.map(str::to_string),
+    }
+}
+
+#[test]
+fn sum_of_100_passes() {
+    assert!(raw("60", "30", Some("10")).validate().is_ok());
+    assert!(raw("33.33", "33.33", Some("33.34")).validate().is_ok());
+    // epsilon 以内の誤差は許容する
+    assert!(raw("33.333", "33.333", Some("33.333")).validate().is_ok());
+}
+
+#[test]
+fn other_sum_is_error_on_group_key() {
+    let errors = raw("60", "30", Some("20")).validate().unwrap_err();
+    let field_errors = errors.field_errors();
+    let err = &field_errors["stocks+bonds+cash"][0];
+    assert_eq!(err.code, "percent_sum_mismatch");
+    assert_eq!(err.params["sum"], 110.0);
+    assert_eq!(err.params["total"], 100.0);
+    assert!(!field_errors.contains_key("stocks"));
+}
+
+#[test]
+fn missing_or_unparsable_field_skips_check() {
+    assert!(raw("60", "30", None).validate().is_ok());
+    let errors = raw("60", "abc", Some("20")).validate().unwrap_err();
+    let field_errors = errors.field_errors();
+    assert!(field_errors.contains_key("bonds"));
+    assert!(!field_errors.contains_key("stocks+bonds+cash"));
+}
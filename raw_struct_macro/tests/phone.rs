@@ -0,0 +1,53 @@
+// phone(region = "JP") は地域の書式を受け付け、normalize を付けると E.164 形式に揃える
+// (cargo test --features phone で実行する)
+#![cfg(feature = "phone")]
+use raw_struct::RawStruct;
+use validator::Validate;
+
+#[derive(RawStruct, Debug)]
+pub struct Contact {
+    #[validate(phone(region = "JP"))]
+    pub tel: String,
+    #[validate(phone(region = "JP", normalize))]
+    pub mobile: Option<String>,
+}
+
+fn raw(tel: &str, mobile: Option<&str>) -> RawContact {
+    RawContact {
+        tel: Some(tel.to_string()),
+        mobile: mobile.map(str::to_string),
+    }
+}
+
+#[test]
+fn local_and_international_formats_are_valid() {
+    assert!(raw("03-1234-5678", Some("090-1234-5678")).validate().is_ok());
+    assert!(raw("+81 3 1234 5678", Some("+819012345678")).validate().is_ok());
+}
+
+#[test]
+fn invalid_number_is_error() {
+    let errors = raw("12", Some("abc")).validate().unwrap_err();
+    assert_eq!(errors.field_errors()["tel"][0].code, "invalid_phone");
+    assert_eq!(errors.field_errors()["mobile"][0].code, "invalid_phone");
+}
+
+#[test]
+fn normalize_converts_to_e164() {
+    let raw = raw("03-1234-5678", Some("090-1234-5678"));
+    let normalized = raw.clone().normalized();
+    // normalize のないフィールドは元の文字列のまま
+    assert_eq!(normalized.tel.as_deref(), Some("03-1234-5678"));
+    assert_eq!(normalized.mobile.as_deref(), Some("+819012345678"));
+
+    let contact = Contact::try_from(raw).unwrap();
+    assert_eq!(contact.tel, "03-1234-5678");
+    assert_eq!(contact.mobile.as_deref(), Some("+819012345678"));
+}
+
+#[test]
+fn normalize_keeps_invalid_value_for_validation() {
+    let normalized = raw("03-1234-5678", Some("abc")).normalized();
+    assert_eq!(normalized.mobile.as_deref(), Some("abc"));
+    assert!(normalized.validate().is_err());
+}
@@ -0,0 +1,52 @@
+// validate_with() で実行時に必須にしたフィールドは、validate() と同じ基準で未入力かどうかを判定する
+// (String のフィールドの空文字は値として扱い、それ以外の型の空文字は未入力とみなす)
+use raw_struct::{RawStruct, ValidationConfig};
+use validator::Validate;
+
+#[derive(RawStruct, Debug)]
+pub struct Contact {
+    pub name: String,
+    pub nickname: Option<String>,
+    pub age: Option<u32>,
+}
+
+fn raw(name: Option<&str>, nickname: Option<&str>, age: Option<&str>) -> RawContact {
+    RawContact {
+        name: name.map(str::to_string),
+        nickname: nickname.map(str::to_string),
+        age: age.map(str::to_string),
+    }
+}
+
+fn config() -> ValidationConfig {
+    ValidationConfig::new().require("name").require("nickname").require("age")
+}
+
+#[test]
+fn empty_string_fields_are_present() {
+    let raw = raw(Some(""), Some(""), Some("20"));
+    assert!(raw.validate().is_ok());
+    assert!(raw.validate_with(&config()).is_ok());
+}
+
+#[test]
+fn missing_fields_are_required() {
+    let errors = raw(Some("a"), None, None).validate_with(&config()).unwrap_err();
+    let field_errors = errors.field_errors();
+    assert_eq!(field_errors["nickname"][0].code, "required");
+    assert_eq!(field_errors["age"][0].code, "required");
+    assert!(!field_errors.contains_key("name"));
+}
+
+#[test]
+fn empty_non_string_field_is_required() {
+    let errors = raw(Some("a"), Some("b"), Some("")).validate_with(&config()).unwrap_err();
+    assert_eq!(errors.field_errors()["age"][0].code, "required");
+}
+
+#[test]
+fn required_field_reports_single_error() {
+    // validate() の必須エラーと重ねて報告しない
+    let errors = raw(None, Some("b"), Some("1")).validate_with(&config()).unwrap_err();
+    assert_eq!(errors.field_errors()["name"].len(), 1);
+}
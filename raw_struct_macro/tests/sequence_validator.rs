@@ -0,0 +1,56 @@
+// SequenceValidator は monotonic 指定のフィールドが行ごとに単調増加しているかを検査する
+use raw_struct::RawStruct;
+
+#[derive(RawStruct, Debug)]
+pub struct Tick {
+    #[validate(monotonic)]
+    pub seq: u64,
+    #[validate(monotonic)]
+    pub at: Option<i64>,
+}
+
+fn raw(seq: &str, at: Option<&str>) -> RawTick {
+    RawTick {
+        seq: Some(seq.to_string()),
+        at: at.map(str::to_string),
+    }
+}
+
+#[test]
+fn increasing_values_pass() {
+    let mut validator = TickSequenceValidator::new();
+    assert!(validator.check(&raw("1", Some("100"))).is_ok());
+    assert!(validator.check(&raw("2", None)).is_ok());
+    assert!(validator.check(&raw("10", Some("101"))).is_ok());
+}
+
+#[test]
+fn equal_or_smaller_value_is_error() {
+    let mut validator = TickSequenceValidator::new();
+    assert!(validator.check(&raw("5", None)).is_ok());
+    assert_eq!(validator.check(&raw("5", None)).unwrap_err().code, "not_increasing");
+    assert_eq!(validator.check(&raw("4", None)).unwrap_err().code, "not_increasing");
+}
+
+#[test]
+fn rejected_row_is_not_remembered() {
+    let mut validator = TickSequenceValidator::new();
+    assert!(validator.check(&raw("5", Some("100"))).is_ok());
+    // at が減っているため行全体がエラーになり、seq の 9 も記憶されない
+    assert!(validator.check(&raw("9", Some("50"))).is_err());
+    assert!(validator.check(&raw("6", Some("101"))).is_ok());
+}
+
+#[test]
+fn missing_optional_value_keeps_previous_value() {
+    let mut validator = TickSequenceValidator::new();
+    assert!(validator.check(&raw("1", Some("100"))).is_ok());
+    assert!(validator.check(&raw("2", None)).is_ok());
+    assert!(validator.check(&raw("3", Some("100"))).is_err());
+}
+
+#[test]
+fn unparsable_value_is_conversion_error() {
+    let mut validator = TickSequenceValidator::new();
+    assert_eq!(validator.check(&raw("x", None)).unwrap_err().code, "not_a_number");
+}
@@ -0,0 +1,50 @@
+// csv::StringRecord とヘッダの組から検証済みの構造体に変換する
+use raw_struct::csv::StringRecord;
+use raw_struct::RawStruct;
+
+#[derive(RawStruct, Debug)]
+pub struct Order {
+    pub id: u32,
+    #[validate(min_length = 1, max_length = 8)]
+    pub sku: String,
+    pub note: Option<String>,
+}
+
+fn headers() -> StringRecord {
+    StringRecord::from(vec!["id", "sku", "note"])
+}
+
+#[test]
+fn valid_row_converts() {
+    let record = StringRecord::from(vec!["7", "AB-1", ""]);
+    let order = Order::try_from((&record, &headers())).unwrap();
+    assert_eq!(order.id, 7);
+    assert_eq!(order.sku, "AB-1");
+}
+
+#[test]
+fn invalid_row_is_validation_error() {
+    let record = StringRecord::from(vec!["7", "TOO-LONG-SKU", ""]);
+    match Order::try_from((&record, &headers())) {
+        Err(raw_struct::Error::Validation(errors)) => {
+            assert!(errors.field_errors().contains_key("sku"));
+        },
+        other => panic!("検証エラーになるべき: {:?}", other),
+    }
+}
+
+#[test]
+fn length_mismatch_is_error() {
+    for record in [StringRecord::from(vec!["7", "AB-1"]), StringRecord::from(vec!["7", "AB-1", "", "extra"])] {
+        match Order::try_from((&record, &headers())) {
+            Err(raw_struct::Error::UnequalLengths { headers: 3, record: found }) => assert_eq!(found, record.len()),
+            other => panic!("列数の不一致のエラーになるべき: {:?}", other),
+        }
+    }
+}
+
+#[test]
+fn raw_alias_names_raw_type() {
+    let raw: RawOfOrder = <Order as raw_struct::HasRaw>::Raw::default();
+    let _: RawOrder = raw;
+}
@@ -0,0 +1,64 @@
+// raw_struct(sum(...)) は addends の合計が equals のフィールドと epsilon 以内で一致するかを検査する
+use raw_struct::RawStruct;
+use validator::Validate;
+
+#[derive(RawStruct, Debug)]
+#[raw_struct(sum(addends = ["subtotal", "tax"], equals = "total", epsilon = 0.01))]
+pub struct Invoice {
+    pub subtotal: f64,
+    pub tax: Option<f64>,
+    pub total: f64,
+}
+
+#[derive(RawStruct, Debug)]
+#[raw_struct(sum(addends = ["adults", "children"], equals = "guests"))]
+pub struct Booking {
+    pub adults: u32,
+    pub children: u32,
+    pub guests: u32,
+}
+
+fn raw(subtotal: &str, tax: Option<&str>, total: &str) -> RawInvoice {
+    RawInvoice {
+        subtotal: Some(subtotal.to_string()),
+        tax: tax.map(str::to_string),
+        total: Some(total.to_string()),
+    }
+}
+
+#[test]
+fn matching_sum_passes() {
+    assert!(raw("1000", Some("100"), "1100").validate().is_ok());
+    // epsilon 以内の誤差は許容する
+    assert!(raw("0.1", Some("0.2"), "0.3").validate().is_ok());
+    assert!(raw("10.00", Some("0.80"), "10.805").validate().is_ok());
+}
+
+#[test]
+fn mismatched_sum_is_error_on_total() {
+    let errors = raw("1000", Some("100"), "1000").validate().unwrap_err();
+    let field_errors = errors.field_errors();
+    assert_eq!(field_errors["total"][0].code, "sum_mismatch");
+    assert!(!field_errors.contains_key("subtotal"));
+}
+
+#[test]
+fn missing_or_unparsable_operand_skips_sum() {
+    assert!(raw("1000", None, "1").validate().is_ok());
+    let errors = raw("x", Some("100"), "1").validate().unwrap_err();
+    let field_errors = errors.field_errors();
+    assert_eq!(field_errors["subtotal"][0].code, "not_a_number");
+    assert!(!field_errors.contains_key("total"));
+}
+
+#[test]
+fn integer_sum_is_compared_exactly() {
+    let booking = |adults: &str, children: &str, guests: &str| RawBooking {
+        adults: Some(adults.to_string()),
+        children: Some(children.to_string()),
+        guests: Some(guests.to_string()),
+    };
+    assert!(booking("2", "1", "3").validate().is_ok());
+    let errors = booking("2", "1", "4").validate().unwrap_err();
+    assert_eq!(errors.field_errors()["guests"][0].code, "sum_mismatch");
+}
@@ -0,0 +1,16 @@
+// bits は符号なし整数のフィールドにしか効かないため、他の型に指定するとエラーにする
+use raw_struct::RawStruct;
+
+#[derive(RawStruct)]
+pub struct Flags {
+    #[validate(bits = 4)]
+    pub mask: i32,
+}
+
+#[derive(RawStruct)]
+pub struct Label {
+    #[validate(bits = 3)]
+    pub name: Option<String>,
+}
+
+fn main() {}
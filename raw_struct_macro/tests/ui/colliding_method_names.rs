@@ -0,0 +1,10 @@
+// fields は予約されたメソッド名のため fields_value() になり、fields_value のフィールドと重複する
+use raw_struct::RawStruct;
+
+#[derive(RawStruct)]
+pub struct Colliding {
+    pub fields: String,
+    pub fields_value: String,
+}
+
+fn main() {}
@@ -0,0 +1,11 @@
+// default の値が range の指定に反するため、定義の誤りとしてコンパイルエラーにする
+use raw_struct::RawStruct;
+
+#[derive(RawStruct)]
+#[raw_struct(lossy)]
+pub struct Stock {
+    #[validate(range(min = 1, max = 10), default = "20")]
+    pub quantity: u32,
+}
+
+fn main() {}
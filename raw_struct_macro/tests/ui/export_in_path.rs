@@ -0,0 +1,10 @@
+// export_in には別のモジュールのパスではなく、構造体と同じ場所に作るモジュールの名前を指定する
+use raw_struct::RawStruct;
+
+#[derive(RawStruct)]
+#[raw_struct(export_in = "crate::raw")]
+pub struct Account {
+    pub name: String,
+}
+
+fn main() {}
@@ -0,0 +1,10 @@
+// default の値を i32 に変換できないため、定義の誤りとしてコンパイルエラーにする
+use raw_struct::RawStruct;
+
+#[derive(RawStruct)]
+pub struct Stock {
+    #[validate(default = "abc")]
+    pub quantity: i32,
+}
+
+fn main() {}
@@ -0,0 +1,10 @@
+// 既知のキーでも値の形式が違う場合はコンパイルエラーにする
+use raw_struct::RawStruct;
+
+#[derive(RawStruct)]
+pub struct Account {
+    #[validate(min_length)]
+    pub name: String,
+}
+
+fn main() {}
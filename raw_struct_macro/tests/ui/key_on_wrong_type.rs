@@ -0,0 +1,34 @@
+// 型に合わないキーは生成するチェックから外れて黙って無視されるため、エラーにする
+use raw_struct::RawStruct;
+
+#[derive(RawStruct)]
+pub struct Card {
+    #[validate(luhn)]
+    pub number: u32,
+}
+
+#[derive(RawStruct)]
+pub struct Name {
+    #[validate(range(min = 1))]
+    pub value: String,
+}
+
+#[derive(RawStruct)]
+pub struct Server {
+    #[validate(hostname)]
+    pub enabled: bool,
+}
+
+#[derive(RawStruct)]
+pub struct Toggle {
+    #[validate(one_of = ["x"])]
+    pub flag: Option<bool>,
+}
+
+#[derive(RawStruct)]
+pub struct Ratio {
+    #[validate(step = 0.5)]
+    pub value: i32,
+}
+
+fn main() {}
@@ -0,0 +1,16 @@
+// 数値のフィールドの one_of に数値以外を指定した場合は、one_of の誤りとして示す
+use raw_struct::RawStruct;
+
+#[derive(RawStruct)]
+pub struct Level {
+    #[validate(one_of = ["a"])]
+    pub value: u32,
+}
+
+#[derive(RawStruct)]
+pub struct Ratio {
+    #[validate(one_of = [0.5, "b"])]
+    pub value: f64,
+}
+
+fn main() {}
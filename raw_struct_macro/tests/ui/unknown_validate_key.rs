@@ -0,0 +1,10 @@
+// 綴りを誤ったキーは黙って無視せず、指定可能なキーの一覧とともにコンパイルエラーにする
+use raw_struct::RawStruct;
+
+#[derive(RawStruct)]
+pub struct Account {
+    #[validate(min_lenght = 5)]
+    pub name: String,
+}
+
+fn main() {}
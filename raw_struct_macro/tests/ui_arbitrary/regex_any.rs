@@ -0,0 +1,10 @@
+// 正規表現に一致する値は組み立てられないため、arbitrary feature では regex_any をエラーにする
+use raw_struct::RawStruct;
+
+#[derive(RawStruct)]
+pub struct Product {
+    #[validate(regex_any = ["^[A-Z]{3}-[0-9]{4}$"])]
+    pub code: String,
+}
+
+fn main() {}
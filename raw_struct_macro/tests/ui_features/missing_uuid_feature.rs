@@ -0,0 +1,10 @@
+// feature が必要な指定は、解決できないパスのエラーではなく必要な feature 名を示すエラーにする
+use raw_struct::RawStruct;
+
+#[derive(RawStruct)]
+pub struct Device {
+    #[validate(uuid)]
+    pub id: String,
+}
+
+fn main() {}
@@ -0,0 +1,55 @@
+// UniqueValidator は unique 指定のフィールドの値がデータ全体で重複していないかを検査する
+use raw_struct::RawStruct;
+
+#[derive(RawStruct, Debug)]
+pub struct Member {
+    #[validate(unique)]
+    pub id: String,
+    #[validate(unique)]
+    pub email: Option<String>,
+}
+
+fn raw(id: &str, email: Option<&str>) -> RawMember {
+    RawMember {
+        id: Some(id.to_string()),
+        email: email.map(str::to_string),
+    }
+}
+
+#[test]
+fn distinct_values_pass() {
+    let mut validator = MemberUniqueValidator::new();
+    assert!(validator.check(&raw("1", Some("a@example.com"))).is_ok());
+    assert!(validator.check(&raw("2", Some("b@example.com"))).is_ok());
+    assert!(validator.finish().is_ok());
+}
+
+#[test]
+fn duplicated_value_is_error() {
+    let mut validator = MemberUniqueValidator::new();
+    assert!(validator.check(&raw("1", Some("a@example.com"))).is_ok());
+    let errors = validator.check(&raw("1", Some("b@example.com"))).unwrap_err();
+    assert_eq!(errors.field_errors()["id"][0].code, "duplicate");
+    assert!(!errors.field_errors().contains_key("email"));
+}
+
+#[test]
+fn missing_values_are_not_duplicates() {
+    let mut validator = MemberUniqueValidator::new();
+    assert!(validator.check(&raw("1", None)).is_ok());
+    assert!(validator.check(&raw("2", None)).is_ok());
+    assert!(validator.check(&raw("3", Some(""))).is_ok());
+    assert!(validator.check(&raw("4", Some(""))).is_ok());
+}
+
+#[test]
+fn finish_reports_each_duplicated_value_once() {
+    let mut validator = MemberUniqueValidator::new();
+    for id in ["1", "2", "1", "1", "2"] {
+        let _ = validator.check(&raw(id, None));
+    }
+    let errors = validator.finish().unwrap_err();
+    let id_errors = &errors.field_errors()["id"];
+    assert_eq!(id_errors.len(), 2);
+    assert!(id_errors.iter().all(|err| err.code == "duplicate"));
+}
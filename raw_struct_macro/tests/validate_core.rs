@@ -0,0 +1,73 @@
+// validate_core() は必須・文字数・数値の範囲を検査し、最初のエラーを CoreError で返す
+// (cargo test --features core_validate で実行する)
+#![cfg(feature = "core_validate")]
+use raw_struct::{CoreError, RawStruct};
+
+#[derive(RawStruct, Debug)]
+pub struct Sensor {
+    #[validate(min_length = 2, max_length = 4)]
+    pub name: String,
+    #[validate(range(min = 0, lt = 100))]
+    pub level: u8,
+    pub active: Option<bool>,
+}
+
+fn raw(name: Option<&str>, level: Option<&str>, active: Option<&str>) -> RawSensor {
+    RawSensor {
+        name: name.map(str::to_string),
+        level: level.map(str::to_string),
+        active: active.map(str::to_string),
+    }
+}
+
+#[test]
+fn valid_record_passes() {
+    assert_eq!(raw(Some("ab"), Some("99"), Some("TRUE")).validate_core(), Ok(()));
+    assert_eq!(raw(Some("abcd"), Some("0"), None).validate_core(), Ok(()));
+}
+
+#[test]
+fn missing_required_field_is_error() {
+    assert_eq!(
+        raw(None, Some("1"), None).validate_core(),
+        Err(CoreError { field: "name", code: "required" })
+    );
+}
+
+#[test]
+fn length_is_checked() {
+    assert_eq!(
+        raw(Some("a"), Some("1"), None).validate_core(),
+        Err(CoreError { field: "name", code: "too_short" })
+    );
+    assert_eq!(
+        raw(Some("abcde"), Some("1"), None).validate_core(),
+        Err(CoreError { field: "name", code: "too_long" })
+    );
+}
+
+#[test]
+fn numbers_are_parsed_and_range_checked() {
+    assert_eq!(
+        raw(Some("ab"), Some("x"), None).validate_core(),
+        Err(CoreError { field: "level", code: "not_a_number" })
+    );
+    // lt は上限の値そのものを含まない
+    assert_eq!(
+        raw(Some("ab"), Some("100"), None).validate_core(),
+        Err(CoreError { field: "level", code: "out_of_range" })
+    );
+    assert_eq!(
+        raw(Some("ab"), Some("1"), Some("yes")).validate_core(),
+        Err(CoreError { field: "active", code: "not_a_bool" })
+    );
+}
+
+#[test]
+fn first_error_is_returned() {
+    // 宣言順で最初のフィールドのエラーだけを返す
+    assert_eq!(
+        raw(Some("a"), Some("x"), None).validate_core(),
+        Err(CoreError { field: "name", code: "too_short" })
+    );
+}
@@ -0,0 +1,50 @@
+// raw_struct(within_tolerance(...)) は actual が expected の ±pct% の範囲にあるかを検査し、actual にエラーを付ける
+use raw_struct::RawStruct;
+use validator::Validate;
+
+#[derive(RawStruct, Debug)]
+#[raw_struct(within_tolerance(actual = "reading", expected = "nominal", pct = 5.0))]
+pub struct Sample {
+    pub reading: Option<f64>,
+    pub nominal: i32,
+}
+
+fn raw(reading: Option<&str>, nominal: &str) -> RawSample {
+    RawSample {
+        reading: reading.map(str::to_string),
+        nominal: Some(nominal.to_string()),
+    }
+}
+
+#[test]
+fn reading_within_tolerance_passes() {
+    assert!(raw(Some("100"), "100").validate().is_ok());
+    // 境界の値 (±5%) も範囲に含める
+    assert!(raw(Some("95"), "100").validate().is_ok());
+    assert!(raw(Some("105"), "100").validate().is_ok());
+    // 基準値が負の場合も絶対値に対する割合で比べる
+    assert!(raw(Some("-104"), "-100").validate().is_ok());
+}
+
+#[test]
+fn reading_out_of_tolerance_is_error_on_actual() {
+    for reading in ["94.9", "105.1", "-100"] {
+        let errors = raw(Some(reading), "100").validate().unwrap_err();
+        let field_errors = errors.field_errors();
+        assert_eq!(field_errors["reading"][0].code, "out_of_tolerance", "{}", reading);
+        assert!(!field_errors.contains_key("nominal"));
+    }
+}
+
+#[test]
+fn zero_expected_only_accepts_zero() {
+    assert!(raw(Some("0"), "0").validate().is_ok());
+    assert!(raw(Some("0.001"), "0").validate().is_err());
+}
+
+#[test]
+fn missing_or_unparsable_value_skips_check() {
+    assert!(raw(None, "100").validate().is_ok());
+    let errors = raw(Some("abc"), "100").validate().unwrap_err();
+    assert_ne!(errors.field_errors()["reading"][0].code, "out_of_tolerance");
+}